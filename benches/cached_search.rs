@@ -0,0 +1,41 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use glam::Vec2;
+use polyanya::{Coords, Mesh, PolyanyaFile};
+
+/// A target moving along a path across the mesh, one point every 10 units.
+fn moving_target(mesh: &Mesh) -> Vec<Coords> {
+    let (from, to) = (Vec2::new(356.0, 166.0), Vec2::new(661.0, 441.0));
+    mesh.path(from, to).unwrap().resample(mesh, from, 10.0)
+}
+
+fn follow_moving_target(c: &mut Criterion) {
+    let mesh: Mesh = PolyanyaFile::from_file("meshes/v2/aurora-merged.mesh")
+        .try_into()
+        .unwrap();
+    let from = Vec2::new(993.0, 290.0);
+    let targets = moving_target(&mesh);
+
+    c.bench_function("moving target with a new search", |b| {
+        b.iter(|| {
+            for to in &targets {
+                black_box(mesh.path(from, *to).unwrap());
+            }
+        })
+    });
+
+    let search = mesh.cached_search(from).unwrap();
+    c.bench_function("moving target with a cached search", |b| {
+        b.iter(|| {
+            for to in &targets {
+                black_box(search.path_to(*to).unwrap());
+            }
+        })
+    });
+
+    c.bench_function("cached search start", |b| {
+        b.iter(|| black_box(mesh.cached_search(from).unwrap()))
+    });
+}
+
+criterion_group!(benches, follow_moving_target);
+criterion_main!(benches);
@@ -0,0 +1,27 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use polyanya::Mesh;
+
+/// A grid with pillars every few cells, and walls with doors between rooms.
+fn rooms(size: usize) -> Vec<bool> {
+    (0..size * size)
+        .map(|index| {
+            let (x, y) = (index % size, index / size);
+            let pillar = x % 4 == 2 && y % 4 == 2;
+            let wall = (x % 16 == 0 && y % 16 != 8) || (y % 16 == 0 && x % 16 != 8);
+            !pillar && !wall
+        })
+        .collect()
+}
+
+fn grid(c: &mut Criterion) {
+    let walkable = rooms(128);
+    c.bench_function("grid rooms 128", |b| {
+        b.iter(|| {
+            let mesh = Mesh::from_grid(128, 128, &walkable).unwrap();
+            black_box(mesh);
+        })
+    });
+}
+
+criterion_group!(benches, grid);
+criterion_main!(benches);
@@ -5,7 +5,7 @@ use polyanya::{Mesh, PolyanyaFile};
 macro_rules! assert_delta {
     ($x:expr, $y:expr) => {
         let val = $x;
-        if !((val.length - $y).abs() < 0.001) {
+        if (val.length - $y).abs() >= 0.001 || val.length.is_nan() {
             assert_eq!(val.length, $y);
         }
         black_box(val);
@@ -35,5 +35,26 @@ fn get_path(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, get_path);
+fn get_distance(c: &mut Criterion) {
+    let mesh: Mesh = PolyanyaFile::from_file("meshes/v2/aurora-merged.mesh")
+        .try_into()
+        .unwrap();
+    [
+        (Vec2::new(993.0, 290.0), Vec2::new(34.0, 622.0), 1123.2226),
+        (Vec2::new(356.0, 166.0), Vec2::new(661.0, 441.0), 595.041),
+        (Vec2::new(827.0, 678.0), Vec2::new(460.0, 383.0), 605.301),
+    ]
+    .iter()
+    .for_each(|(from, to, len)| {
+        c.bench_function(&format!("get distance {from:?}"), |b| {
+            b.iter(|| {
+                let distance = mesh.distance(*from, *to).unwrap();
+                assert!((distance - *len).abs() < 0.001);
+                black_box(distance);
+            })
+        });
+    });
+}
+
+criterion_group!(benches, get_path, get_distance);
 criterion_main!(benches);
@@ -11,7 +11,7 @@ fn prepare_fullmesh(c: &mut Criterion) {
         serde_json::from_reader(File::open("meshes/recast/detail_mesh.json").unwrap()).unwrap();
     let full_mesh = RecastFullMesh::new(rasterised, detailed);
 
-    c.bench_function(&"recast - prepare fullmesh".to_string(), |b| {
+    c.bench_function("recast - prepare fullmesh", |b| {
         b.iter(|| {
             let full_mesh = full_mesh.clone();
             let mesh: Mesh = full_mesh.into();
@@ -28,9 +28,9 @@ fn find_path(c: &mut Criterion) {
     let full_mesh: polyanya::Mesh = RecastFullMesh::new(rasterised, detailed).into();
 
     let from = vec3(46.998413, 9.998184, 1.717747);
-    let to = vec3(20.703018, 18.651773, -80.770203);
+    let to = vec3(20.703018, 18.651773, -80.770_2);
 
-    c.bench_function(&"recast - find path".to_string(), |b| {
+    c.bench_function("recast - find path", |b| {
         b.iter(|| {
             let path = full_mesh.path(from.xz(), to.xz()).unwrap();
             black_box(path);
@@ -46,9 +46,9 @@ fn find_path_with_height(c: &mut Criterion) {
     let full_mesh: polyanya::Mesh = RecastFullMesh::new(rasterised, detailed).into();
 
     let from = vec3(46.998413, 9.998184, 1.717747);
-    let to = vec3(20.703018, 18.651773, -80.770203);
+    let to = vec3(20.703018, 18.651773, -80.770_2);
 
-    c.bench_function(&"recast - find path with height".to_string(), |b| {
+    c.bench_function("recast - find path with height", |b| {
         b.iter(|| {
             let path = full_mesh.path(from.xz(), to.xz()).unwrap();
             let path_with_height = path.path_with_height(from, to, &full_mesh);
@@ -65,10 +65,10 @@ fn enrich_path_with_height(c: &mut Criterion) {
     let full_mesh: polyanya::Mesh = RecastFullMesh::new(rasterised, detailed).into();
 
     let from = vec3(46.998413, 9.998184, 1.717747);
-    let to = vec3(20.703018, 18.651773, -80.770203);
+    let to = vec3(20.703018, 18.651773, -80.770_2);
     let path = full_mesh.path(from.xz(), to.xz()).unwrap();
 
-    c.bench_function(&"recast - enrich path with height".to_string(), |b| {
+    c.bench_function("recast - enrich path with height", |b| {
         b.iter(|| {
             let path_with_height = path.path_with_height(from, to, &full_mesh);
             black_box(path_with_height);
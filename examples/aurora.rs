@@ -4,7 +4,7 @@ use polyanya::{Mesh, PolyanyaFile};
 macro_rules! assert_delta {
     ($x:expr, $y:expr) => {
         let val = $x;
-        if !((val.length - $y).abs() < 0.001) {
+        if (val.length - $y).abs() >= 0.001 || val.length.is_nan() {
             assert_eq!(val.length, $y);
         }
     };
@@ -9,7 +9,7 @@ use polyanya::{Mesh, PolyanyaFile};
 macro_rules! assert_delta {
     ($x:expr, $y:expr) => {
         let val = $x;
-        if !((val - $y).abs() < 0.001) {
+        if (val - $y).abs() >= 0.001 || val.is_nan() {
             assert_eq!(val, $y);
         }
     };
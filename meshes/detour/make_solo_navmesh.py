@@ -0,0 +1,224 @@
+"""Generate solo_navmesh.bin, used by the `recast_demo_navmesh_set` test of tests/detour.rs.
+
+It's the navigation mesh set RecastDemo saves for a solo mesh, built from the Recast meshes of
+meshes/recast by following dtCreateNavMeshData, dtNavMesh::addTile and Sample::saveAll of
+recastnavigation, with the default agent and the areas and flags of Sample_SoloMesh.
+
+Run it from anywhere with `python3 meshes/detour/make_solo_navmesh.py`.
+"""
+import json
+import os
+import struct
+
+HERE = os.path.dirname(os.path.abspath(__file__))
+RECAST = os.path.join(HERE, '..', 'recast')
+
+f = lambda x: struct.unpack('<f', struct.pack('<f', x))[0]
+
+pm = json.load(open(os.path.join(RECAST, 'poly_mesh.json')))
+dm = json.load(open(os.path.join(RECAST, 'detail_mesh.json')))
+
+NVP = 6
+NULL_IDX = 0xffff
+DT_NULL_LINK = 0xffffffff
+bmin = [f(x) for x in pm['aabb']['min']]
+bmax = [f(x) for x in pm['aabb']['max']]
+cs, ch = f(pm['cell_size']), f(pm['cell_height'])
+# RecastDemo agent defaults
+walkable_height, walkable_radius, walkable_climb = 2.0, 0.6, 0.9
+
+polys = [pm['polygons'][i * NVP:(i + 1) * NVP] for i in range(len(pm['areas']))]
+npolys = len(polys)
+nverts = len(pm['vertices'])
+
+# Sample_SoloMesh: area and flags of the polygons
+GROUND, WATER, ROAD, DOOR, GRASS = 0, 1, 2, 3, 4
+WALK, SWIM, DOOR_FLAG = 0x01, 0x02, 0x04
+areas, flags = [], []
+for area in pm['areas']:
+    if area == 255 or area == 63:
+        area = GROUND
+    areas.append(area)
+    if area in (GROUND, GRASS, ROAD):
+        flags.append(WALK)
+    elif area == WATER:
+        flags.append(SWIM)
+    elif area == DOOR:
+        flags.append(WALK | DOOR_FLAG)
+    else:
+        flags.append(0)
+
+# Neighbours, like buildMeshAdjacency
+edges = {}
+for i, p in enumerate(polys):
+    vs = [v for v in p if v != NULL_IDX]
+    for j in range(len(vs)):
+        a, b = vs[j], vs[(j + 1) % len(vs)]
+        edges.setdefault((min(a, b), max(a, b)), []).append((i, j))
+neis = [[NULL_IDX] * NVP for _ in polys]
+for shared in edges.values():
+    if len(shared) == 2:
+        (p0, e0), (p1, e1) = shared
+        neis[p0][e0] = p1
+        neis[p1][e1] = p0
+
+edge_count = sum(len([v for v in p if v != NULL_IDX]) for p in polys)
+max_link_count = edge_count
+
+# Detail meshes
+detail_vert_count = 0
+detail_tri_count = 0
+for i, m in enumerate(dm['meshes']):
+    nv = len([v for v in polys[i] if v != NULL_IDX])
+    detail_vert_count += m['vertex_count'] - nv
+    detail_tri_count += m['triangle_count']
+
+
+def world_vertex(iv):
+    return [f(bmin[0] + f(iv[0] * cs)), f(bmin[1] + f(iv[1] * ch)), f(bmin[2] + f(iv[2] * cs))]
+
+
+def dist_pt_seg_2d(pt, p, q):
+    pqx, pqz = q[0] - p[0], q[2] - p[2]
+    dx, dz = pt[0] - p[0], pt[2] - p[2]
+    d = pqx * pqx + pqz * pqz
+    t = pqx * dx + pqz * dz
+    if d > 0:
+        t /= d
+    t = min(max(t, 0.0), 1.0)
+    dx = p[0] + t * pqx - pt[0]
+    dz = p[2] + t * pqz - pt[2]
+    return dx * dx + dz * dz
+
+
+def edge_flags(va, vb, vpoly):
+    # getEdgeFlags of RecastMeshDetail.cpp
+    thr_sqr = 0.001 * 0.001
+    n = len(vpoly)
+    j = n - 1
+    for i in range(n):
+        if dist_pt_seg_2d(va, vpoly[j], vpoly[i]) < thr_sqr and \
+                dist_pt_seg_2d(vb, vpoly[j], vpoly[i]) < thr_sqr:
+            return 1
+        j = i
+    return 0
+
+
+def tri_flags(va, vb, vc, vpoly):
+    return edge_flags(va, vb, vpoly) | edge_flags(vb, vc, vpoly) << 2 | edge_flags(vc, va, vpoly) << 4
+
+
+# BV tree, like createBVTree with detail meshes
+quant_factor = f(1.0 / cs)
+items = []
+for i, m in enumerate(dm['meshes']):
+    vb, ndv = m['base_vertex_index'], m['vertex_count']
+    vs = [[f(c) for c in dm['vertices'][vb + j]] for j in range(ndv)]
+    lo = [min(v[k] for v in vs) for k in range(3)]
+    hi = [max(v[k] for v in vs) for k in range(3)]
+    q = lambda x, k: min(max(int(f(f(x - bmin[k]) * quant_factor)), 0), 0xffff)
+    items.append({'bmin': [q(lo[k], k) for k in range(3)],
+                  'bmax': [q(hi[k], k) for k in range(3)], 'i': i})
+
+nodes = []
+
+
+def subdivide(items, imin, imax):
+    inum = imax - imin
+    icur = len(nodes)
+    node = {}
+    nodes.append(node)
+    if inum == 1:
+        node['bmin'] = items[imin]['bmin'][:]
+        node['bmax'] = items[imin]['bmax'][:]
+        node['i'] = items[imin]['i']
+    else:
+        node['bmin'] = [min(it['bmin'][k] for it in items[imin:imax]) for k in range(3)]
+        node['bmax'] = [max(it['bmax'][k] for it in items[imin:imax]) for k in range(3)]
+        x, y, z = (node['bmax'][k] - node['bmin'][k] for k in range(3))
+        axis, max_val = 0, x
+        if y > max_val:
+            axis, max_val = 1, y
+        if z > max_val:
+            axis = 2
+        items[imin:imax] = sorted(items[imin:imax], key=lambda it: it['bmin'][axis])
+        isplit = imin + inum // 2
+        subdivide(items, imin, isplit)
+        subdivide(items, isplit, imax)
+        node['i'] = -(len(nodes) - icur)
+
+
+subdivide(items, 0, len(items))
+bv_node_count = npolys * 2
+
+# dtNavMesh::init with a single tile, and addTile
+poly_bits = (npolys - 1).bit_length()
+salt = 1
+base = salt << poly_bits
+links = [{'ref': 0, 'next': i + 1, 'edge': 0, 'side': 0, 'bmin': 0, 'bmax': 0}
+         for i in range(max_link_count)]
+links[-1]['next'] = DT_NULL_LINK
+free_list = 0
+first_links = []
+for i, p in enumerate(polys):
+    first = DT_NULL_LINK
+    nv = len([v for v in p if v != NULL_IDX])
+    for j in reversed(range(nv)):
+        if neis[i][j] == NULL_IDX:
+            continue
+        idx = free_list
+        free_list = links[idx]['next']
+        links[idx] = {'ref': base | neis[i][j], 'next': first, 'edge': j, 'side': 0xff,
+                      'bmin': 0, 'bmax': 0}
+        first = idx
+    first_links.append(first)
+
+data = bytearray()
+data += struct.pack('<15i', int.from_bytes(b'DNAV', 'big'), 7, 0, 0, 0, 0, npolys, nverts,
+                    max_link_count, npolys, detail_vert_count, detail_tri_count, bv_node_count,
+                    0, npolys)
+data += struct.pack('<3f', walkable_height, walkable_radius, walkable_climb)
+data += struct.pack('<3f', *bmin) + struct.pack('<3f', *bmax)
+data += struct.pack('<f', quant_factor)
+assert len(data) == 100
+for iv in pm['vertices']:
+    data += struct.pack('<3f', *world_vertex(iv))
+for i, p in enumerate(polys):
+    nv = len([v for v in p if v != NULL_IDX])
+    data += struct.pack('<I', first_links[i])
+    data += struct.pack('<6H', *[v if v != NULL_IDX else 0 for v in p])
+    data += struct.pack('<6H', *[n + 1 if n != NULL_IDX else 0 for n in neis[i]])
+    data += struct.pack('<HBB', flags[i], nv, areas[i] & 0x3f)
+for link in links:
+    data += struct.pack('<IIBBBB', link['ref'], link['next'], link['edge'], link['side'],
+                        link['bmin'], link['bmax'])
+vbase = 0
+for i, m in enumerate(dm['meshes']):
+    nv = len([v for v in polys[i] if v != NULL_IDX])
+    data += struct.pack('<IIBBxx', vbase, m['base_triangle_index'], m['vertex_count'] - nv,
+                        m['triangle_count'])
+    vbase += m['vertex_count'] - nv
+for i, m in enumerate(dm['meshes']):
+    nv = len([v for v in polys[i] if v != NULL_IDX])
+    for j in range(nv, m['vertex_count']):
+        data += struct.pack('<3f', *[f(c) for c in dm['vertices'][m['base_vertex_index'] + j]])
+for i, m in enumerate(dm['meshes']):
+    vpoly = [world_vertex(pm['vertices'][v]) for v in polys[i] if v != NULL_IDX]
+    vs = [[f(c) for c in v] for v in
+          dm['vertices'][m['base_vertex_index']:m['base_vertex_index'] + m['vertex_count']]]
+    for t in dm['triangles'][m['base_triangle_index']:m['base_triangle_index'] + m['triangle_count']]:
+        data += struct.pack('<4B', *t, tri_flags(vs[t[0]], vs[t[1]], vs[t[2]], vpoly))
+for k in range(bv_node_count):
+    node = nodes[k] if k < len(nodes) else {'bmin': [0] * 3, 'bmax': [0] * 3, 'i': 0}
+    data += struct.pack('<6Hi', *node['bmin'], *node['bmax'], node['i'])
+
+# Sample::saveAll
+out = bytearray()
+out += struct.pack('<3i', int.from_bytes(b'MSET', 'big'), 1, 1)
+out += struct.pack('<3f', *bmin)
+out += struct.pack('<2f', f(bmax[0] - bmin[0]), f(bmax[2] - bmin[2]))
+out += struct.pack('<2i', 1, npolys)
+assert len(out) == 40
+out += struct.pack('<Ii', base, len(data))
+out += data
+open(os.path.join(HERE, 'solo_navmesh.bin'), 'wb').write(out)
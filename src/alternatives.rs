@@ -0,0 +1,116 @@
+use glam::Vec2;
+use hashbrown::{HashMap, HashSet};
+#[cfg(feature = "tracing")]
+use tracing::instrument;
+
+use crate::{instance::U32Layer, policy::SearchPolicy, Coords, Mesh, Path};
+
+/// Extra cost of going through a polygon, for each previous route using it, as a multiplier of
+/// the length.
+const ROUTE_PENALTY: f32 = 1.0;
+
+/// Maximum number of searches for each path requested from [`Mesh::alternative_paths`].
+const SEARCHES_PER_PATH: usize = 4;
+
+/// Makes polygons used by previous routes more expensive.
+struct RoutePenalties {
+    uses: HashMap<u32, u32>,
+}
+
+impl RoutePenalties {
+    fn add(&mut self, path: &Path) {
+        for polygon in inner_polygons(path) {
+            *self.uses.entry(polygon).or_default() += 1;
+        }
+    }
+}
+
+impl SearchPolicy for RoutePenalties {
+    fn segment_cost(&self, from: Vec2, to: Vec2, layer: u8, polygon: u32) -> f32 {
+        let uses = self
+            .uses
+            .get(&u32::from_layer_and_polygon(layer, polygon))
+            .copied()
+            .unwrap_or(0);
+        from.distance(to) * (1.0 + uses as f32 * ROUTE_PENALTY)
+    }
+}
+
+/// Polygons a path goes through, without the ones of its start and its destination.
+fn inner_polygons(path: &Path) -> impl Iterator<Item = u32> + '_ {
+    let polygons = &path.path_through_polygons;
+    polygons
+        .iter()
+        .skip(1)
+        .take(polygons.len().saturating_sub(2))
+        .copied()
+}
+
+impl Path {
+    /// How different this path is from another one, between `0.0` and `1.0`.
+    ///
+    /// This is the share of the polygons this path goes through that the other path doesn't use.
+    /// The polygons of the start and of the destination are ignored as they're the same for all
+    /// paths between two points.
+    pub fn dissimilarity(&self, other: &Path) -> f32 {
+        let polygons: HashSet<u32> = inner_polygons(self).collect();
+        if polygons.is_empty() {
+            return 0.0;
+        }
+        let others: HashSet<u32> = inner_polygons(other).collect();
+        polygons.difference(&others).count() as f32 / polygons.len() as f32
+    }
+}
+
+impl Mesh {
+    /// Compute up to `k` different paths between two points, sorted by length.
+    ///
+    /// The first path is the shortest one. The other ones are found by searching again with the
+    /// polygons used by previous routes made more expensive, and are kept if their
+    /// [`Path::dissimilarity`] with every path already kept is at least `min_dissimilarity`.
+    ///
+    /// At most four searches are made for each path requested. Fewer than `k` paths are returned
+    /// if a search finds no path, or if those searches only found paths too similar to the ones
+    /// already kept.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn alternative_paths(
+        &self,
+        from: impl Into<Coords>,
+        to: impl Into<Coords>,
+        k: usize,
+        min_dissimilarity: f32,
+    ) -> Vec<Path> {
+        let (from, to) = (from.into(), to.into());
+        let mut paths: Vec<Path> = vec![];
+        if k == 0 {
+            return paths;
+        }
+        let Some(shortest) = self.path(from, to) else {
+            return paths;
+        };
+        let mut penalties = RoutePenalties {
+            uses: HashMap::new(),
+        };
+        penalties.add(&shortest);
+        paths.push(shortest);
+
+        for _ in 0..k.saturating_mul(SEARCHES_PER_PATH) {
+            if paths.len() == k {
+                break;
+            }
+            let Some(path) = self.path_with_policy(from, to, &penalties) else {
+                break;
+            };
+            penalties.add(&path);
+            if paths
+                .iter()
+                .all(|other| path.dissimilarity(other) >= min_dissimilarity)
+            {
+                paths.push(path);
+            }
+        }
+
+        paths.sort_by(|a, b| a.length.total_cmp(&b.length));
+        paths
+    }
+}
@@ -1,11 +1,11 @@
 #[cfg(feature = "stats")]
 use std::time::Instant;
-use std::{collections::HashSet, fmt, future::Future, task::Poll};
+use std::{fmt, future::Future, task::Poll};
 
 use glam::Vec2;
 
 use crate::{
-    instance::{InstanceStep, SearchInstance, U32Layer},
+    instance::{InstanceStep, SearchInstance, SearchOptions, U32Layer},
     Mesh, Path,
 };
 
@@ -39,7 +39,7 @@ impl Future for FuturePath<'_> {
         if let Some(search_instance) = self.instance.as_mut() {
             for _i in 0..3 {
                 match search_instance.next() {
-                    InstanceStep::Found(path) => return Poll::Ready(Some(path)),
+                    InstanceStep::Found(path, _) => return Poll::Ready(Some(path)),
                     InstanceStep::NotFound => return Poll::Ready(None),
                     InstanceStep::Continue => {}
                 }
@@ -102,7 +102,7 @@ impl Future for FuturePath<'_> {
                 self.mesh,
                 (self.from, starting_polygon_index),
                 (self.to, ending_polygon),
-                HashSet::default(),
+                SearchOptions::default(),
                 #[cfg(feature = "stats")]
                 start,
             ));
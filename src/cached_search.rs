@@ -0,0 +1,163 @@
+use std::collections::HashSet;
+
+#[cfg(feature = "tracing")]
+use tracing::instrument;
+
+use crate::{
+    instance::{SearchInstance, SearchOptions, U32Layer},
+    Coords, Mesh, Path,
+};
+
+/// A search from a fixed start, caching the distance from the start to every vertex of the mesh
+/// to compute paths to a target that moves.
+///
+/// This is not an incremental search like D* Lite: the search tree is not kept between paths.
+/// Each path is a new search, backward from the target, using the cached distances as an almost
+/// exact heuristic, so it only explores polygons close to the path. The `cached_search` benchmark
+/// compares it with a new search with [`Mesh::path`] each time the target moves: on the aurora
+/// mesh, each path is about 7 times faster, but computing the distances costs about as much as
+/// 70 searches.
+///
+/// The distances ignore blocked polygons, so they stay valid as lower bounds when polygons are
+/// blocked or unblocked, and paths stay optimal without computing them again. Moving the start
+/// explores the whole mesh to compute them again.
+///
+/// Paths can't be searched backward when layers are stitched one way. On those meshes, no
+/// distances are cached and each path is a search from the start like [`Mesh::path`].
+#[derive(Debug)]
+pub struct CachedSearch<'m> {
+    mesh: &'m Mesh,
+    from: Coords,
+    blocked: HashSet<u32>,
+    /// Distances from the start to every vertex, `None` when layers are stitched one way
+    distances: Option<Vec<Vec<f32>>>,
+}
+
+impl Mesh {
+    /// Start a search from a point, caching the distances from it to compute paths to a moving
+    /// target with [`CachedSearch::path_to`].
+    ///
+    /// Returns `None` if the point is not in the mesh.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn cached_search(&self, from: impl Into<Coords>) -> Option<CachedSearch<'_>> {
+        let mut search = CachedSearch {
+            mesh: self,
+            from: self.get_closest_point(from)?,
+            blocked: HashSet::new(),
+            distances: (!self.has_one_way_links()).then(Vec::new),
+        };
+        search.update_distances();
+        Some(search)
+    }
+}
+
+impl CachedSearch<'_> {
+    /// Compute the shortest path from the start to a target.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn path_to(&self, to: impl Into<Coords>) -> Option<Path> {
+        let to = self.mesh.get_closest_point(to)?;
+        let Some(distances) = self.distances.as_ref() else {
+            return self.mesh.path_with_options(
+                self.from,
+                to,
+                SearchOptions {
+                    blocked_polygons: Some(&self.blocked),
+                    ..Default::default()
+                },
+            );
+        };
+        let backward = self.mesh.path_with_options(
+            to,
+            self.from,
+            SearchOptions {
+                blocked_polygons: Some(&self.blocked),
+                distances_to_goal: Some(distances),
+                ..Default::default()
+            },
+        )?;
+        Some(backward.reversed(to))
+    }
+
+    /// Change the start of the paths, and compute the distances from it again.
+    ///
+    /// Returns `false` and keeps the previous start if the point is not in the mesh, or is in a
+    /// blocked polygon.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn set_start(&mut self, from: impl Into<Coords>) -> bool {
+        let Some(from) = self.mesh.get_closest_point(from) else {
+            return false;
+        };
+        if self.blocked.contains(&from.polygon_index) {
+            return false;
+        }
+        self.from = from;
+        self.update_distances();
+        true
+    }
+
+    /// Block polygons, identified by their layer and their index in the layer. Paths won't go
+    /// through them anymore.
+    ///
+    /// This doesn't need to compute the distances again.
+    pub fn block_polygons(&mut self, polygons: impl IntoIterator<Item = (u8, u32)>) {
+        self.blocked.extend(
+            polygons
+                .into_iter()
+                .map(|(layer, polygon)| u32::from_layer_and_polygon(layer, polygon)),
+        );
+    }
+
+    /// Unblock polygons blocked by [`Self::block_polygons`].
+    ///
+    /// This doesn't need to compute the distances again.
+    pub fn unblock_polygons(&mut self, polygons: impl IntoIterator<Item = (u8, u32)>) {
+        for (layer, polygon) in polygons {
+            self.blocked
+                .remove(&u32::from_layer_and_polygon(layer, polygon));
+        }
+    }
+
+    /// Polygons currently blocked, with their layer and their index in the layer.
+    pub fn blocked_polygons(&self) -> impl Iterator<Item = (u8, u32)> + '_ {
+        self.blocked
+            .iter()
+            .map(|polygon| (polygon.layer(), polygon.polygon()))
+    }
+
+    fn update_distances(&mut self) {
+        if let Some(distances) = self.distances.as_mut() {
+            *distances =
+                SearchInstance::distances_from(self.mesh, (self.from.pos, self.from.polygon_index));
+        }
+    }
+}
+
+impl Path {
+    /// The same path in the other direction, ending at `start`, the start of this path.
+    fn reversed(mut self, start: Coords) -> Path {
+        self.path.pop();
+        self.path.reverse();
+        self.path.push(start.pos);
+        #[cfg(feature = "detailed-layers")]
+        {
+            // Each step has the layer of the path after it, which is the layer of the previous
+            // step in the other direction
+            let start_layer = start.polygon_index.layer();
+            let steps = std::mem::take(&mut self.path_with_layers);
+            self.path_with_layers = (0..steps.len().saturating_sub(1))
+                .rev()
+                .map(|index| {
+                    let layer = if index == 0 {
+                        start_layer
+                    } else {
+                        steps[index - 1].1
+                    };
+                    (steps[index].0, layer)
+                })
+                .chain(std::iter::once((start.pos, start_layer)))
+                .collect();
+        }
+        self.path_through_polygons.reverse();
+        self
+    }
+}
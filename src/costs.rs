@@ -0,0 +1,248 @@
+use glam::Vec2;
+#[cfg(feature = "tracing")]
+use tracing::instrument;
+
+use crate::{Coords, Mesh, Path, SearchPolicy};
+
+/// A cost proportional to the angle of each turn of a path, so that vehicles or large creatures
+/// prefer gentle turns.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TurnPenalty {
+    /// Cost of turning by one radian, in the same unit as the length of the path.
+    pub cost_per_radian: f32,
+}
+
+impl TurnPenalty {
+    /// Create a turn penalty.
+    pub fn new(cost_per_radian: f32) -> Self {
+        TurnPenalty { cost_per_radian }
+    }
+}
+
+impl SearchPolicy for TurnPenalty {
+    fn turn_cost(&self, _corner: Vec2, angle: f32) -> f32 {
+        angle * self.cost_per_radian
+    }
+}
+
+/// Shape of a [`CostZone`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ZoneShape {
+    /// A circle.
+    Circle {
+        /// Center of the circle.
+        center: Vec2,
+        /// Radius of the circle.
+        radius: f32,
+    },
+    /// A polygon, defined by its vertices. It doesn't need to be convex.
+    Polygon(Vec<Vec2>),
+}
+
+/// An area where moving costs more than elsewhere, like a fire or the sight cone of an enemy.
+///
+/// Zones are given to a query with [`Mesh::path_with_cost_zones`], without modifying the mesh.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CostZone {
+    /// Shape of the zone.
+    pub shape: ZoneShape,
+    /// Multiplier applied to the length of the parts of a path inside the zone.
+    ///
+    /// Values lower than `1.0` are treated as `1.0`. Where zones overlap, their weights minus
+    /// `1.0` add up.
+    pub weight: f32,
+}
+
+impl CostZone {
+    /// Create a circular zone.
+    pub fn circle(center: Vec2, radius: f32, weight: f32) -> Self {
+        CostZone {
+            shape: ZoneShape::Circle { center, radius },
+            weight,
+        }
+    }
+
+    /// Create a polygonal zone.
+    pub fn polygon(vertices: Vec<Vec2>, weight: f32) -> Self {
+        CostZone {
+            shape: ZoneShape::Polygon(vertices),
+            weight,
+        }
+    }
+
+    /// Length of the part of a segment that is inside the zone.
+    pub fn length_inside(&self, segment: (Vec2, Vec2)) -> f32 {
+        let direction = segment.1 - segment.0;
+        let length = direction.length();
+        if length == 0.0 {
+            return 0.0;
+        }
+        match &self.shape {
+            ZoneShape::Circle { center, radius } => {
+                let a = direction.length_squared();
+                let b = 2.0 * direction.dot(segment.0 - *center);
+                let c = (segment.0 - *center).length_squared() - radius * radius;
+                let discriminant = b * b - 4.0 * a * c;
+                if discriminant <= 0.0 {
+                    return 0.0;
+                }
+                let root = discriminant.sqrt();
+                let enter = ((-b - root) / (2.0 * a)).clamp(0.0, 1.0);
+                let exit = ((-b + root) / (2.0 * a)).clamp(0.0, 1.0);
+                (exit - enter) * length
+            }
+            ZoneShape::Polygon(vertices) => {
+                let mut times = vec![0.0, 1.0];
+                for (a, b) in vertices
+                    .iter()
+                    .zip(vertices.iter().cycle().skip(1))
+                    .map(|(a, b)| (*a, *b))
+                {
+                    let edge = b - a;
+                    let denominator = direction.perp_dot(edge);
+                    if denominator == 0.0 {
+                        continue;
+                    }
+                    let time = (a - segment.0).perp_dot(edge) / denominator;
+                    let edge_time = (a - segment.0).perp_dot(direction) / denominator;
+                    if time > 0.0 && time < 1.0 && (0.0..=1.0).contains(&edge_time) {
+                        times.push(time);
+                    }
+                }
+                times.sort_unstable_by(f32::total_cmp);
+                times
+                    .windows(2)
+                    .filter(|times| {
+                        contains(
+                            vertices,
+                            segment.0 + direction * (times[0] + times[1]) / 2.0,
+                        )
+                    })
+                    .map(|times| (times[1] - times[0]) * length)
+                    .sum()
+            }
+        }
+    }
+}
+
+/// Is the point inside the polygon, by counting crossings of a ray going right.
+fn contains(vertices: &[Vec2], point: Vec2) -> bool {
+    let mut inside = false;
+    for (a, b) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+        if (a.y > point.y) != (b.y > point.y)
+            && point.x < a.x + (point.y - a.y) / (b.y - a.y) * (b.x - a.x)
+        {
+            inside = !inside;
+        }
+    }
+    inside
+}
+
+impl CostZone {
+    /// Cost of a segment inside the zone, on top of its length.
+    fn extra_cost(&self, from: Vec2, to: Vec2) -> f32 {
+        self.length_inside((from, to)) * (self.weight.max(1.0) - 1.0)
+    }
+}
+
+impl SearchPolicy for CostZone {
+    fn segment_cost(&self, from: Vec2, to: Vec2, _layer: u8, _polygon: u32) -> f32 {
+        from.distance(to) + self.extra_cost(from, to)
+    }
+}
+
+impl SearchPolicy for [CostZone] {
+    fn segment_cost(&self, from: Vec2, to: Vec2, _layer: u8, _polygon: u32) -> f32 {
+        from.distance(to)
+            + self
+                .iter()
+                .map(|zone| zone.extra_cost(from, to))
+                .sum::<f32>()
+    }
+}
+
+impl SearchPolicy for Vec<CostZone> {
+    fn segment_cost(&self, from: Vec2, to: Vec2, layer: u8, polygon: u32) -> f32 {
+        self.as_slice().segment_cost(from, to, layer, polygon)
+    }
+}
+
+impl Mesh {
+    /// Compute a path between two points, avoiding zones that are more expensive to go through.
+    ///
+    /// The part of a path inside a zone costs its length multiplied by the weight of the zone.
+    /// Where zones overlap, the extra cost of each zone, its weight minus `1.0`, is added to the
+    /// length: two zones of weight `2.0` cost three times the length.
+    ///
+    /// The path with the lowest cost is returned, which may still go through a zone if going
+    /// around is too long. Paths only turn at corners of the mesh, so a path can't follow the
+    /// border of a zone.
+    ///
+    /// [`Path::length`] is the length of the path, not its cost.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn path_with_cost_zones(
+        &self,
+        from: impl Into<Coords>,
+        to: impl Into<Coords>,
+        zones: &[CostZone],
+    ) -> Option<Path> {
+        self.path_with_policy(from, to, &zones)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::vec2;
+
+    use super::{CostZone, TurnPenalty};
+    use crate::SearchPolicy;
+
+    #[test]
+    fn length_inside_circle() {
+        let zone = CostZone::circle(vec2(0.0, 0.0), 1.0, 2.0);
+        assert_eq!(zone.length_inside((vec2(-2.0, 0.0), vec2(2.0, 0.0))), 2.0);
+        assert_eq!(zone.length_inside((vec2(0.0, 0.0), vec2(2.0, 0.0))), 1.0);
+        assert_eq!(zone.length_inside((vec2(-0.5, 0.0), vec2(0.5, 0.0))), 1.0);
+        assert_eq!(zone.length_inside((vec2(-2.0, 1.0), vec2(2.0, 1.0))), 0.0);
+        assert_eq!(zone.length_inside((vec2(2.0, 0.0), vec2(3.0, 0.0))), 0.0);
+    }
+
+    #[test]
+    fn length_inside_polygon() {
+        // A U shape, open on the top
+        let zone = CostZone::polygon(
+            vec![
+                vec2(0.0, 0.0),
+                vec2(3.0, 0.0),
+                vec2(3.0, 3.0),
+                vec2(2.0, 3.0),
+                vec2(2.0, 1.0),
+                vec2(1.0, 1.0),
+                vec2(1.0, 3.0),
+                vec2(0.0, 3.0),
+            ],
+            2.0,
+        );
+        assert_eq!(zone.length_inside((vec2(-1.0, 0.5), vec2(4.0, 0.5))), 3.0);
+        assert_eq!(zone.length_inside((vec2(-1.0, 2.0), vec2(4.0, 2.0))), 2.0);
+        assert_eq!(zone.length_inside((vec2(0.5, 2.0), vec2(1.5, 2.0))), 0.5);
+        assert_eq!(zone.length_inside((vec2(1.5, 2.0), vec2(1.5, 4.0))), 0.0);
+        assert_eq!(zone.length_inside((vec2(-1.0, 4.0), vec2(4.0, 4.0))), 0.0);
+    }
+
+    #[test]
+    fn combined_policies() {
+        let zone = CostZone::circle(vec2(0.0, 0.0), 1.0, 3.0);
+        let turn = TurnPenalty::new(2.0);
+        let policy = (&zone, turn);
+        assert_eq!(
+            policy.segment_cost(vec2(-2.0, 0.0), vec2(2.0, 0.0), 0, 0),
+            8.0
+        );
+        assert_eq!(policy.turn_cost(vec2(0.0, 0.0), 0.5), 1.0);
+        assert_eq!(
+            vec![zone.clone(), zone].segment_cost(vec2(0.0, 0.0), vec2(2.0, 0.0), 0, 0),
+            6.0
+        );
+    }
+}
@@ -0,0 +1,208 @@
+use glam::{FloatExt, Vec2};
+#[cfg(feature = "tracing")]
+use tracing::instrument;
+
+use crate::{
+    helpers::{line_intersect_segment, Vec2Helper},
+    instance::{SearchOptions, U32Layer},
+    Coords, Mesh, Path, SearchPolicy,
+};
+
+/// A policy where segments cost their length in 3D, plus a penalty for each unit of height
+/// climbed or descended. Layers without heights are flat.
+#[derive(Debug, Clone, Copy)]
+pub struct HeightCost<'m> {
+    mesh: &'m Mesh,
+    /// Cost of each unit of height climbed or descended, on top of the length.
+    pub slope_penalty: f32,
+}
+
+impl<'m> HeightCost<'m> {
+    /// Create a height cost for a mesh. Negative penalties are treated as `0.0`.
+    pub fn new(mesh: &'m Mesh, slope_penalty: f32) -> Self {
+        HeightCost {
+            mesh,
+            slope_penalty: slope_penalty.max(0.0),
+        }
+    }
+}
+
+impl SearchPolicy for HeightCost<'_> {
+    fn segment_cost(&self, from: Vec2, to: Vec2, layer: u8, polygon: u32) -> f32 {
+        let polygon = u32::from_layer_and_polygon(layer, polygon);
+        let (from_height, to_height) = (
+            self.mesh.height_in_polygon(polygon, from),
+            self.mesh.height_in_polygon(polygon, to),
+        );
+        from.extend(from_height).distance(to.extend(to_height))
+            + (to_height - from_height).abs() * self.slope_penalty
+    }
+}
+
+impl Mesh {
+    /// Compute a path between two points, taking the height of the vertices into account.
+    ///
+    /// Segments cost their length in 3D, with heights interpolated from the vertices along the
+    /// edges they cross, plus `slope_penalty` for each unit of height climbed or descended. Use a
+    /// penalty of `0.0` to only minimize the 3D length. Layers without heights are flat.
+    ///
+    /// This is the same as [`Mesh::path_with_policy`] with a [`HeightCost`], except that
+    /// [`Path::length`] is the length of the path in 3D, without the slope penalty.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn path_with_heights(
+        &self,
+        from: impl Into<Coords>,
+        to: impl Into<Coords>,
+        slope_penalty: f32,
+    ) -> Option<Path> {
+        let from = from.into();
+        let mut path = self.path_with_policy(from, to, &HeightCost::new(self, slope_penalty))?;
+        let points = std::iter::once(from.pos)
+            .chain(path.path.iter().copied())
+            .collect::<Vec<_>>();
+        path.length = self.length_3d(&points, &path.path_through_polygons).0;
+        Some(path)
+    }
+
+    /// Compute a path between two points, without going through polygons steeper than
+    /// `max_slope`, in radians.
+    ///
+    /// The slope of each polygon comes from the height of its vertices, see [`Layer::slope`](crate::Layer::slope).
+    /// Returns `None` if the start or the end of the path is on a polygon that is too steep.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn path_with_max_slope(
+        &self,
+        from: impl Into<Coords>,
+        to: impl Into<Coords>,
+        max_slope: f32,
+    ) -> Option<Path> {
+        self.path_with_options(
+            from.into(),
+            to.into(),
+            SearchOptions {
+                max_slope: Some(max_slope),
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Height of a vertex, or `0.0` if its layer has no heights.
+    #[inline(always)]
+    pub(crate) fn vertex_height(&self, layer: u8, vertex: u32) -> f32 {
+        self.layers[layer as usize]
+            .height
+            .get(vertex as usize)
+            .copied()
+            .unwrap_or(0.0)
+    }
+
+    /// Height of a point in a polygon.
+    ///
+    /// Points on an edge are interpolated between its two vertices, points in a triangle use
+    /// their barycentric coordinates, and other points use the average height of the polygon.
+    pub(crate) fn height_in_polygon(&self, polygon: u32, point: Vec2) -> f32 {
+        let layer = &self.layers[polygon.layer() as usize];
+        let vertices = &layer.polygons[polygon.polygon() as usize].vertices;
+        let coords = |vertex: u32| layer.vertices[vertex as usize].coords + layer.offset;
+        let height = |vertex: u32| self.vertex_height(polygon.layer(), vertex);
+
+        for (i, vertex) in vertices.iter().enumerate() {
+            let next = vertices[(i + 1) % vertices.len()];
+            let (a, b) = (coords(*vertex), coords(next));
+            if point.on_segment((a, b)) {
+                let t = ((point - a).dot(b - a) / (b - a).length_squared()).clamp(0.0, 1.0);
+                return height(*vertex).lerp(height(next), t);
+            }
+        }
+        if let [v0, v1, v2] = vertices[..] {
+            let (a, b, c) = (coords(v0), coords(v1), coords(v2));
+            let area = (b - a).perp_dot(c - a);
+            if area != 0.0 {
+                let w1 = (point - a).perp_dot(c - a) / area;
+                let w2 = (b - a).perp_dot(point - a) / area;
+                return height(v0) * (1.0 - w1 - w2) + height(v1) * w1 + height(v2) * w2;
+            }
+        }
+        vertices.iter().map(|vertex| height(*vertex)).sum::<f32>() / vertices.len() as f32
+    }
+
+    /// Follow a path through a list of polygons, calling `f` with each point of the path and
+    /// each point where it goes from one polygon to the next, with their heights and the polygon
+    /// the path went through to reach them.
+    ///
+    /// `points` starts in the first polygon and ends in the last one, and the segments between
+    /// them must go through `polygons` in order.
+    pub(crate) fn heights_along(
+        &self,
+        points: &[Vec2],
+        polygons: &[u32],
+        mut f: impl FnMut(Vec2, f32, u32),
+    ) {
+        let (Some(first), Some(last)) = (points.first(), polygons.last()) else {
+            return;
+        };
+        f(
+            *first,
+            self.height_in_polygon(polygons[0], *first),
+            polygons[0],
+        );
+        let mut previous = *first;
+        let mut segment = 0;
+        for pair in polygons.windows(2) {
+            let Some(([edge0, edge1], _)) = self
+                .polygon_neighbours_with_edges(pair[0])
+                .find(|(_, other_side)| *other_side == pair[1])
+            else {
+                continue;
+            };
+            let layer = &self.layers[pair[0].layer() as usize];
+            let a = layer.vertices[edge0 as usize].coords + layer.offset;
+            let b = layer.vertices[edge1 as usize].coords + layer.offset;
+            let height_at = |point: Vec2| {
+                let t = ((point - a).dot(b - a) / (b - a).length_squared()).clamp(0.0, 1.0);
+                self.vertex_height(pair[0].layer(), edge0)
+                    .lerp(self.vertex_height(pair[0].layer(), edge1), t)
+            };
+            while segment + 1 < points.len() {
+                let target = points[segment + 1];
+                if target.on_segment((a, b)) {
+                    // The path turns on this edge
+                    if target != previous {
+                        f(target, height_at(target), pair[0]);
+                        previous = target;
+                    }
+                    segment += 1;
+                    continue;
+                }
+                if let Some(crossing) = line_intersect_segment((points[segment], target), (a, b)) {
+                    if crossing.in_bounding_box((points[segment], target)) && crossing != previous {
+                        f(crossing, height_at(crossing), pair[0]);
+                        previous = crossing;
+                    }
+                }
+                break;
+            }
+        }
+        for point in points.iter().skip(segment + 1) {
+            f(*point, self.height_in_polygon(*last, *point), *last);
+        }
+    }
+
+    /// Length in 3D of a path going through a list of polygons, and the total height difference
+    /// along it.
+    pub(crate) fn length_3d(&self, points: &[Vec2], polygons: &[u32]) -> (f32, f32) {
+        let mut length = 0.0;
+        let mut climb = 0.0;
+        let mut previous: Option<(Vec2, f32)> = None;
+        self.heights_along(points, polygons, |point, height, _| {
+            if let Some((previous_point, previous_height)) = previous {
+                length += previous_point
+                    .extend(previous_height)
+                    .distance(point.extend(height));
+                climb += (height - previous_height).abs();
+            }
+            previous = Some((point, height));
+        });
+        (length, climb)
+    }
+}
@@ -0,0 +1,516 @@
+use std::{
+    cmp::Ordering,
+    collections::{BinaryHeap, HashSet, VecDeque},
+};
+
+use glam::Vec2;
+use hashbrown::HashMap;
+#[cfg(feature = "tracing")]
+use tracing::instrument;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    instance::{SearchInstance, SearchOptions, U32Layer},
+    landmarks::UNREACHABLE,
+    Coords, Mesh, Path,
+};
+
+/// A hierarchical abstraction of a [`Mesh`], baked by [`Mesh::bake_hierarchy`].
+///
+/// Polygons are clustered into regions. Each pair of neighbouring polygons in different regions
+/// is a portal, and the distance between every pair of portals of a region is computed with a
+/// search restricted to that region.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Hierarchy {
+    /// Region of each polygon, for each layer. `u32::MAX` for empty polygons.
+    pub(crate) polygon_regions: Vec<Vec<u32>>,
+    /// Polygons in each region.
+    pub(crate) regions: Vec<Vec<u32>>,
+    /// Portals between two regions.
+    pub(crate) portals: Vec<Portal>,
+    /// Portals touching each region.
+    pub(crate) region_portals: Vec<Vec<u32>>,
+    /// For each portal, the portals reachable by staying in one of the regions it touches, with their distance.
+    pub(crate) edges: Vec<Vec<(u32, f32)>>,
+    /// If the distance between two points is the same in both directions.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) symmetric: bool,
+}
+
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub(crate) struct Portal {
+    /// Middle of the edge shared by the two polygons, moved slightly inside each polygon.
+    pub(crate) positions: [Vec2; 2],
+    pub(crate) polygons: [u32; 2],
+    pub(crate) regions: [u32; 2],
+}
+
+impl Portal {
+    fn side_in(&self, region: u32) -> usize {
+        if self.regions[0] == region {
+            0
+        } else {
+            1
+        }
+    }
+
+    fn location_in(&self, region: u32) -> (Vec2, u32) {
+        let side = self.side_in(region);
+        (self.positions[side], self.polygons[side])
+    }
+}
+
+impl Hierarchy {
+    /// Number of regions in the hierarchy.
+    pub fn regions_count(&self) -> usize {
+        self.regions.len()
+    }
+
+    /// Number of portals between regions in the hierarchy.
+    pub fn portals_count(&self) -> usize {
+        self.portals.len()
+    }
+
+    /// Region of a polygon, identified by its layer and its index in the layer.
+    pub fn region(&self, layer: u8, polygon: u32) -> Option<u32> {
+        self.polygon_regions
+            .get(layer as usize)
+            .and_then(|regions| regions.get(polygon as usize))
+            .copied()
+            .filter(|region| *region != u32::MAX)
+    }
+
+    fn region_of(&self, polygon: u32) -> Option<u32> {
+        self.region(polygon.layer(), polygon.polygon())
+    }
+
+    fn corridor<'a>(&self, regions: impl IntoIterator<Item = &'a u32>) -> HashSet<u32> {
+        regions
+            .into_iter()
+            .flat_map(|region| self.regions[*region as usize].iter().copied())
+            .collect()
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    fn build(mesh: &Mesh, region_size: usize) -> Self {
+        let region_size = region_size.max(1);
+
+        // Cluster polygons by growing regions from the first unassigned polygon
+        let mut polygon_regions: Vec<Vec<u32>> = mesh
+            .layers
+            .iter()
+            .map(|layer| vec![u32::MAX; layer.polygons.len()])
+            .collect();
+        let mut regions: Vec<Vec<u32>> = vec![];
+        for (layer_index, layer) in mesh.layers.iter().enumerate() {
+            for (polygon_index, polygon) in layer.polygons.iter().enumerate() {
+                if polygon.vertices.is_empty()
+                    || polygon_regions[layer_index][polygon_index] != u32::MAX
+                {
+                    continue;
+                }
+                let region_index = regions.len() as u32;
+                let mut region = vec![];
+                let mut to_visit = VecDeque::new();
+                to_visit.push_back(u32::from_layer_and_polygon(
+                    layer_index as u8,
+                    polygon_index as u32,
+                ));
+                while let Some(next) = to_visit.pop_front() {
+                    if region.len() == region_size {
+                        break;
+                    }
+                    if mesh.layers[next.layer() as usize]
+                        .polygons
+                        .get(next.polygon() as usize)
+                        .is_none_or(|polygon| polygon.vertices.is_empty())
+                    {
+                        continue;
+                    }
+                    let assigned =
+                        &mut polygon_regions[next.layer() as usize][next.polygon() as usize];
+                    if *assigned != u32::MAX {
+                        continue;
+                    }
+                    *assigned = region_index;
+                    region.push(next);
+                    to_visit.extend(mesh.polygon_neighbours(next));
+                }
+                regions.push(region);
+            }
+        }
+
+        // Find portals between regions
+        let mut portals = vec![];
+        let mut region_portals = vec![vec![]; regions.len()];
+        let mut seen = HashSet::new();
+        for (region_index, region) in regions.iter().enumerate() {
+            for polygon in region {
+                for ([edge0, edge1], other_side) in mesh.polygon_neighbours_with_edges(*polygon) {
+                    let other_region =
+                        polygon_regions[other_side.layer() as usize][other_side.polygon() as usize];
+                    if other_region == region_index as u32
+                        || other_region == u32::MAX
+                        || !seen.insert((*polygon.min(&other_side), *polygon.max(&other_side)))
+                    {
+                        continue;
+                    }
+                    let layer = &mesh.layers[polygon.layer() as usize];
+                    let middle = (layer.vertices[edge0 as usize].coords
+                        + layer.vertices[edge1 as usize].coords)
+                        / 2.0
+                        + layer.offset;
+                    let portal_index = portals.len() as u32;
+                    portals.push(Portal {
+                        positions: [
+                            middle.lerp(mesh.polygon_center(*polygon), 1.0e-3),
+                            middle.lerp(mesh.polygon_center(other_side), 1.0e-3),
+                        ],
+                        polygons: [*polygon, other_side],
+                        regions: [region_index as u32, other_region],
+                    });
+                    region_portals[region_index].push(portal_index);
+                    region_portals[other_region as usize].push(portal_index);
+                }
+            }
+        }
+
+        let mut hierarchy = Hierarchy {
+            polygon_regions,
+            regions,
+            portals,
+            region_portals,
+            edges: vec![],
+            symmetric: !mesh.has_one_way_links(),
+        };
+
+        // Compute distances between portals of the same region
+        let mut edges = vec![vec![]; hierarchy.portals.len()];
+        for region in 0..hierarchy.regions.len() as u32 {
+            let corridor = hierarchy.corridor(&[region]);
+            for from in &hierarchy.region_portals[region as usize] {
+                let distances = SearchInstance::distances_to_points(
+                    mesh,
+                    hierarchy.portals[*from as usize].location_in(region),
+                    &hierarchy.portal_locations(region),
+                    &corridor,
+                );
+                edges[*from as usize].extend(
+                    hierarchy.region_portals[region as usize]
+                        .iter()
+                        .zip(distances)
+                        .filter(|(to, distance)| *to != from && *distance != UNREACHABLE)
+                        .map(|(to, distance)| (*to, distance)),
+                );
+            }
+        }
+        hierarchy.edges = edges;
+        hierarchy
+    }
+
+    /// Location of each portal of a region, in the order of [`Hierarchy::region_portals`].
+    fn portal_locations(&self, region: u32) -> Vec<(Vec2, u32)> {
+        self.region_portals[region as usize]
+            .iter()
+            .map(|portal| self.portals[*portal as usize].location_in(region))
+            .collect()
+    }
+}
+
+struct AbstractNode {
+    portal: u32,
+    distance: f32,
+    heuristic: f32,
+}
+
+impl PartialEq for AbstractNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for AbstractNode {}
+
+impl PartialOrd for AbstractNode {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for AbstractNode {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (other.distance + other.heuristic).total_cmp(&(self.distance + self.heuristic))
+    }
+}
+
+impl Mesh {
+    /// Pre-compute a hierarchical abstraction of the mesh, used by [`Mesh::hierarchical_path`].
+    ///
+    /// Polygons are grouped in regions of up to `region_size` polygons, following neighbours
+    /// through stitches between layers. Distances between the portals of each region are computed
+    /// when baking, which can take a while on big meshes.
+    ///
+    /// Like the other pre-computed optimizations, this must be called again after the mesh is modified.
+    /// When deserializing a mesh, the hierarchy is dropped if it doesn't match the mesh anymore.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn bake_hierarchy(&mut self, region_size: usize) {
+        self.hierarchy = Some(Hierarchy::build(self, region_size));
+    }
+
+    /// The hierarchy baked by [`Mesh::bake_hierarchy`], if any.
+    pub fn hierarchy(&self) -> Option<&Hierarchy> {
+        self.hierarchy.as_ref()
+    }
+
+    /// Compute a path between two points, using the hierarchy baked by [`Mesh::bake_hierarchy`].
+    ///
+    /// The regions to go through are found by searching the graph of portals between regions,
+    /// then the path is computed only through the polygons of those regions. The start and the
+    /// goal are connected to the portals of their regions by exploring each of those regions once.
+    ///
+    /// Paths are not optimal: they can be a few percent longer than the ones from [`Mesh::path`]
+    /// when the shortest path goes through other regions. This is much faster for long paths on
+    /// big meshes, but slower than [`Mesh::path`] on small meshes or for short paths, as the
+    /// start and goal regions are explored on every query.
+    ///
+    /// If the hierarchy is not baked, or if no path is found through the selected regions, this
+    /// falls back to [`Mesh::path`].
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn hierarchical_path(
+        &self,
+        from: impl Into<Coords>,
+        to: impl Into<Coords>,
+    ) -> Option<Path> {
+        let from = from.into();
+        let to = to.into();
+        let Some(hierarchy) = self.hierarchy.as_ref() else {
+            return self.path(from, to);
+        };
+
+        let from = self.locate(from)?;
+        let to = self.locate(to)?;
+        let (Some(start_region), Some(goal_region)) = (
+            hierarchy.region_of(from.polygon_index),
+            hierarchy.region_of(to.polygon_index),
+        ) else {
+            return self.path(from, to);
+        };
+
+        let regions = if start_region == goal_region {
+            vec![start_region]
+        } else {
+            let Some(regions) = self.abstract_path(hierarchy, from, start_region, to, goal_region)
+            else {
+                return self.path(from, to);
+            };
+            regions
+        };
+
+        let corridor = hierarchy.corridor(&regions);
+        self.path_with_options(
+            from,
+            to,
+            SearchOptions {
+                corridor: Some(&corridor),
+                ..Default::default()
+            },
+        )
+        .or_else(|| self.path(from, to))
+    }
+
+    /// Find the regions to go through to reach `to` from `from`, by searching the graph of portals.
+    fn abstract_path(
+        &self,
+        hierarchy: &Hierarchy,
+        from: Coords,
+        start_region: u32,
+        to: Coords,
+        goal_region: u32,
+    ) -> Option<Vec<u32>> {
+        // Distance from each portal of the goal region to the goal, staying in the region. When
+        // distances are the same in both directions, they are all found from the goal at once.
+        let goal_corridor = hierarchy.corridor(&[goal_region]);
+        let goal_portals = &hierarchy.region_portals[goal_region as usize];
+        let to_goal: HashMap<u32, f32> = if hierarchy.symmetric {
+            goal_portals
+                .iter()
+                .copied()
+                .zip(SearchInstance::distances_to_points(
+                    self,
+                    (to.pos, to.polygon_index),
+                    &hierarchy.portal_locations(goal_region),
+                    &goal_corridor,
+                ))
+                .filter(|(_, distance)| *distance != UNREACHABLE)
+                .collect()
+        } else {
+            goal_portals
+                .iter()
+                .filter_map(|portal| {
+                    let (pos, polygon) =
+                        hierarchy.portals[*portal as usize].location_in(goal_region);
+                    self.path_with_options(
+                        Coords {
+                            pos,
+                            layer: Some(polygon.layer()),
+                            polygon_index: polygon,
+                        },
+                        to,
+                        SearchOptions {
+                            corridor: Some(&goal_corridor),
+                            ..Default::default()
+                        },
+                    )
+                    .map(|path| (*portal, path.length))
+                })
+                .collect()
+        };
+        if to_goal.is_empty() {
+            return None;
+        }
+
+        let goal = hierarchy.portals.len() as u32;
+        let mut distances: HashMap<u32, f32> = HashMap::new();
+        let mut previous: HashMap<u32, u32> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        let from_start = SearchInstance::distances_to_points(
+            self,
+            (from.pos, from.polygon_index),
+            &hierarchy.portal_locations(start_region),
+            &hierarchy.corridor(&[start_region]),
+        );
+        for (portal, distance) in hierarchy.region_portals[start_region as usize]
+            .iter()
+            .zip(from_start)
+        {
+            if distance == UNREACHABLE {
+                continue;
+            }
+            distances.insert(*portal, distance);
+            queue.push(AbstractNode {
+                portal: *portal,
+                distance,
+                heuristic: hierarchy.portals[*portal as usize].positions[0].distance(to.pos),
+            });
+        }
+
+        while let Some(node) = queue.pop() {
+            if node.portal == goal {
+                break;
+            }
+            if distances
+                .get(&node.portal)
+                .is_some_and(|distance| *distance < node.distance)
+            {
+                continue;
+            }
+            let successors = hierarchy.edges[node.portal as usize]
+                .iter()
+                .copied()
+                .chain(to_goal.get(&node.portal).map(|distance| (goal, *distance)));
+            for (next, distance) in successors {
+                let distance = node.distance + distance;
+                if distances.get(&next).is_some_and(|known| *known <= distance) {
+                    continue;
+                }
+                distances.insert(next, distance);
+                previous.insert(next, node.portal);
+                queue.push(AbstractNode {
+                    portal: next,
+                    distance,
+                    heuristic: if next == goal {
+                        0.0
+                    } else {
+                        hierarchy.portals[next as usize].positions[0].distance(to.pos)
+                    },
+                });
+            }
+        }
+
+        let mut current = *previous.get(&goal)?;
+        let mut regions = vec![start_region, goal_region];
+        loop {
+            regions.extend(hierarchy.portals[current as usize].regions);
+            match previous.get(&current) {
+                Some(portal) => current = *portal,
+                None => break,
+            }
+        }
+        regions.sort_unstable();
+        regions.dedup();
+        Some(regions)
+    }
+
+    /// Find the polygon containing a point, keeping the point position.
+    fn locate(&self, point: Coords) -> Option<Coords> {
+        if point.polygon_index != u32::MAX {
+            return Some(point);
+        }
+        let found = self.get_closest_point(point)?;
+        Some(Coords {
+            pos: point.pos,
+            layer: found.layer,
+            polygon_index: found.polygon_index,
+        })
+    }
+
+    /// Iterate over the polygons neighbouring a polygon, with the edge they share.
+    pub(crate) fn polygon_neighbours_with_edges(
+        &self,
+        polygon: u32,
+    ) -> impl Iterator<Item = ([u32; 2], u32)> + '_ {
+        let layer = &self.layers[polygon.layer() as usize];
+        layer.polygons[polygon.polygon() as usize]
+            .edges_index()
+            .filter_map(move |[edge0, edge1]| {
+                let start = layer.vertices.get(edge0 as usize)?;
+                let end = layer.vertices.get(edge1 as usize)?;
+                start
+                    .polygons
+                    .iter()
+                    .find(|i| **i != u32::MAX && **i != polygon && end.polygons.contains(*i))
+                    .map(|other_side| ([edge0, edge1], *other_side))
+            })
+    }
+
+    /// Iterate over the polygons neighbouring a polygon.
+    pub(crate) fn polygon_neighbours(&self, polygon: u32) -> impl Iterator<Item = u32> + '_ {
+        self.polygon_neighbours_with_edges(polygon)
+            .map(|(_, other_side)| other_side)
+    }
+
+    /// Check if a polygon has a neighbour it's not a neighbour of, which happens when layers
+    /// are stitched one way.
+    pub(crate) fn has_one_way_links(&self) -> bool {
+        self.layers
+            .iter()
+            .enumerate()
+            .flat_map(|(layer_index, layer)| {
+                (0..layer.polygons.len() as u32)
+                    .map(move |polygon| u32::from_layer_and_polygon(layer_index as u8, polygon))
+            })
+            .any(|polygon| {
+                self.polygon_neighbours(polygon).any(|neighbour| {
+                    !self
+                        .polygon_neighbours(neighbour)
+                        .any(|other| other == polygon)
+                })
+            })
+    }
+
+    /// Center of a polygon, including its layer offset.
+    pub(crate) fn polygon_center(&self, polygon: u32) -> Vec2 {
+        let layer = &self.layers[polygon.layer() as usize];
+        let vertices = &layer.polygons[polygon.polygon() as usize].vertices;
+        vertices
+            .iter()
+            .map(|v| layer.vertices[*v as usize].coords)
+            .sum::<Vec2>()
+            / vertices.len() as f32
+            + layer.offset
+    }
+}
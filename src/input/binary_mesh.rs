@@ -0,0 +1,337 @@
+use glam::Vec2;
+use thiserror::Error;
+
+use crate::{
+    input::navmesh_file::{bake_loaded, validate},
+    Layer, Mesh, MeshError, Polygon, Vertex,
+};
+
+/// Magic bytes at the start of a binary mesh.
+const MAGIC: &[u8; 4] = b"PNAV";
+/// Latest version of the binary format. Meshes with a higher version can't be read.
+const BINARY_VERSION: u16 = 1;
+
+/// The layer has heights.
+const HAS_HEIGHTS: u8 = 1;
+/// The layer has baked islands.
+const HAS_ISLANDS: u8 = 1 << 1;
+/// The layer has baked slopes.
+const HAS_SLOPES: u8 = 1 << 2;
+/// The layer has a baked polygon finder. It's not stored, but built again when reading the mesh.
+const HAS_POLYGON_FINDER: u8 = 1 << 3;
+
+/// Errors that can happen when reading a binary mesh with [`Mesh::from_binary`]
+#[derive(Error, Debug, Copy, Clone, PartialEq)]
+pub enum BinaryMeshError {
+    /// The bytes don't start with the magic bytes of a binary mesh.
+    #[error("Not a binary mesh")]
+    InvalidMagic,
+    /// The binary mesh was written by a later version of the format.
+    #[error("Unsupported binary mesh version {0}")]
+    UnsupportedVersion(u16),
+    /// The bytes ended before the mesh was complete.
+    #[error("Unexpected end of the binary mesh at byte {offset}")]
+    UnexpectedEnd {
+        /// Where more bytes were expected.
+        offset: usize,
+    },
+    /// There are bytes left after the mesh.
+    #[error("Unexpected bytes after the end of the binary mesh, at byte {offset}")]
+    TrailingBytes {
+        /// Where the mesh ended.
+        offset: usize,
+    },
+    /// The bytes were read, but the mesh they describe is invalid.
+    #[error("The mesh is invalid: {0}")]
+    Mesh(#[from] MeshError),
+}
+
+/// Reads little-endian values from a slice of bytes.
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> ByteReader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], BinaryMeshError> {
+        let end = self
+            .offset
+            .checked_add(len)
+            .filter(|end| *end <= self.bytes.len())
+            .ok_or(BinaryMeshError::UnexpectedEnd {
+                offset: self.bytes.len(),
+            })?;
+        let taken = &self.bytes[self.offset..end];
+        self.offset = end;
+        Ok(taken)
+    }
+
+    fn u8(&mut self) -> Result<u8, BinaryMeshError> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, BinaryMeshError> {
+        Ok(u16::from_le_bytes(self.take(2)?.try_into().unwrap()))
+    }
+
+    fn u32(&mut self) -> Result<u32, BinaryMeshError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn f32(&mut self) -> Result<f32, BinaryMeshError> {
+        Ok(f32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn count(&mut self) -> Result<usize, BinaryMeshError> {
+        Ok(self.u32()? as usize)
+    }
+
+    /// A number of values too large to be read from these bytes.
+    fn too_large(&self) -> BinaryMeshError {
+        BinaryMeshError::UnexpectedEnd {
+            offset: self.bytes.len(),
+        }
+    }
+
+    /// Sum of counts, checked for overflow.
+    fn total(&self, mut counts: impl Iterator<Item = usize>) -> Result<usize, BinaryMeshError> {
+        counts
+            .try_fold(0_usize, |total, count| total.checked_add(count))
+            .ok_or_else(|| self.too_large())
+    }
+
+    /// An array of `count` values of 4 bytes, checked to be complete before allocating it.
+    fn array<T>(
+        &mut self,
+        count: usize,
+        read: impl Fn([u8; 4]) -> T,
+    ) -> Result<Vec<T>, BinaryMeshError> {
+        let bytes = self.take(count.checked_mul(4).ok_or_else(|| self.too_large())?)?;
+        Ok(bytes
+            .chunks_exact(4)
+            .map(|chunk| read(chunk.try_into().unwrap()))
+            .collect())
+    }
+}
+
+fn put_u32s(bytes: &mut Vec<u8>, values: impl Iterator<Item = u32>) {
+    for value in values {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+fn put_f32s(bytes: &mut Vec<u8>, values: impl Iterator<Item = f32>) {
+    for value in values {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+}
+
+impl Mesh {
+    /// Encode the complete mesh in a compact little-endian binary format.
+    ///
+    /// All layers are kept with their offset, scale, heights and stitches, as well as the
+    /// search settings. Baked islands and slopes are kept if present. The baked polygon finder is
+    /// not stored, as it's quick to build again when reading the mesh.
+    pub fn to_binary(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+        bytes.extend_from_slice(&self.search_delta.to_le_bytes());
+        bytes.extend_from_slice(&self.search_steps.to_le_bytes());
+        put_u32s(&mut bytes, std::iter::once(self.layers.len() as u32));
+
+        for layer in &self.layers {
+            #[cfg(feature = "detailed-layers")]
+            let scale = layer.scale;
+            #[cfg(not(feature = "detailed-layers"))]
+            let scale = Vec2::ONE;
+            put_f32s(
+                &mut bytes,
+                [layer.offset.x, layer.offset.y, scale.x, scale.y].into_iter(),
+            );
+            let mut flags = 0;
+            if !layer.height.is_empty() {
+                flags |= HAS_HEIGHTS;
+            }
+            if layer.islands.is_some() {
+                flags |= HAS_ISLANDS;
+            }
+            if layer.slopes.is_some() {
+                flags |= HAS_SLOPES;
+            }
+            if layer.baked_polygons.is_some() {
+                flags |= HAS_POLYGON_FINDER;
+            }
+            bytes.push(flags);
+
+            // Vertices, then the polygons around each of them
+            put_u32s(&mut bytes, std::iter::once(layer.vertices.len() as u32));
+            put_f32s(
+                &mut bytes,
+                layer
+                    .vertices
+                    .iter()
+                    .flat_map(|vertex| [vertex.coords.x, vertex.coords.y]),
+            );
+            put_u32s(
+                &mut bytes,
+                layer
+                    .vertices
+                    .iter()
+                    .map(|vertex| vertex.polygons.len() as u32),
+            );
+            put_u32s(
+                &mut bytes,
+                layer
+                    .vertices
+                    .iter()
+                    .flat_map(|vertex| vertex.polygons.iter().copied()),
+            );
+
+            // Polygons, then the vertices of each of them
+            put_u32s(&mut bytes, std::iter::once(layer.polygons.len() as u32));
+            put_u32s(
+                &mut bytes,
+                layer.polygons.iter().map(|polygon| {
+                    polygon.vertices.len() as u32 | (polygon.is_one_way as u32) << 31
+                }),
+            );
+            put_u32s(
+                &mut bytes,
+                layer
+                    .polygons
+                    .iter()
+                    .flat_map(|polygon| polygon.vertices.iter().copied()),
+            );
+
+            if !layer.height.is_empty() {
+                put_f32s(&mut bytes, layer.height.iter().copied());
+            }
+            if let Some(islands) = layer.islands.as_ref() {
+                put_u32s(&mut bytes, islands.iter().map(|island| *island as u32));
+            }
+            if let Some(slopes) = layer.slopes.as_ref() {
+                put_f32s(&mut bytes, slopes.iter().copied());
+            }
+        }
+        bytes
+    }
+
+    /// Read a mesh encoded with [`Mesh::to_binary`].
+    ///
+    /// The bytes are validated, so an invalid mesh returns an error instead of panicking when
+    /// used. Loading is not zero-copy, as the mesh owns its data: values are copied once from the
+    /// bytes into the vectors of the mesh, each allocated at its final size.
+    ///
+    /// The polygon finder of a layer that had one baked is built again.
+    pub fn from_binary(bytes: &[u8]) -> Result<Mesh, BinaryMeshError> {
+        let mut reader = ByteReader { bytes, offset: 0 };
+        if reader.take(4).ok() != Some(MAGIC.as_slice()) {
+            return Err(BinaryMeshError::InvalidMagic);
+        }
+        let version = reader.u16()?;
+        if version == 0 || version > BINARY_VERSION {
+            return Err(BinaryMeshError::UnsupportedVersion(version));
+        }
+        let search_delta = reader.f32()?;
+        let search_steps = reader.u32()?;
+        let nb_layers = reader.count()?;
+
+        let mut layers = Vec::with_capacity(nb_layers.min(u8::MAX as usize + 1));
+        let mut polygon_finders = Vec::with_capacity(layers.capacity());
+        for _ in 0..nb_layers {
+            let offset = Vec2::new(reader.f32()?, reader.f32()?);
+            let _scale = Vec2::new(reader.f32()?, reader.f32()?);
+            let flags = reader.u8()?;
+            polygon_finders.push(flags & HAS_POLYGON_FINDER != 0);
+
+            let nb_vertices = reader.count()?;
+            let nb_coords = nb_vertices
+                .checked_mul(2)
+                .ok_or_else(|| reader.too_large())?;
+            let coords = reader.array(nb_coords, f32::from_le_bytes)?;
+            let nb_neighbours = reader.array(nb_vertices, u32::from_le_bytes)?;
+            let total = reader.total(nb_neighbours.iter().map(|n| *n as usize))?;
+            let mut neighbours = reader.array(total, u32::from_le_bytes)?.into_iter();
+            let vertices = coords
+                .chunks_exact(2)
+                .zip(&nb_neighbours)
+                .map(|(coords, n)| {
+                    Vertex::new(
+                        Vec2::new(coords[0], coords[1]),
+                        neighbours.by_ref().take(*n as usize).collect(),
+                    )
+                })
+                .collect();
+
+            let nb_polygons = reader.count()?;
+            let sizes = reader.array(nb_polygons, u32::from_le_bytes)?;
+            let total = reader.total(sizes.iter().map(|size| (size & !(1 << 31)) as usize))?;
+            let mut polygon_vertices = reader.array(total, u32::from_le_bytes)?.into_iter();
+            let polygons = sizes
+                .iter()
+                .map(|size| {
+                    Polygon::new(
+                        polygon_vertices
+                            .by_ref()
+                            .take((size & !(1 << 31)) as usize)
+                            .collect(),
+                        size >> 31 == 1,
+                    )
+                })
+                .collect();
+
+            let height = if flags & HAS_HEIGHTS != 0 {
+                reader.array(nb_vertices, f32::from_le_bytes)?
+            } else {
+                vec![]
+            };
+            let islands = if flags & HAS_ISLANDS != 0 {
+                let islands = reader.array(nb_polygons, u32::from_le_bytes)?;
+                if islands.iter().any(|island| *island as usize >= nb_polygons) {
+                    return Err(MeshError::InvalidMesh.into());
+                }
+                Some(islands.into_iter().map(|island| island as usize).collect())
+            } else {
+                None
+            };
+            let slopes = if flags & HAS_SLOPES != 0 {
+                Some(reader.array(nb_polygons, f32::from_le_bytes)?)
+            } else {
+                None
+            };
+
+            layers.push(Layer {
+                vertices,
+                polygons,
+                offset,
+                #[cfg(feature = "detailed-layers")]
+                scale: _scale,
+                baked_polygons: None,
+                islands,
+                slopes,
+                height,
+            });
+        }
+        if reader.offset != bytes.len() {
+            return Err(BinaryMeshError::TrailingBytes {
+                offset: reader.offset,
+            });
+        }
+
+        let mut mesh = Mesh {
+            layers,
+            search_delta,
+            search_steps,
+            ..Default::default()
+        };
+        validate(&mesh)?;
+        for (layer, polygon_finder) in mesh.layers.iter_mut().zip(polygon_finders) {
+            if polygon_finder {
+                layer.bake_polygon_finder();
+            }
+        }
+        bake_loaded(&mut mesh);
+        Ok(mesh)
+    }
+}
@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use geo::{Area, Contains, Coord, LineString, SimplifyVwPreserve};
+use glam::{vec2, Vec2};
+
+use crate::Layer;
+
+/// A grid of values sampled at integer coordinates, like the pixels of an image or a heightmap.
+///
+/// Value `(x, y)` is at `values[y * width + x]`, at point `(x, y)`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScalarField {
+    /// Number of values in a row.
+    pub width: usize,
+    /// Number of rows.
+    pub height: usize,
+    /// Values, row by row.
+    pub values: Vec<f32>,
+}
+
+/// An edge between two neighbouring samples of the field, identified by its first sample and
+/// whether it goes along the x axis.
+type GridEdge = (i32, i32, bool);
+
+impl ScalarField {
+    /// Create a scalar field from an alpha mask, with values from `0.0` for fully transparent to
+    /// `1.0` for fully opaque.
+    pub fn from_alpha_mask(width: usize, height: usize, alpha: &[u8]) -> ScalarField {
+        ScalarField {
+            width,
+            height,
+            values: alpha.iter().map(|alpha| *alpha as f32 / 255.0).collect(),
+        }
+    }
+
+    /// Value at integer coordinates, `None` outside of the field.
+    pub fn value(&self, x: i32, y: i32) -> Option<f32> {
+        if x < 0 || y < 0 || x as usize >= self.width || y as usize >= self.height {
+            return None;
+        }
+        self.values
+            .get(y as usize * self.width + x as usize)
+            .copied()
+    }
+
+    /// Value at a point, interpolated between the four closest samples. Points outside of the
+    /// field take the value of the closest sample on its border.
+    pub fn sample(&self, point: Vec2) -> f32 {
+        if self.width == 0 || self.height == 0 {
+            return 0.0;
+        }
+        let point = point.clamp(
+            Vec2::ZERO,
+            vec2(self.width as f32 - 1.0, self.height as f32 - 1.0),
+        );
+        let (x, y) = (point.x.floor() as i32, point.y.floor() as i32);
+        let (tx, ty) = (point.x - x as f32, point.y - y as f32);
+        let value = |dx: i32, dy: i32| {
+            self.value(x + dx, y + dy)
+                .or_else(|| self.value(x, y))
+                .unwrap_or(0.0)
+        };
+        let bottom = value(0, 0) * (1.0 - tx) + value(1, 0) * tx;
+        let top = value(0, 1) * (1.0 - tx) + value(1, 1) * tx;
+        bottom * (1.0 - ty) + top * ty
+    }
+
+    /// Extract the contours of the areas with values at least `threshold`, using marching
+    /// squares.
+    ///
+    /// Each area is a polygon, with its holes as interiors, ready to be used with
+    /// [`Triangulation::from_geo_polygon`](crate::Triangulation::from_geo_polygon). Values
+    /// outside of the field are below the threshold, so areas touching the border are closed
+    /// along it.
+    ///
+    /// Contours are simplified with a topology-preserving variant of the
+    /// [Visvalingam-Whyatt algorithm](https://www.tandfonline.com/doi/abs/10.1179/000870493786962263),
+    /// `simplification` being the minimum area a point should contribute to a contour. Use `0.0`
+    /// to keep all points.
+    pub fn contours(&self, threshold: f32, simplification: f32) -> Vec<geo::Polygon<f32>> {
+        let inside = |x: i32, y: i32| self.value(x, y).is_some_and(|value| value >= threshold);
+
+        // Each contour crosses edges of the grid, with the area on its left. Link each crossing
+        // to the next one.
+        let mut next_crossing: HashMap<GridEdge, GridEdge> = HashMap::new();
+        let mut crossings = vec![];
+        for y in -1..self.height as i32 {
+            for x in -1..self.width as i32 {
+                // Corners and edges of the cell, counterclockwise
+                let corners = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)];
+                let edges = [
+                    (x, y, true),
+                    (x + 1, y, false),
+                    (x, y + 1, true),
+                    (x, y, false),
+                ];
+                let inside = corners.map(|(x, y)| inside(x, y));
+                // The center decides if opposite corners inside are connected
+                let connected = self.cell_center(x, y) >= threshold;
+                for exit in 0..4 {
+                    // The contour leaves the area along the edge
+                    if !inside[exit] || inside[(exit + 1) % 4] {
+                        continue;
+                    }
+                    let steps: [usize; 3] = if connected { [1, 2, 3] } else { [3, 2, 1] };
+                    let entry = steps
+                        .iter()
+                        .map(|step| (exit + step) % 4)
+                        .find(|entry| !inside[*entry] && inside[(*entry + 1) % 4])
+                        .unwrap();
+                    next_crossing.insert(edges[exit], edges[entry]);
+                    crossings.push(edges[exit]);
+                }
+            }
+        }
+
+        let mut exteriors = vec![];
+        let mut holes = vec![];
+        for start in crossings {
+            if !next_crossing.contains_key(&start) {
+                continue;
+            }
+            let mut points: Vec<Coord<f32>> = vec![];
+            let mut crossing = start;
+            while let Some(next) = next_crossing.remove(&crossing) {
+                let point = self.crossing_point(crossing, threshold);
+                if points.last() != Some(&point) {
+                    points.push(point);
+                }
+                crossing = next;
+            }
+            if points.len() > 1 && points.first() == points.last() {
+                points.pop();
+            }
+            // Keep only the corners of straight runs, like along the border of the field
+            let corners: Vec<bool> = (0..points.len())
+                .map(|index| {
+                    let previous = points[(index + points.len() - 1) % points.len()];
+                    let next = points[(index + 1) % points.len()];
+                    let (a, b) = (points[index] - previous, next - points[index]);
+                    (a.x * b.y - a.y * b.x).abs() > f32::EPSILON
+                })
+                .collect();
+            let mut corners = corners.into_iter();
+            points.retain(|_| corners.next().unwrap());
+            if points.len() < 3 {
+                continue;
+            }
+            let ring = LineString::new(points);
+            let polygon = geo::Polygon::new(ring.clone(), vec![]);
+            // Areas are on the left of their contours, holes on the right
+            if polygon.signed_area() > 0.0 {
+                exteriors.push((polygon, vec![]));
+            } else {
+                holes.push(ring);
+            }
+        }
+
+        // Each hole is in the smallest area containing it
+        for hole in holes {
+            let point = hole.0[0];
+            if let Some((_, interiors)) = exteriors
+                .iter_mut()
+                .filter(|(exterior, _)| exterior.contains(&point))
+                .min_by(|(a, _), (b, _)| a.unsigned_area().total_cmp(&b.unsigned_area()))
+            {
+                interiors.push(hole);
+            }
+        }
+
+        exteriors
+            .into_iter()
+            .map(|(exterior, interiors)| {
+                let polygon = geo::Polygon::new(exterior.into_inner().0, interiors);
+                if simplification > 0.0 {
+                    polygon.simplify_vw_preserve(simplification)
+                } else {
+                    polygon
+                }
+            })
+            .collect()
+    }
+
+    /// Average of the four corners of a cell, values outside of the field being ignored.
+    fn cell_center(&self, x: i32, y: i32) -> f32 {
+        let values = [(x, y), (x + 1, y), (x + 1, y + 1), (x, y + 1)]
+            .iter()
+            .filter_map(|(x, y)| self.value(*x, *y))
+            .collect::<Vec<_>>();
+        if values.len() < 4 {
+            // On the border, areas are never connected through the outside
+            return f32::NEG_INFINITY;
+        }
+        values.iter().sum::<f32>() / 4.0
+    }
+
+    /// Where the contour crosses an edge of the grid, interpolated between its two samples.
+    fn crossing_point(&self, (x, y, along_x): GridEdge, threshold: f32) -> Coord<f32> {
+        let (end_x, end_y) = if along_x { (x + 1, y) } else { (x, y + 1) };
+        let t = match (self.value(x, y), self.value(end_x, end_y)) {
+            (Some(start), Some(end)) if start != end => {
+                ((threshold - start) / (end - start)).clamp(0.0, 1.0)
+            }
+            // Outside of the field, the contour is on the last sample
+            (None, _) => 1.0,
+            _ => 0.0,
+        };
+        Coord {
+            x: x as f32 + t * (end_x - x) as f32,
+            y: y as f32 + t * (end_y - y) as f32,
+        }
+    }
+}
+
+impl Layer {
+    /// Set the height of each vertex from a heightmap, sampled at the coordinates of the vertex.
+    ///
+    /// Slopes are baked again if they were already baked.
+    pub fn sample_heights(&mut self, heightmap: &ScalarField) {
+        self.height = self
+            .vertices
+            .iter()
+            .map(|vertex| heightmap.sample(vertex.coords))
+            .collect();
+        if self.slopes.is_some() {
+            self.bake_slopes();
+        }
+    }
+}
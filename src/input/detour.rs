@@ -0,0 +1,369 @@
+use std::collections::BTreeSet;
+
+use glam::Vec3;
+use rerecast::{AreaType, SubMesh};
+use thiserror::Error;
+
+use crate::{Mesh, RecastFullMesh, RecastPolyMesh, RecastPolyMeshDetail};
+
+/// Magic number of a Detour tile: the four-character code `DNAV` as a big-endian integer, which
+/// the file stores as a little-endian `i32`.
+const TILE_MAGIC: u32 = u32::from_be_bytes(*b"DNAV");
+/// Version of the Detour tile format that can be read.
+const TILE_VERSION: u32 = 7;
+/// Magic number of a navigation mesh set saved by RecastDemo: the four-character code `MSET` as a
+/// big-endian integer, which the file stores as a little-endian `i32`.
+const SET_MAGIC: u32 = u32::from_be_bytes(*b"MSET");
+/// Version of the navigation mesh set format that can be read.
+const SET_VERSION: u32 = 1;
+
+/// Size of the header of a tile.
+const HEADER_SIZE: usize = 100;
+/// Size of the header of a navigation mesh set, with the parameters of the navigation mesh.
+const SET_HEADER_SIZE: usize = 40;
+/// Maximum number of vertices in a polygon.
+const VERTICES_PER_POLYGON: usize = 6;
+/// Size of a polygon.
+const POLYGON_SIZE: usize = 32;
+/// Size of a link between polygons, with 32 bits polygon references.
+const LINK_SIZE: usize = 12;
+/// Size of the detail mesh of a polygon.
+const DETAIL_MESH_SIZE: usize = 12;
+/// Size of a node of the bounding volume tree.
+const BV_NODE_SIZE: usize = 16;
+/// Size of an off-mesh connection.
+const OFF_MESH_CONNECTION_SIZE: usize = 36;
+
+/// Default area id of walkable polygons.
+const WALKABLE_AREA: u8 = 63;
+/// Type of the polygons of an off-mesh connection.
+const POLYTYPE_OFF_MESH_CONNECTION: u8 = 1;
+/// Flag of the off-mesh connections that can be used in both directions.
+const OFF_MESH_CONNECTION_BIDIRECTIONAL: u8 = 1;
+
+/// Errors that can happen when reading a Detour tile with [`DetourTile::from_bytes`]
+#[derive(Error, Debug, Copy, Clone, PartialEq)]
+pub enum DetourTileError {
+    /// The bytes don't start with the magic number of a Detour tile or navigation mesh set.
+    #[error("Not a Detour tile")]
+    InvalidMagic,
+    /// The tile or navigation mesh set has a version that can't be read.
+    #[error("Unsupported Detour version {0}")]
+    UnsupportedVersion(u32),
+    /// The bytes ended before the tile was complete.
+    #[error("Unexpected end of the Detour tile at byte {offset}")]
+    UnexpectedEnd {
+        /// Where more bytes were expected.
+        offset: usize,
+    },
+    /// A polygon or its detail mesh references data outside of the tile.
+    #[error("Polygon {polygon} is invalid")]
+    InvalidPolygon {
+        /// Index of the polygon in the tile.
+        polygon: usize,
+    },
+    /// An off-mesh connection references a polygon outside of the tile.
+    #[error("Off-mesh connection {connection} is invalid")]
+    InvalidOffMeshConnection {
+        /// Index of the off-mesh connection in the tile.
+        connection: usize,
+    },
+}
+
+/// A connection between two points that are not linked by the mesh, like a jump or a ladder.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OffMeshConnection {
+    /// Start of the connection.
+    pub start: Vec3,
+    /// End of the connection.
+    pub end: Vec3,
+    /// Radius around the ends of the connection where it can be used.
+    pub radius: f32,
+    /// Area id of the connection.
+    pub area: u8,
+    /// User flags of the connection.
+    pub flags: u16,
+    /// If the connection can also be used from its end to its start.
+    pub bidirectional: bool,
+    /// User id of the connection.
+    pub user_id: u32,
+}
+
+/// A ground polygon of a tile, with its detail mesh.
+#[derive(Debug, Clone)]
+struct DetailPolygon {
+    area: u8,
+    /// Vertices of the polygon followed by the vertices of its detail mesh.
+    vertices: Vec<Vec3>,
+    triangles: Vec<[u8; 3]>,
+    triangle_flags: Vec<u8>,
+}
+
+/// A tile of a navigation mesh built by [Detour](https://github.com/recastnavigation/recastnavigation),
+/// as saved by `dtCreateNavMeshData`.
+///
+/// Tiles must have been built with 32 bits polygon references, the default, and saved on a
+/// little-endian platform. Only the polygons, their detail meshes and area ids, and the off-mesh
+/// connections are read: links between polygons are computed again by the conversion to a
+/// [`Mesh`].
+#[derive(Debug, Clone)]
+pub struct DetourTile {
+    /// Position of the tile on the x axis, in the grid of tiles of its navigation mesh.
+    pub x: i32,
+    /// Position of the tile on the z axis, in the grid of tiles of its navigation mesh.
+    pub y: i32,
+    /// Layer of the tile, for tiles at the same position.
+    pub layer: i32,
+    /// Off-mesh connections of the tile.
+    ///
+    /// They are not part of the converted [`Mesh`], as the search can't follow them.
+    pub off_mesh_connections: Vec<OffMeshConnection>,
+    polygons: Vec<DetailPolygon>,
+}
+
+fn u16_at(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes(bytes[offset..offset + 2].try_into().unwrap())
+}
+
+fn u32_at(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn f32_at(bytes: &[u8], offset: usize) -> f32 {
+    f32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+fn vec3_at(bytes: &[u8], offset: usize) -> Vec3 {
+    Vec3::new(
+        f32_at(bytes, offset),
+        f32_at(bytes, offset + 4),
+        f32_at(bytes, offset + 8),
+    )
+}
+
+impl DetourTile {
+    /// Read a tile from the data of a Detour tile.
+    pub fn from_bytes(bytes: &[u8]) -> Result<DetourTile, DetourTileError> {
+        if bytes.len() < HEADER_SIZE {
+            return Err(DetourTileError::UnexpectedEnd {
+                offset: bytes.len(),
+            });
+        }
+        if u32_at(bytes, 0) != TILE_MAGIC {
+            return Err(DetourTileError::InvalidMagic);
+        }
+        let version = u32_at(bytes, 4);
+        if version != TILE_VERSION {
+            return Err(DetourTileError::UnsupportedVersion(version));
+        }
+        let count = |offset: usize| u32_at(bytes, offset) as usize;
+        let (polygon_count, vertex_count, link_count) = (count(24), count(28), count(32));
+        let (detail_mesh_count, detail_vertex_count, detail_triangle_count) =
+            (count(36), count(40), count(44));
+        let (bv_node_count, off_mesh_connection_count) = (count(48), count(52));
+
+        // Start of each section, and the end of the tile
+        let mut sections = [0; 9];
+        sections[0] = HEADER_SIZE;
+        for (index, size) in [
+            vertex_count.checked_mul(12),
+            polygon_count.checked_mul(POLYGON_SIZE),
+            link_count.checked_mul(LINK_SIZE),
+            detail_mesh_count.checked_mul(DETAIL_MESH_SIZE),
+            detail_vertex_count.checked_mul(12),
+            detail_triangle_count.checked_mul(4),
+            bv_node_count.checked_mul(BV_NODE_SIZE),
+            off_mesh_connection_count.checked_mul(OFF_MESH_CONNECTION_SIZE),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            sections[index + 1] = size
+                .and_then(|size| sections[index].checked_add(size))
+                .filter(|end| *end <= bytes.len())
+                .ok_or(DetourTileError::UnexpectedEnd {
+                    offset: bytes.len(),
+                })?;
+        }
+        let (vertices, polygons, detail_meshes) = (sections[0], sections[1], sections[3]);
+        let (detail_vertices, detail_triangles, off_mesh_connections) =
+            (sections[4], sections[5], sections[7]);
+
+        let vertex = |index: usize| vec3_at(bytes, vertices + index * 12);
+        let detail_vertex = |index: usize| vec3_at(bytes, detail_vertices + index * 12);
+        // Vertices, flags and area and type of each polygon
+        let polygon = |index: usize| {
+            let offset = polygons + index * POLYGON_SIZE;
+            let count = (bytes[offset + 30] as usize).min(VERTICES_PER_POLYGON);
+            let vertices: Vec<usize> = (0..count)
+                .map(|vertex| u16_at(bytes, offset + 4 + vertex * 2) as usize)
+                .collect();
+            (vertices, u16_at(bytes, offset + 28), bytes[offset + 31])
+        };
+
+        let mut tile_polygons = vec![];
+        for index in 0..polygon_count {
+            let (vertices, _, area_and_type) = polygon(index);
+            if area_and_type >> 6 == POLYTYPE_OFF_MESH_CONNECTION {
+                continue;
+            }
+            let invalid = DetourTileError::InvalidPolygon { polygon: index };
+            if vertices.len() < 3 || vertices.iter().any(|vertex| *vertex >= vertex_count) {
+                return Err(invalid);
+            }
+            if index >= detail_mesh_count {
+                return Err(invalid);
+            }
+            let offset = detail_meshes + index * DETAIL_MESH_SIZE;
+            let (vertex_base, triangle_base) = (count(offset), count(offset + 4));
+            let (nb_detail_vertices, nb_triangles) =
+                (bytes[offset + 8] as usize, bytes[offset + 9] as usize);
+            if vertex_base + nb_detail_vertices > detail_vertex_count
+                || triangle_base + nb_triangles > detail_triangle_count
+            {
+                return Err(invalid);
+            }
+            // Indices in triangles are for the vertices of the polygon, then the vertices of
+            // the detail mesh
+            let polygon_vertices: Vec<Vec3> = vertices
+                .iter()
+                .map(|index| vertex(*index))
+                .chain((vertex_base..vertex_base + nb_detail_vertices).map(detail_vertex))
+                .collect();
+            let triangles: Vec<[u8; 3]> = (triangle_base..triangle_base + nb_triangles)
+                .map(|triangle| {
+                    let offset = detail_triangles + triangle * 4;
+                    [bytes[offset], bytes[offset + 1], bytes[offset + 2]]
+                })
+                .collect();
+            let triangle_flags = (triangle_base..triangle_base + nb_triangles)
+                .map(|triangle| bytes[detail_triangles + triangle * 4 + 3])
+                .collect();
+            if triangles
+                .iter()
+                .flatten()
+                .any(|vertex| *vertex as usize >= polygon_vertices.len())
+            {
+                return Err(invalid);
+            }
+            tile_polygons.push(DetailPolygon {
+                area: area_and_type & 0x3f,
+                vertices: polygon_vertices,
+                triangles,
+                triangle_flags,
+            });
+        }
+
+        let off_mesh_connections = (0..off_mesh_connection_count)
+            .map(|connection| {
+                let offset = off_mesh_connections + connection * OFF_MESH_CONNECTION_SIZE;
+                let polygon_index = u16_at(bytes, offset + 28) as usize;
+                if polygon_index >= polygon_count {
+                    return Err(DetourTileError::InvalidOffMeshConnection { connection });
+                }
+                let (_, flags, area_and_type) = polygon(polygon_index);
+                Ok(OffMeshConnection {
+                    start: vec3_at(bytes, offset),
+                    end: vec3_at(bytes, offset + 12),
+                    radius: f32_at(bytes, offset + 24),
+                    area: area_and_type & 0x3f,
+                    flags,
+                    bidirectional: bytes[offset + 30] & OFF_MESH_CONNECTION_BIDIRECTIONAL != 0,
+                    user_id: u32_at(bytes, offset + 32),
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(DetourTile {
+            x: u32_at(bytes, 8) as i32,
+            y: u32_at(bytes, 12) as i32,
+            layer: u32_at(bytes, 16) as i32,
+            off_mesh_connections,
+            polygons: tile_polygons,
+        })
+    }
+
+    /// Read all the tiles of a navigation mesh set, as saved by the `.bin` files of RecastDemo.
+    pub fn from_navmesh_set(bytes: &[u8]) -> Result<Vec<DetourTile>, DetourTileError> {
+        if bytes.len() < SET_HEADER_SIZE {
+            return Err(DetourTileError::UnexpectedEnd {
+                offset: bytes.len(),
+            });
+        }
+        if u32_at(bytes, 0) != SET_MAGIC {
+            return Err(DetourTileError::InvalidMagic);
+        }
+        let version = u32_at(bytes, 4);
+        if version != SET_VERSION {
+            return Err(DetourTileError::UnsupportedVersion(version));
+        }
+        let mut tiles = vec![];
+        let mut offset = SET_HEADER_SIZE;
+        for _ in 0..u32_at(bytes, 8) {
+            if bytes.len() < offset + 8 {
+                return Err(DetourTileError::UnexpectedEnd {
+                    offset: bytes.len(),
+                });
+            }
+            let (reference, size) = (u32_at(bytes, offset), u32_at(bytes, offset + 4) as usize);
+            // Like RecastDemo, stop at the first empty tile
+            if reference == 0 || size == 0 {
+                break;
+            }
+            offset += 8;
+            let data = bytes.get(offset..offset.saturating_add(size)).ok_or(
+                DetourTileError::UnexpectedEnd {
+                    offset: bytes.len(),
+                },
+            )?;
+            tiles.push(DetourTile::from_bytes(data)?);
+            offset += size;
+        }
+        Ok(tiles)
+    }
+}
+
+impl Mesh {
+    /// Build a mesh from Detour tiles, like a [`RecastFullMesh`].
+    ///
+    /// Each area id is a layer. Like with Recast, the default walkable area `63` is the first
+    /// layer, followed by the other area ids of the tiles in increasing order. Polygons are the
+    /// triangles of the detail meshes, with their heights. Tiles are connected where their
+    /// polygons share vertices.
+    pub fn from_detour_tiles(tiles: &[DetourTile]) -> Mesh {
+        let mut areas: Vec<u8> = tiles
+            .iter()
+            .flat_map(|tile| tile.polygons.iter().map(|polygon| polygon.area))
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+        if let Some(&WALKABLE_AREA) = areas.last() {
+            areas.pop();
+            areas.insert(0, WALKABLE_AREA);
+        }
+
+        let mut rasterised = RecastPolyMesh::default();
+        let mut detailed = RecastPolyMeshDetail::default();
+        for polygon in tiles.iter().flat_map(|tile| tile.polygons.iter()) {
+            let layer = areas.iter().position(|area| *area == polygon.area).unwrap();
+            rasterised.areas.push(AreaType(layer as u8));
+            detailed.meshes.push(SubMesh {
+                base_vertex_index: detailed.vertices.len() as u32,
+                vertex_count: polygon.vertices.len() as u32,
+                base_triangle_index: detailed.triangles.len() as u32,
+                triangle_count: polygon.triangles.len() as u32,
+            });
+            detailed.vertices.extend(&polygon.vertices);
+            detailed.triangles.extend(&polygon.triangles);
+            detailed.triangle_flags.extend(&polygon.triangle_flags);
+        }
+
+        RecastFullMesh::new(rasterised, detailed).into()
+    }
+}
+
+impl From<DetourTile> for Mesh {
+    fn from(tile: DetourTile) -> Self {
+        Mesh::from_detour_tiles(&[tile])
+    }
+}
@@ -0,0 +1,181 @@
+use geo::{Area, LineString};
+use geojson::{feature::Id, Feature, FeatureCollection, GeoJson, Geometry, JsonValue, Value};
+use glam::Vec2;
+use thiserror::Error;
+
+use crate::{instance::U32Layer, Layer, Mesh, Path, Triangulation};
+
+/// Errors that can happen when building a [`Triangulation`] from GeoJSON with
+/// [`Triangulation::from_geojson`].
+#[derive(Error, Debug)]
+pub enum GeoJsonError {
+    /// A geometry is not valid GeoJSON.
+    #[error(transparent)]
+    GeoJson(Box<geojson::Error>),
+    /// There is no polygon in the GeoJSON.
+    #[error("There is no polygon")]
+    NoPolygon,
+    /// A polygon used as an obstacle has holes.
+    #[error("Polygon {index} is an obstacle, it can't have holes")]
+    ObstacleWithHoles {
+        /// Index of the polygon, in the order they are found in the GeoJSON.
+        index: usize,
+    },
+}
+
+impl Layer {
+    /// Convert the layer to a GeoJSON feature collection, with a polygon feature for each
+    /// polygon of the layer. Polygons without vertices, like the ones left empty by merging
+    /// polygons, are skipped.
+    ///
+    /// Polygons are moved by the offset of the layer. The id of each feature is the id of its
+    /// polygon in a mesh where this layer is at index `layer`, and each feature has the
+    /// properties:
+    /// - `index`: the index of the polygon in the layer
+    /// - `layer`: the index of the layer
+    /// - `island`: the island of the polygon, or `null` if islands are not baked
+    /// - `area`: the area of the polygon
+    pub fn to_geojson(&self, layer: u8) -> FeatureCollection {
+        let features = self
+            .polygons
+            .iter()
+            .enumerate()
+            .filter(|(_, polygon)| !polygon.vertices.is_empty())
+            .map(|(index, polygon)| {
+                let mut exterior: Vec<Vec<f64>> = polygon
+                    .vertices
+                    .iter()
+                    .map(|vertex| {
+                        let coords = self.vertices[*vertex as usize].coords + self.offset;
+                        vec![coords.x as f64, coords.y as f64]
+                    })
+                    .collect();
+                exterior.push(exterior[0].clone());
+                let mut feature = Feature::from(Value::Polygon(vec![exterior]));
+                feature.id = Some(Id::Number(
+                    u32::from_layer_and_polygon(layer, index as u32).into(),
+                ));
+                feature.set_property("index", index);
+                feature.set_property("layer", layer);
+                feature.set_property(
+                    "island",
+                    self.islands
+                        .as_ref()
+                        .and_then(|islands| islands.get(index))
+                        .map_or(JsonValue::Null, |island| (*island).into()),
+                );
+                feature.set_property("area", polygon.area(self));
+                feature
+            })
+            .collect();
+        FeatureCollection {
+            bbox: None,
+            features,
+            foreign_members: None,
+        }
+    }
+}
+
+impl Mesh {
+    /// Convert each layer of the mesh to a GeoJSON feature collection, see
+    /// [`Layer::to_geojson`].
+    pub fn to_geojson(&self) -> Vec<FeatureCollection> {
+        self.layers
+            .iter()
+            .enumerate()
+            .map(|(index, layer)| layer.to_geojson(index as u8))
+            .collect()
+    }
+}
+
+impl Path {
+    /// Convert the path to a GeoJSON line string feature, starting at `from`.
+    ///
+    /// The feature has a `length` property with the length of the path.
+    pub fn to_geojson(&self, from: Vec2) -> Feature {
+        let mut feature = Feature::from(Value::LineString(
+            std::iter::once(from)
+                .chain(self.path.iter().copied())
+                .map(|point| vec![point.x as f64, point.y as f64])
+                .collect(),
+        ));
+        feature.set_property("length", self.length);
+        feature
+    }
+}
+
+impl Triangulation {
+    /// Create a new triangulation from the polygons of a GeoJSON geometry, feature or feature
+    /// collection.
+    ///
+    /// The largest polygon is the outer edge of the triangulation, and its holes are obstacles.
+    /// Other polygons are obstacles too, and can't have holes. Polygons can be in multi-polygons
+    /// and geometry collections, other geometries are ignored.
+    pub fn from_geojson(geojson: &GeoJson) -> Result<Triangulation, GeoJsonError> {
+        let mut polygons = vec![];
+        match geojson {
+            GeoJson::Geometry(geometry) => collect_polygons(geometry, &mut polygons)?,
+            GeoJson::Feature(feature) => {
+                if let Some(geometry) = &feature.geometry {
+                    collect_polygons(geometry, &mut polygons)?;
+                }
+            }
+            GeoJson::FeatureCollection(collection) => {
+                for geometry in collection
+                    .features
+                    .iter()
+                    .filter_map(|feature| feature.geometry.as_ref())
+                {
+                    collect_polygons(geometry, &mut polygons)?;
+                }
+            }
+        }
+
+        let outer = polygons
+            .iter()
+            .enumerate()
+            .max_by(|(_, a), (_, b)| a.unsigned_area().total_cmp(&b.unsigned_area()))
+            .map(|(index, _)| index)
+            .ok_or(GeoJsonError::NoPolygon)?;
+        let mut obstacles: Vec<LineString<f32>> = vec![];
+        for (index, polygon) in polygons.iter().enumerate() {
+            if index == outer {
+                continue;
+            }
+            if !polygon.interiors().is_empty() {
+                return Err(GeoJsonError::ObstacleWithHoles { index });
+            }
+            obstacles.push(polygon.exterior().clone());
+        }
+
+        let (exterior, mut interiors) = polygons.swap_remove(outer).into_inner();
+        interiors.extend(obstacles);
+        Ok(Triangulation::from_geo_polygon(geo::Polygon::new(
+            exterior, interiors,
+        )))
+    }
+}
+
+/// Add the polygons of a geometry to a list, in the order they are found.
+fn collect_polygons(
+    geometry: &Geometry,
+    polygons: &mut Vec<geo::Polygon<f32>>,
+) -> Result<(), GeoJsonError> {
+    let invalid = |error| GeoJsonError::GeoJson(Box::new(error));
+    match &geometry.value {
+        Value::Polygon(_) => {
+            polygons.push(geo::Polygon::try_from(&geometry.value).map_err(invalid)?)
+        }
+        Value::MultiPolygon(_) => {
+            polygons.extend(geo::MultiPolygon::<f32>::try_from(&geometry.value).map_err(invalid)?)
+        }
+        Value::GeometryCollection(geometries) => {
+            for geometry in geometries {
+                collect_polygons(geometry, polygons)?;
+            }
+        }
+        // Points and lines
+        _ => (),
+    }
+    Ok(())
+}
@@ -0,0 +1,260 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    io::Read,
+};
+
+use glam::{ivec2, IVec2, Vec2};
+
+use crate::{
+    input::{
+        navmesh_file::{bake_loaded, validate},
+        polyanya_file::{MeshLines, PolyanyaFileError},
+    },
+    Layer, Mesh, MeshError, Triangulation,
+};
+
+impl Mesh {
+    /// Build a mesh from an occupancy grid of `width` by `height` cells, with `walkable` in row
+    /// order.
+    ///
+    /// Cell `(x, y)` is the square from `(x, y)` to `(x + 1, y + 1)`. Walkable cells that share
+    /// an edge are connected, cells touching only at a corner are not. The walkable regions are
+    /// traced into their outer edges and obstacles, triangulated with a [`Triangulation`], and
+    /// merged. The mesh is baked.
+    ///
+    /// Returns [`MeshError::InvalidMesh`] if `walkable` doesn't have `width * height` cells, or
+    /// if the grid has more than `i32::MAX` cells.
+    pub fn from_grid(width: usize, height: usize, walkable: &[bool]) -> Result<Mesh, MeshError> {
+        if cell_count(width, height) != Some(walkable.len()) {
+            return Err(MeshError::InvalidMesh);
+        }
+        // Both fit as the number of cells does, unless the grid is empty
+        let (Ok(width), Ok(height)) = (i32::try_from(width), i32::try_from(height)) else {
+            return Err(MeshError::InvalidMesh);
+        };
+        let grid = Grid {
+            width,
+            height,
+            walkable,
+        };
+
+        let mut layer = Layer::default();
+        for component in grid.components() {
+            let mut loops = grid.trace(&component);
+            // Walkable cells are on the left of the edges, so the outer edge is the only loop
+            // going counterclockwise
+            let outer = loops
+                .iter()
+                .position(|edges| signed_area(edges) > 0.0)
+                .ok_or(MeshError::InvalidMesh)?;
+            let outer = loops.swap_remove(outer);
+            let mut triangulation = Triangulation::from_outer_edges(&outer);
+            triangulation.add_obstacles(loops);
+            append(&mut layer, triangulation.as_layer());
+        }
+
+        let mut mesh = Mesh {
+            layers: vec![layer],
+            ..Default::default()
+        };
+        validate(&mesh)?;
+        while mesh.merge_polygons() {}
+        bake_loaded(&mut mesh);
+        Ok(mesh)
+    }
+
+    /// Build a mesh from a map in the [Moving AI](https://movingai.com/benchmarks/formats.html)
+    /// format, with [`Mesh::from_grid`].
+    ///
+    /// Cells `.`, `G` and `S` are walkable, all others are blocked. Row `y` of the map is cell
+    /// `(x, y)`, like the coordinates in the scenarios of the benchmarks.
+    pub fn from_moving_ai_map_reader(reader: impl Read) -> Result<Mesh, PolyanyaFileError> {
+        let mut lines = MeshLines::new(reader);
+        let (mut width, mut height) = (None, None);
+        let (width, height) = loop {
+            let line = lines.next("`map`")?;
+            let mut tokens = line.tokens();
+            match tokens.parse::<String>("a header")?.as_str() {
+                "map" => match (width, height) {
+                    (Some(width), Some(height)) if cell_count(width, height).is_some() => {
+                        break (width, height)
+                    }
+                    (Some(_), Some(_)) => {
+                        return Err(line.unexpected("a map of at most `i32::MAX` cells"))
+                    }
+                    _ => return Err(line.unexpected("the width and height before `map`")),
+                },
+                "height" => height = Some(tokens.parse::<usize>("the height of the map")?),
+                "width" => width = Some(tokens.parse::<usize>("the width of the map")?),
+                // `type`, and headers from other versions of the format
+                _ => continue,
+            }
+            tokens.end()?;
+        };
+
+        // Rows are checked as they're read, the header isn't trusted to allocate the map
+        let mut walkable = vec![];
+        for _ in 0..height {
+            let line = lines.next("a row of the map")?;
+            let row = line.text.trim_end();
+            if row.len() != width {
+                return Err(line.unexpected("a row as long as the width of the map"));
+            }
+            walkable.extend(row.bytes().map(|cell| matches!(cell, b'.' | b'G' | b'S')));
+        }
+        lines.end()?;
+
+        Ok(Mesh::from_grid(width, height, &walkable)?)
+    }
+
+    /// Build a mesh from the bytes of a Moving AI map, see [`Mesh::from_moving_ai_map_reader`].
+    pub fn from_moving_ai_map_bytes(bytes: &[u8]) -> Result<Mesh, PolyanyaFileError> {
+        Self::from_moving_ai_map_reader(bytes)
+    }
+
+    /// Build a mesh from a Moving AI map file, see [`Mesh::from_moving_ai_map_reader`].
+    pub fn from_moving_ai_map_file(path: &str) -> Result<Mesh, PolyanyaFileError> {
+        Self::from_moving_ai_map_reader(std::fs::File::open(path)?)
+    }
+}
+
+/// Number of cells of a grid, if it can be indexed with `i32` coordinates.
+fn cell_count(width: usize, height: usize) -> Option<usize> {
+    width
+        .checked_mul(height)
+        .filter(|cells| *cells <= i32::MAX as usize)
+}
+
+struct Grid<'a> {
+    width: i32,
+    height: i32,
+    walkable: &'a [bool],
+}
+
+impl Grid<'_> {
+    fn is_walkable(&self, cell: IVec2) -> bool {
+        cell.x >= 0
+            && cell.y >= 0
+            && cell.x < self.width
+            && cell.y < self.height
+            && self.walkable[(cell.y * self.width + cell.x) as usize]
+    }
+
+    /// Walkable cells, grouped by the regions of cells connected by an edge.
+    fn components(&self) -> Vec<Vec<IVec2>> {
+        let mut visited = vec![false; self.walkable.len()];
+        let mut components = vec![];
+        for start in (0..self.height).flat_map(|y| (0..self.width).map(move |x| ivec2(x, y))) {
+            let index = (start.y * self.width + start.x) as usize;
+            if !self.walkable[index] || visited[index] {
+                continue;
+            }
+            visited[index] = true;
+            let mut component = vec![];
+            let mut to_visit = VecDeque::from([start]);
+            while let Some(cell) = to_visit.pop_front() {
+                component.push(cell);
+                for next in [IVec2::X, IVec2::Y, IVec2::NEG_X, IVec2::NEG_Y].map(|d| cell + d) {
+                    if self.is_walkable(next) {
+                        let index = (next.y * self.width + next.x) as usize;
+                        if !visited[index] {
+                            visited[index] = true;
+                            to_visit.push_back(next);
+                        }
+                    }
+                }
+            }
+            components.push(component);
+        }
+        components
+    }
+
+    /// Loops of edges between the cells of a component and blocked cells, with the walkable
+    /// cells on the left. Only the corners of each loop are kept.
+    fn trace(&self, component: &[IVec2]) -> Vec<Vec<Vec2>> {
+        // Edges of each cell counterclockwise, with the cell on the other side
+        let sides = [
+            (ivec2(0, 0), ivec2(1, 0), IVec2::NEG_Y),
+            (ivec2(1, 0), ivec2(1, 1), IVec2::X),
+            (ivec2(1, 1), ivec2(0, 1), IVec2::Y),
+            (ivec2(0, 1), ivec2(0, 0), IVec2::NEG_X),
+        ];
+        let mut edges = vec![];
+        let mut outgoing: HashMap<IVec2, Vec<IVec2>> = HashMap::new();
+        for cell in component {
+            for (from, to, across) in sides {
+                if !self.is_walkable(*cell + across) {
+                    edges.push((*cell + from, *cell + to));
+                    outgoing.entry(*cell + from).or_default().push(*cell + to);
+                }
+            }
+        }
+        // Where two walkable cells touch only at a corner, there are two edges leaving it. Turn
+        // right to follow the same obstacle.
+        let next = |(from, to): (IVec2, IVec2)| {
+            let direction = to - from;
+            let next = outgoing[&to]
+                .iter()
+                .copied()
+                .max_by_key(|next| (*next - to).perp_dot(direction))
+                .unwrap();
+            (to, next)
+        };
+
+        let mut visited = HashSet::new();
+        let mut loops = vec![];
+        for start in edges {
+            if visited.contains(&start) {
+                continue;
+            }
+            let mut corners = vec![];
+            let mut edge = start;
+            loop {
+                visited.insert(edge);
+                let following = next(edge);
+                if following.1 - following.0 != edge.1 - edge.0 {
+                    corners.push(edge.1.as_vec2());
+                }
+                if following == start {
+                    break;
+                }
+                edge = following;
+            }
+            loops.push(corners);
+        }
+        loops
+    }
+}
+
+/// Twice the signed area of a loop, positive if it's counterclockwise.
+fn signed_area(points: &[Vec2]) -> f32 {
+    points
+        .iter()
+        .zip(points.iter().cycle().skip(1))
+        .map(|(a, b)| a.perp_dot(*b))
+        .sum()
+}
+
+/// Add the vertices and polygons of a layer to another.
+fn append(layer: &mut Layer, other: Layer) {
+    let nb_vertices = layer.vertices.len() as u32;
+    let nb_polygons = layer.polygons.len() as u32;
+    layer
+        .vertices
+        .extend(other.vertices.into_iter().map(|mut vertex| {
+            for polygon in vertex.polygons.iter_mut() {
+                if *polygon != u32::MAX {
+                    *polygon += nb_polygons;
+                }
+            }
+            vertex
+        }));
+    layer
+        .polygons
+        .extend(other.polygons.into_iter().map(|mut polygon| {
+            for vertex in polygon.vertices.iter_mut() {
+                *vertex += nb_vertices;
+            }
+            polygon
+        }));
+}
@@ -1,3 +1,12 @@
+pub mod binary_mesh;
+pub mod contours;
+#[cfg(feature = "recast")]
+pub mod detour;
+#[cfg(feature = "geojson")]
+pub mod geojson;
+pub mod grid;
+pub mod navmesh_file;
+pub mod obj_file;
 pub mod polyanya_file;
 #[cfg(feature = "recast")]
 pub mod recast;
@@ -0,0 +1,338 @@
+use std::{
+    io::{BufRead, BufWriter, Read, Write},
+    str::FromStr,
+};
+
+use glam::Vec2;
+
+use crate::{
+    input::polyanya_file::{Line, MeshLines, PolyanyaFileError},
+    instance::U32Layer,
+    Layer, Mesh, MeshError, Polygon, Vertex,
+};
+
+/// Latest version of the navmesh format. Files with a higher version can't be read.
+const NAVMESH_VERSION: u32 = 1;
+
+/// A reference to a polygon from a vertex, written `-1` when there is no polygon, `polygon` on
+/// layer 0, and `layer:polygon` on other layers.
+struct PolygonRef(u32);
+
+impl FromStr for PolygonRef {
+    type Err = ();
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        if token == "-1" {
+            return Ok(PolygonRef(u32::MAX));
+        }
+        let (layer, polygon) = match token.split_once(':') {
+            Some((layer, polygon)) => (layer.parse().map_err(|_| ())?, polygon),
+            None => (0, token),
+        };
+        let polygon: u32 = polygon.parse().map_err(|_| ())?;
+        if polygon >= 1 << 24 {
+            return Err(());
+        }
+        Ok(PolygonRef(u32::from_layer_and_polygon(layer, polygon)))
+    }
+}
+
+fn polygon_ref(polygon: u32) -> String {
+    if polygon == u32::MAX {
+        "-1".to_string()
+    } else if polygon.layer() == 0 {
+        polygon.to_string()
+    } else {
+        format!("{}:{}", polygon.layer(), polygon.polygon())
+    }
+}
+
+impl Mesh {
+    /// Write the complete mesh in the navmesh format: all its layers with their offset, scale and
+    /// heights, stitches between layers, and search settings. Baked data is not written.
+    ///
+    /// See <https://github.com/vleue/polyanya/blob/main/meshes/navmesh/format.txt> for the format
+    /// description.
+    pub fn write_navmesh(&self, mut writer: impl Write) -> std::io::Result<()> {
+        writeln!(writer, "navmesh")?;
+        writeln!(writer, "{NAVMESH_VERSION}")?;
+        writeln!(writer, "settings 2")?;
+        writeln!(writer, "search_delta {}", self.search_delta)?;
+        writeln!(writer, "search_steps {}", self.search_steps)?;
+        for layer in &self.layers {
+            let properties = [
+                format!("offset {} {}", layer.offset.x, layer.offset.y),
+                #[cfg(feature = "detailed-layers")]
+                format!("scale {} {}", layer.scale.x, layer.scale.y),
+            ];
+            writeln!(writer, "layer {}", properties.len())?;
+            for property in properties {
+                writeln!(writer, "{property}")?;
+            }
+
+            writeln!(writer, "vertices {}", layer.vertices.len())?;
+            for vertex in &layer.vertices {
+                write!(
+                    writer,
+                    "{} {} {}",
+                    vertex.coords.x,
+                    vertex.coords.y,
+                    vertex.polygons.len()
+                )?;
+                for polygon in &vertex.polygons {
+                    write!(writer, " {}", polygon_ref(*polygon))?;
+                }
+                writeln!(writer)?;
+            }
+
+            writeln!(writer, "polygons {}", layer.polygons.len())?;
+            for polygon in &layer.polygons {
+                write!(writer, "{}", polygon.vertices.len())?;
+                for vertex in &polygon.vertices {
+                    write!(writer, " {vertex}")?;
+                }
+                writeln!(writer, " {}", polygon.is_one_way as u8)?;
+            }
+
+            if !layer.height.is_empty() {
+                writeln!(writer, "heights {}", layer.height.len())?;
+                for height in &layer.height {
+                    writeln!(writer, "{height}")?;
+                }
+            }
+        }
+        writer.flush()
+    }
+
+    /// Write the complete mesh to bytes in the navmesh format, see [`Mesh::write_navmesh`].
+    pub fn to_navmesh_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_navmesh(&mut bytes)
+            .expect("writing to a Vec can't fail");
+        bytes
+    }
+
+    /// Write the complete mesh to a file in the navmesh format, see [`Mesh::write_navmesh`].
+    pub fn to_navmesh_file(&self, path: &str) -> std::io::Result<()> {
+        self.write_navmesh(BufWriter::new(std::fs::File::create(path)?))
+    }
+
+    /// Read a mesh in the navmesh format, written by [`Mesh::write_navmesh`].
+    ///
+    /// Sections and settings that are not known are skipped, so files written by a later
+    /// version of the format can be read if they keep the same version number. The mesh is
+    /// baked, except for islands detection on layers stitched to other layers.
+    ///
+    /// See <https://github.com/vleue/polyanya/blob/main/meshes/navmesh/format.txt> for the format
+    /// description.
+    pub fn from_navmesh_reader(reader: impl Read) -> Result<Mesh, PolyanyaFileError> {
+        let mut lines = MeshLines::new(reader);
+
+        let header = lines.next("the header `navmesh`")?;
+        if header.text.trim() != "navmesh" {
+            return Err(header.unexpected("the header `navmesh`"));
+        }
+        let version = lines.next("the version")?;
+        match version.text.trim().parse::<u32>() {
+            Ok(number) if (1..=NAVMESH_VERSION).contains(&number) => (),
+            _ => return Err(version.unexpected("a supported version")),
+        }
+
+        let mut mesh = Mesh {
+            layers: vec![],
+            ..Default::default()
+        };
+        while let Some(section) = lines.next_line()? {
+            let mut tokens = section.tokens();
+            let name: String = tokens.parse("a section name")?;
+            let count: usize = tokens.parse("the number of lines of the section")?;
+            tokens.end()?;
+            match name.as_str() {
+                "settings" => read_settings(&mut lines, count, &mut mesh)?,
+                "layer" => {
+                    mesh.layers.push(Layer::default());
+                    read_layer_properties(&mut lines, count, &mut mesh)?;
+                }
+                "vertices" => {
+                    let layer = current_layer(&mut mesh, &section)?;
+                    for _ in 0..count {
+                        let line = lines.next("a vertex")?;
+                        let mut tokens = line.tokens();
+                        let x = tokens.parse("the x coordinate of a vertex")?;
+                        let y = tokens.parse("the y coordinate of a vertex")?;
+                        let nb_polygons = tokens.parse("the number of polygons around a vertex")?;
+                        let polygons: Vec<PolygonRef> =
+                            tokens.parse_many(nb_polygons, "a polygon reference")?;
+                        tokens.end()?;
+                        layer.vertices.push(Vertex::new(
+                            Vec2::new(x, y),
+                            polygons.into_iter().map(|polygon| polygon.0).collect(),
+                        ));
+                    }
+                }
+                "polygons" => {
+                    let layer = current_layer(&mut mesh, &section)?;
+                    for _ in 0..count {
+                        let line = lines.next("a polygon")?;
+                        let mut tokens = line.tokens();
+                        let nb_vertices = tokens.parse("the number of vertices of a polygon")?;
+                        let vertices = tokens.parse_many(nb_vertices, "a vertex index")?;
+                        let is_one_way = match tokens.parse("`0` or `1` for a one way polygon")? {
+                            0 => false,
+                            1 => true,
+                            _ => return Err(line.unexpected("`0` or `1` for a one way polygon")),
+                        };
+                        tokens.end()?;
+                        layer.polygons.push(Polygon::new(vertices, is_one_way));
+                    }
+                }
+                "heights" => {
+                    let layer = current_layer(&mut mesh, &section)?;
+                    for _ in 0..count {
+                        let line = lines.next("a height")?;
+                        let mut tokens = line.tokens();
+                        layer.height.push(tokens.parse("a height")?);
+                        tokens.end()?;
+                    }
+                }
+                // Unknown section, from a later version of the format
+                _ => {
+                    for _ in 0..count {
+                        lines.next("a line of the section")?;
+                    }
+                }
+            }
+        }
+
+        validate(&mesh)?;
+        bake_loaded(&mut mesh);
+        Ok(mesh)
+    }
+
+    /// Read a mesh from bytes in the navmesh format, see [`Mesh::from_navmesh_reader`].
+    pub fn from_navmesh_bytes(bytes: &[u8]) -> Result<Mesh, PolyanyaFileError> {
+        Self::from_navmesh_reader(bytes)
+    }
+
+    /// Read a mesh from a file in the navmesh format, see [`Mesh::from_navmesh_reader`].
+    pub fn from_navmesh_file(path: &str) -> Result<Mesh, PolyanyaFileError> {
+        Self::from_navmesh_reader(std::fs::File::open(path)?)
+    }
+}
+
+fn read_settings<R: BufRead>(
+    lines: &mut MeshLines<R>,
+    count: usize,
+    mesh: &mut Mesh,
+) -> Result<(), PolyanyaFileError> {
+    for _ in 0..count {
+        let line = lines.next("a setting")?;
+        let mut tokens = line.tokens();
+        match tokens.parse::<String>("a setting name")?.as_str() {
+            "search_delta" => mesh.search_delta = tokens.parse("the search delta")?,
+            "search_steps" => mesh.search_steps = tokens.parse("the number of search steps")?,
+            // Unknown setting, from a later version of the format
+            _ => continue,
+        }
+        tokens.end()?;
+    }
+    Ok(())
+}
+
+fn read_layer_properties<R: BufRead>(
+    lines: &mut MeshLines<R>,
+    count: usize,
+    mesh: &mut Mesh,
+) -> Result<(), PolyanyaFileError> {
+    let layer = mesh.layers.last_mut().unwrap();
+    for _ in 0..count {
+        let line = lines.next("a layer property")?;
+        let mut tokens = line.tokens();
+        match tokens.parse::<String>("a layer property name")?.as_str() {
+            "offset" => {
+                layer.offset = Vec2::new(
+                    tokens.parse("the x offset of the layer")?,
+                    tokens.parse("the y offset of the layer")?,
+                );
+            }
+            #[cfg(feature = "detailed-layers")]
+            "scale" => {
+                layer.scale = Vec2::new(
+                    tokens.parse("the x scale of the layer")?,
+                    tokens.parse("the y scale of the layer")?,
+                );
+            }
+            // Unknown property, from a later version of the format
+            _ => continue,
+        }
+        tokens.end()?;
+    }
+    Ok(())
+}
+
+/// The layer that sections of vertices, polygons and heights are for.
+fn current_layer<'m>(
+    mesh: &'m mut Mesh,
+    section: &Line,
+) -> Result<&'m mut Layer, PolyanyaFileError> {
+    mesh.layers
+        .last_mut()
+        .ok_or_else(|| section.unexpected("a `layer` section first"))
+}
+
+/// Bake a mesh read from a file, keeping what was already baked.
+pub(crate) fn bake_loaded(mesh: &mut Mesh) {
+    #[cfg(not(feature = "no-default-baking"))]
+    for layer in mesh.layers.iter_mut() {
+        layer.bake_loaded();
+    }
+    #[cfg(feature = "no-default-baking")]
+    let _ = mesh;
+}
+
+/// Check that a mesh read from a file can be used without panicking.
+pub(crate) fn validate(mesh: &Mesh) -> Result<(), MeshError> {
+    if mesh.layers.is_empty() {
+        return Err(MeshError::EmptyMesh);
+    }
+    for layer in &mesh.layers {
+        if layer.vertices.is_empty() || layer.polygons.is_empty() {
+            return Err(MeshError::EmptyMesh);
+        }
+        if layer.polygons.len() > (2_i32.pow(24) - 1) as usize {
+            return Err(MeshError::TooManyPolygons);
+        }
+        if !layer.height.is_empty() && layer.height.len() != layer.vertices.len() {
+            return Err(MeshError::InvalidMesh);
+        }
+        if layer
+            .polygons
+            .iter()
+            .any(|polygon| polygon.vertices.len() < 3)
+        {
+            return Err(MeshError::InvalidMesh);
+        }
+        if layer
+            .polygons
+            .iter()
+            .flat_map(|polygon| polygon.vertices.iter())
+            .any(|vertex| *vertex as usize >= layer.vertices.len())
+        {
+            return Err(MeshError::InvalidMesh);
+        }
+        if layer
+            .vertices
+            .iter()
+            .flat_map(|vertex| vertex.polygons.iter())
+            .filter(|polygon| **polygon != u32::MAX)
+            .any(|polygon| {
+                mesh.layers
+                    .get(polygon.layer() as usize)
+                    .is_none_or(|other| polygon.polygon() as usize >= other.polygons.len())
+            })
+        {
+            return Err(MeshError::InvalidMesh);
+        }
+    }
+    Ok(())
+}
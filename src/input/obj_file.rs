@@ -0,0 +1,302 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    io::{BufWriter, Read, Write},
+};
+
+use glam::{Vec2, Vec3};
+use thiserror::Error;
+
+use crate::{
+    input::{
+        navmesh_file::{bake_loaded, validate},
+        polyanya_file::{MeshLines, PolyanyaFileError},
+    },
+    Layer, Mesh, MeshError, Polygon, Vertex,
+};
+
+/// Errors that can happen when reading a mesh from an OBJ file with [`Mesh::from_obj_reader`].
+#[derive(Error, Debug)]
+pub enum ObjFileError {
+    /// The file couldn't be read or parsed.
+    #[error(transparent)]
+    Parse(#[from] PolyanyaFileError),
+    /// A face has less than three different vertices, or no area.
+    #[error("Line {line}: the face is degenerate")]
+    DegenerateFace {
+        /// Line of the face, starting at 1.
+        line: usize,
+    },
+    /// A face is not convex.
+    #[error("Line {line}: the face is not convex")]
+    NonConvexFace {
+        /// Line of the face, starting at 1.
+        line: usize,
+    },
+    /// An edge of a face is shared by more than two faces, or by two overlapping faces.
+    #[error("Line {line}: the edge between vertices {} and {} is not manifold", .edge.0, .edge.1)]
+    NonManifoldEdge {
+        /// Line of the face, starting at 1.
+        line: usize,
+        /// Vertices of the edge, as indexed in the file starting at 1.
+        edge: (usize, usize),
+    },
+    /// The file was read, but the mesh it describes is invalid.
+    #[error("The mesh is invalid: {0}")]
+    Mesh(#[from] MeshError),
+}
+
+/// A face read from an OBJ file, with the line it's on and the index of its vertices in the file.
+struct Face {
+    line: usize,
+    vertices: Vec<usize>,
+}
+
+impl Mesh {
+    /// Write the mesh as a Wavefront OBJ model, with one object per layer.
+    ///
+    /// The model is Y-up: the mesh is on the X and -Z axes, and the height of each vertex is its
+    /// Y coordinate. Layers are moved by their offset, and each polygon is a face.
+    pub fn write_obj(&self, mut writer: impl Write) -> std::io::Result<()> {
+        let mut first_vertex = 1;
+        for (index, layer) in self.layers.iter().enumerate() {
+            writeln!(writer, "o layer_{index}")?;
+            for (vertex_index, vertex) in layer.vertices.iter().enumerate() {
+                let position = vertex.coords + layer.offset;
+                let height = layer.height.get(vertex_index).copied().unwrap_or(0.0);
+                // `0.0 -` to not write `-0` for vertices on the X axis
+                writeln!(writer, "v {} {} {}", position.x, height, 0.0 - position.y)?;
+            }
+            for polygon in &layer.polygons {
+                write!(writer, "f")?;
+                for vertex in &polygon.vertices {
+                    write!(writer, " {}", first_vertex + *vertex as usize)?;
+                }
+                writeln!(writer)?;
+            }
+            first_vertex += layer.vertices.len();
+        }
+        writer.flush()
+    }
+
+    /// Write the mesh to bytes as a Wavefront OBJ model, see [`Mesh::write_obj`].
+    pub fn to_obj_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        self.write_obj(&mut bytes)
+            .expect("writing to a Vec can't fail");
+        bytes
+    }
+
+    /// Write the mesh to a file as a Wavefront OBJ model, see [`Mesh::write_obj`].
+    pub fn to_obj_file(&self, path: &str) -> std::io::Result<()> {
+        self.write_obj(BufWriter::new(std::fs::File::create(path)?))
+    }
+
+    /// Read a mesh from the faces of a Wavefront OBJ model of the walkable surface.
+    ///
+    /// The model must be Y-up, like in [`Mesh::write_obj`]. Each object is a layer, with the Y
+    /// coordinate of its vertices as their height. Layers are not stitched together. Faces can
+    /// face up or down, but they must be convex, and each edge can be shared by at most two
+    /// faces. The polygons around each vertex are computed from the faces.
+    ///
+    /// Only vertices and faces are read, other elements like normals or materials are skipped.
+    pub fn from_obj_reader(reader: impl Read) -> Result<Mesh, ObjFileError> {
+        let mut lines = MeshLines::new(reader);
+        let mut positions = vec![];
+        let mut objects: Vec<Vec<Face>> = vec![];
+
+        while let Some(line) = lines.next_line()? {
+            let mut tokens = line.tokens();
+            match tokens.parse::<String>("an element")?.as_str() {
+                "v" => positions.push(Vec3::new(
+                    tokens.parse("the x coordinate of a vertex")?,
+                    tokens.parse("the y coordinate of a vertex")?,
+                    tokens.parse("the z coordinate of a vertex")?,
+                )),
+                "o" => objects.push(vec![]),
+                "f" => {
+                    let mut vertices = vec![];
+                    while !tokens.is_empty() {
+                        let token: String = tokens.parse("a vertex index")?;
+                        // Texture and normal indices are skipped
+                        let index = token
+                            .split('/')
+                            .next()
+                            .and_then(|index| index.parse::<i64>().ok())
+                            .and_then(|index| match index {
+                                1.. => Some(index as usize - 1),
+                                // Relative to the end of the vertices
+                                ..0 => positions.len().checked_sub(index.unsigned_abs() as usize),
+                                0 => None,
+                            })
+                            .filter(|index| *index < positions.len())
+                            .ok_or(PolyanyaFileError::UnexpectedToken {
+                                line: line.number,
+                                token,
+                                expected: "the index of a vertex already defined",
+                            })?;
+                        vertices.push(index);
+                    }
+                    if objects.is_empty() {
+                        objects.push(vec![]);
+                    }
+                    objects.last_mut().unwrap().push(Face {
+                        line: line.number,
+                        vertices,
+                    });
+                }
+                // Normals, texture coordinates, groups, materials, comments, ...
+                _ => continue,
+            }
+        }
+
+        let mut mesh = Mesh {
+            layers: objects
+                .iter()
+                .filter(|faces| !faces.is_empty())
+                .map(|faces| layer_from_faces(&positions, faces))
+                .collect::<Result<_, _>>()?,
+            ..Default::default()
+        };
+        validate(&mesh)?;
+        bake_loaded(&mut mesh);
+        Ok(mesh)
+    }
+
+    /// Read a mesh from bytes of a Wavefront OBJ model, see [`Mesh::from_obj_reader`].
+    pub fn from_obj_bytes(bytes: &[u8]) -> Result<Mesh, ObjFileError> {
+        Self::from_obj_reader(bytes)
+    }
+
+    /// Read a mesh from a Wavefront OBJ file, see [`Mesh::from_obj_reader`].
+    pub fn from_obj_file(path: &str) -> Result<Mesh, ObjFileError> {
+        Self::from_obj_reader(std::fs::File::open(path).map_err(PolyanyaFileError::from)?)
+    }
+}
+
+/// Build a layer from the faces of an object.
+fn layer_from_faces(positions: &[Vec3], faces: &[Face]) -> Result<Layer, ObjFileError> {
+    // Keep the vertices used by the faces, in the same order as in the file
+    let used: Vec<usize> = faces
+        .iter()
+        .flat_map(|face| face.vertices.iter().copied())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect();
+    let local: HashMap<usize, u32> = used
+        .iter()
+        .enumerate()
+        .map(|(local, global)| (*global, local as u32))
+        .collect();
+    let coords: Vec<Vec2> = used
+        .iter()
+        .map(|global| Vec2::new(positions[*global].x, -positions[*global].z))
+        .collect();
+
+    let mut polygons = Vec::with_capacity(faces.len());
+    let mut edges = HashMap::new();
+    for (index, face) in faces.iter().enumerate() {
+        let mut vertices: Vec<u32> = face.vertices.iter().map(|v| local[v]).collect();
+        vertices.dedup();
+        while vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices.pop();
+        }
+        let points: Vec<Vec2> = vertices.iter().map(|v| coords[*v as usize]).collect();
+        let area: f32 = points
+            .iter()
+            .zip(points.iter().cycle().skip(1))
+            .map(|(a, b)| a.perp_dot(*b))
+            .sum();
+        if vertices.len() < 3 || area.abs() < f32::EPSILON {
+            return Err(ObjFileError::DegenerateFace { line: face.line });
+        }
+        // Faces facing down are clockwise once projected
+        if area < 0.0 {
+            vertices.reverse();
+        }
+        let points: Vec<Vec2> = vertices.iter().map(|v| coords[*v as usize]).collect();
+        if (0..points.len()).any(|i| {
+            let (a, b, c) = (
+                points[i],
+                points[(i + 1) % points.len()],
+                points[(i + 2) % points.len()],
+            );
+            (b - a).perp_dot(c - b) < -1e-6 * (b - a).length() * (c - b).length()
+        }) {
+            return Err(ObjFileError::NonConvexFace { line: face.line });
+        }
+        for (a, b) in vertices.iter().zip(vertices.iter().cycle().skip(1)) {
+            if edges.insert((*a, *b), index as u32).is_some() {
+                return Err(ObjFileError::NonManifoldEdge {
+                    line: face.line,
+                    edge: (used[*a as usize] + 1, used[*b as usize] + 1),
+                });
+            }
+        }
+        polygons.push(vertices);
+    }
+
+    let mut around: Vec<Vec<u32>> = vec![vec![]; coords.len()];
+    for (index, polygon) in polygons.iter().enumerate() {
+        for vertex in polygon {
+            around[*vertex as usize].push(index as u32);
+        }
+    }
+    // Vertices before and after a vertex in a polygon
+    let sides = |polygon: u32, vertex: usize| {
+        let polygon = &polygons[polygon as usize];
+        let position = polygon.iter().position(|v| *v as usize == vertex).unwrap();
+        (
+            polygon[(position + polygon.len() - 1) % polygon.len()],
+            polygon[(position + 1) % polygon.len()],
+        )
+    };
+    let vertices = around
+        .into_iter()
+        .enumerate()
+        .map(|(vertex, mut around)| {
+            // Order the polygons counterclockwise, by the direction of their edge leaving the
+            // vertex
+            around.sort_by(|a, b| {
+                let angle = |polygon: u32| {
+                    let direction = coords[sides(polygon, vertex).1 as usize] - coords[vertex];
+                    direction.y.atan2(direction.x)
+                };
+                angle(*a).total_cmp(&angle(*b))
+            });
+            let mut neighbours = Vec::with_capacity(around.len() + 1);
+            for (index, polygon) in around.iter().enumerate() {
+                neighbours.push(*polygon);
+                let next = around[(index + 1) % around.len()];
+                // The polygons don't share an edge, there's an obstacle between them
+                if sides(*polygon, vertex).0 != sides(next, vertex).1 {
+                    neighbours.push(u32::MAX);
+                }
+            }
+            Vertex::new(coords[vertex], neighbours)
+        })
+        .collect();
+
+    let polygons = polygons
+        .into_iter()
+        .map(|vertices| {
+            let nb_neighbours = vertices
+                .iter()
+                .zip(vertices.iter().cycle().skip(1))
+                .filter(|(a, b)| edges.contains_key(&(**b, **a)))
+                .count();
+            Polygon::new(vertices, nb_neighbours <= 1)
+        })
+        .collect();
+
+    let height: Vec<f32> = used.iter().map(|global| positions[*global].y).collect();
+    Ok(Layer {
+        vertices,
+        polygons,
+        height: if height.iter().all(|height| *height == 0.0) {
+            vec![]
+        } else {
+            height
+        },
+        ..Default::default()
+    })
+}
@@ -1,8 +1,11 @@
-use core::panic;
-use std::io::{BufRead, BufReader, Lines, Read, Write};
+use std::{
+    io::{BufRead, BufReader, Lines, Read, Write},
+    str::{FromStr, SplitWhitespace},
+};
 
 use glam::Vec2;
 use hashbrown::HashMap;
+use thiserror::Error;
 
 use crate::{Mesh, MeshError, Polygon, Vertex};
 
@@ -16,69 +19,151 @@ pub struct PolyanyaFile {
     pub vertices: Vec<Vertex>,
     /// List of polygon described in the file
     pub polygons: Vec<Polygon>,
+    obstacles: Vec<Polygon>,
+}
+
+/// Errors that can happen when reading a Polyanya file with [`PolyanyaFile::try_from_bytes`],
+/// [`PolyanyaFile::try_from_file`] or [`PolyanyaFile::try_from_reader`], or a complete mesh with
+/// [`Mesh::from_navmesh_reader`]
+#[derive(Error, Debug)]
+pub enum PolyanyaFileError {
+    /// The file couldn't be read.
+    #[error("Failed to read the mesh: {0}")]
+    Io(#[from] std::io::Error),
+    /// A token is not what was expected.
+    #[error("Line {line}: expected {expected}, found `{token}`")]
+    UnexpectedToken {
+        /// Line of the token, starting at 1.
+        line: usize,
+        /// The token found.
+        token: String,
+        /// What was expected instead.
+        expected: &'static str,
+    },
+    /// A line ended before all its values were read.
+    #[error("Line {line}: expected {expected}, found the end of the line")]
+    MissingToken {
+        /// Line that is too short, starting at 1.
+        line: usize,
+        /// What was expected.
+        expected: &'static str,
+    },
+    /// The file ended before the mesh was complete.
+    #[error("Unexpected end of the file after line {line}, expected {expected}")]
+    UnexpectedEnd {
+        /// Last line of the file.
+        line: usize,
+        /// What was expected.
+        expected: &'static str,
+    },
+    /// The file was read, but the mesh it describes is invalid.
+    #[error("The mesh is invalid: {0}")]
+    Mesh(#[from] MeshError),
 }
 
 impl PolyanyaFile {
+    /// Create a `PolyanyaFile` from its vertices and polygons, without obstacles.
+    pub fn new(vertices: Vec<Vertex>, polygons: Vec<Polygon>) -> PolyanyaFile {
+        PolyanyaFile {
+            vertices,
+            polygons,
+            obstacles: vec![],
+        }
+    }
+
+    /// Set the non-traversable faces written by [`Self::to_bytes_v3`].
+    pub fn with_obstacles(mut self, obstacles: Vec<Polygon>) -> PolyanyaFile {
+        self.obstacles = obstacles;
+        self
+    }
+
+    /// List of non-traversable faces described in the file, only in the format `mesh 3`. They
+    /// are not part of the mesh, but are written back by [`Self::to_bytes_v3`].
+    pub fn obstacles(&self) -> &[Polygon] {
+        &self.obstacles
+    }
+
     /// Create a `Mesh` from a file in the formats `mesh 2` or `mesh 3`.
     ///
     /// See <https://github.com/vleue/polyanya/blob/main/meshes/v2/format.txt> for v2 format description, or
     /// <https://github.com/vleue/polyanya/blob/main/meshes/v3/format.txt>
+    ///
+    /// # Panics
+    ///
+    /// Panics if the file can't be read or is not a valid mesh. See [`Self::try_from_file`] for
+    /// a version that returns an error instead.
     pub fn from_file(path: &str) -> PolyanyaFile {
-        let mut file = std::fs::File::open(path).unwrap();
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer).unwrap();
-        Self::from_bytes(&buffer)
+        Self::try_from_file(path)
+            .unwrap_or_else(|error| panic!("Invalid polyanya .mesh file: {error}"))
     }
 
     /// Create a `Mesh` from bytes in the formats `mesh 2` or `mesh 3`.
     ///
     /// See <https://github.com/vleue/polyanya/blob/main/meshes/v2/format.txt> for v2 format description, or
     /// <https://github.com/vleue/polyanya/blob/main/meshes/v3/format.txt>
+    ///
+    /// # Panics
+    ///
+    /// Panics if the bytes are not a valid mesh. See [`Self::try_from_bytes`] for a version that
+    /// returns an error instead.
     pub fn from_bytes(bytes: &[u8]) -> PolyanyaFile {
-        let mut lines = BufReader::new(bytes).lines();
+        Self::try_from_bytes(bytes)
+            .unwrap_or_else(|error| panic!("Invalid polyanya .mesh file: {error}"))
+    }
+
+    /// Create a `Mesh` from a file in the formats `mesh 2` or `mesh 3`, or return where the file
+    /// is invalid.
+    pub fn try_from_file(path: &str) -> Result<PolyanyaFile, PolyanyaFileError> {
+        Self::try_from_reader(std::fs::File::open(path)?)
+    }
+
+    /// Create a `Mesh` from bytes in the formats `mesh 2` or `mesh 3`, or return where the bytes
+    /// are invalid.
+    pub fn try_from_bytes(bytes: &[u8]) -> Result<PolyanyaFile, PolyanyaFileError> {
+        Self::try_from_reader(bytes)
+    }
+
+    /// Create a `Mesh` from a reader in the formats `mesh 2` or `mesh 3`, or return where the
+    /// content is invalid.
+    ///
+    /// The content is read line by line, without loading all of it first.
+    pub fn try_from_reader(reader: impl Read) -> Result<PolyanyaFile, PolyanyaFileError> {
+        let mut lines = MeshLines::new(reader);
 
         // First check the header.
-        if let Some(Ok(first)) = lines.next() {
-            if first != "mesh" {
-                panic!("Invalid polyanya .mesh file");
-            }
-        } else {
-            panic!("Invalid polyanya .mesh file");
+        let header = lines.next("the header `mesh`")?;
+        if header.text.trim() != "mesh" {
+            return Err(header.unexpected("the header `mesh`"));
         }
 
-        let version = if let Some(Ok(version)) = lines.next() {
-            if version == "2" {
-                2
-            } else if version == "3" {
-                3
-            } else {
-                panic!("Invalid polyanya .mesh file");
-            }
-        } else {
-            panic!("Invalid polyanya .mesh file");
+        let version = lines.next("the version")?;
+        let version = match version.text.trim() {
+            "2" => 2,
+            "3" => 3,
+            _ => return Err(version.unexpected("the version `2` or `3`")),
         };
 
-        let (nb_vertices, nb_polygons) = lines
-            .next()
-            .unwrap()
-            .unwrap()
-            .split_once(' ')
-            .map(|(a, b)| (a.parse().unwrap(), b.parse().unwrap()))
-            .unwrap();
+        let counts = lines.next("the number of vertices and polygons")?;
+        let mut tokens = counts.tokens();
+        let nb_vertices: usize = tokens.parse("the number of vertices")?;
+        let nb_polygons: usize = tokens.parse("the number of polygons")?;
+        tokens.end()?;
 
+        // The counts are not trusted to allocate memory, the file may be corrupted
         let mut mesh = PolyanyaFile {
-            vertices: Vec::with_capacity(nb_vertices),
-            polygons: Vec::with_capacity(nb_polygons),
+            vertices: vec![],
+            polygons: vec![],
+            obstacles: vec![],
         };
 
         // Parse the remainder of the file depending on what `.mesh` version it is.
         match version {
-            2 => parse_v2(&mut mesh, lines, nb_vertices, nb_polygons),
-            3 => parse_v3(&mut mesh, lines, nb_vertices, nb_polygons),
-            _ => panic!("Invalid polyanya mesh version"),
+            2 => parse_v2(&mut mesh, &mut lines, nb_vertices, nb_polygons)?,
+            _ => parse_v3(&mut mesh, &mut lines, nb_vertices, nb_polygons)?,
         }
+        lines.end()?;
 
-        mesh
+        Ok(mesh)
     }
 
     /// Write a `Mesh` to a file in the format `mesh 2`.
@@ -135,6 +220,90 @@ impl PolyanyaFile {
         }
         bytes
     }
+
+    /// Write a `Mesh` to a file in the format `mesh 3`.
+    ///
+    /// See <https://github.com/vleue/polyanya/blob/main/meshes/v3/format.txt> for format description.
+    pub fn to_file_v3(&self, path: &str) -> std::io::Result<()> {
+        std::fs::File::create(path)?.write_all(&self.to_bytes_v3())
+    }
+
+    /// Write a `Mesh` to bytes in the format `mesh 3`.
+    ///
+    /// Polygons are written as traversable faces, followed by the obstacles as non-traversable
+    /// faces. Edges with an obstacle on either side can't be traversed and are written with a
+    /// negative neighbour, and edges without a neighbouring face with a neighbour of `0`.
+    ///
+    /// See <https://github.com/vleue/polyanya/blob/main/meshes/v3/format.txt> for format description.
+    pub fn to_bytes_v3(&self) -> Vec<u8> {
+        // Polygons on each side of each edge
+        let faces: Vec<&Polygon> = self.polygons.iter().chain(&self.obstacles).collect();
+        let is_traversable = |face: usize| face < self.polygons.len();
+        let mut edges: HashMap<(u32, u32), Vec<usize>> = HashMap::new();
+        for (index, polygon) in faces.iter().enumerate() {
+            for edge in polygon_edges(polygon) {
+                edges
+                    .entry((edge.0.min(edge.1), edge.0.max(edge.1)))
+                    .or_default()
+                    .push(index);
+            }
+        }
+
+        let mut bytes = Vec::new();
+
+        bytes.extend_from_slice(b"mesh\n");
+        bytes.extend_from_slice(b"3\n");
+        bytes.extend_from_slice(format!("{} {}\n", self.vertices.len(), faces.len()).as_bytes());
+        for vertex in &self.vertices {
+            bytes
+                .extend_from_slice(format!("{} {}\n", vertex.coords.x, vertex.coords.y).as_bytes());
+        }
+        for (index, polygon) in faces.iter().enumerate() {
+            // Vertices and faces are 1-indexed, and 0 is for no neighbour
+            let neighbours = polygon_edges(polygon).map(|edge| {
+                edges[&(edge.0.min(edge.1), edge.0.max(edge.1))]
+                    .iter()
+                    .find(|other| **other != index)
+                    .map_or(0, |other| {
+                        if is_traversable(index) && is_traversable(*other) {
+                            *other as isize + 1
+                        } else {
+                            -(*other as isize + 1)
+                        }
+                    })
+            });
+            bytes.extend_from_slice(
+                format!(
+                    "{} {} {} {}\n",
+                    is_traversable(index) as u8,
+                    polygon.vertices.len(),
+                    polygon
+                        .vertices
+                        .iter()
+                        .map(|v| (v + 1).to_string())
+                        .collect::<Vec<String>>()
+                        .join(" "),
+                    neighbours
+                        .map(|n| n.to_string())
+                        .collect::<Vec<String>>()
+                        .join(" ")
+                )
+                .as_bytes(),
+            );
+        }
+        bytes
+    }
+}
+
+/// Edges of a polygon, the `j`th one going from its vertex `j - 1` to its vertex `j`.
+fn polygon_edges(polygon: &Polygon) -> impl Iterator<Item = (u32, u32)> + '_ {
+    let count = polygon.vertices.len();
+    (0..count).map(move |j| {
+        (
+            polygon.vertices[(j + count - 1) % count],
+            polygon.vertices[j],
+        )
+    })
 }
 
 impl TryFrom<PolyanyaFile> for Mesh {
@@ -145,147 +314,292 @@ impl TryFrom<PolyanyaFile> for Mesh {
     }
 }
 
+/// Only the last layer of the mesh is kept, as the Polyanya formats have a single layer. Use
+/// [`Mesh::write_navmesh`] to keep all of them.
 impl From<Mesh> for PolyanyaFile {
     fn from(mut mesh: Mesh) -> Self {
         let last_layer = mesh.layers.pop().unwrap();
-        PolyanyaFile {
-            vertices: last_layer.vertices,
-            polygons: last_layer.polygons,
+        PolyanyaFile::new(last_layer.vertices, last_layer.polygons)
+    }
+}
+
+/// Lines of a mesh file, skipping empty lines and keeping track of their number.
+pub(crate) struct MeshLines<R: BufRead> {
+    lines: Lines<R>,
+    number: usize,
+}
+
+/// A line of a mesh file, with its number.
+pub(crate) struct Line {
+    pub(crate) number: usize,
+    pub(crate) text: String,
+}
+
+/// Tokens of a line of a mesh file.
+pub(crate) struct Tokens<'a> {
+    line: usize,
+    values: SplitWhitespace<'a>,
+}
+
+impl<R: Read> MeshLines<BufReader<R>> {
+    pub(crate) fn new(reader: R) -> Self {
+        MeshLines {
+            lines: BufReader::new(reader).lines(),
+            number: 0,
         }
     }
 }
 
-/// Create a `PolyanyaFile` from the v2 mesh format
-fn parse_v2(
-    mesh: &mut PolyanyaFile,
-    lines: Lines<BufReader<&[u8]>>,
-    mut nb_vertices: usize,
-    mut nb_polygons: usize,
-) {
-    let mut phase = 1;
-
-    for line in lines {
-        let line: String = line.unwrap();
-        if phase == 1 {
-            if nb_vertices > 0 {
-                nb_vertices -= 1;
-                let mut values = line.split_whitespace();
-                let x = values.next().unwrap().parse().unwrap();
-                let y = values.next().unwrap().parse().unwrap();
-                let _ = values.next();
-                let vertex = Vertex::new(
-                    Vec2::new(x, y),
-                    values.map(|v| v.parse().unwrap_or(u32::MAX)).collect(),
-                );
-                mesh.vertices.push(vertex);
-            } else {
-                phase = 2;
+impl<R: BufRead> MeshLines<R> {
+    /// Next line that is not empty, if any.
+    pub(crate) fn next_line(&mut self) -> Result<Option<Line>, PolyanyaFileError> {
+        for line in self.lines.by_ref() {
+            self.number += 1;
+            let text = line?;
+            if !text.trim().is_empty() {
+                return Ok(Some(Line {
+                    number: self.number,
+                    text,
+                }));
             }
         }
-        if phase == 2 {
-            if nb_polygons > 0 {
-                nb_polygons -= 1;
-                let mut values = line.split_whitespace();
-                let n = values.next().unwrap().parse().unwrap();
-                let polygon = Polygon::using(n, values.map(|v| v.parse().unwrap()).collect());
-                mesh.polygons.push(polygon)
-            } else {
-                panic!("Failed to parse v2 mesh format, unexpected line.");
-            }
+        Ok(None)
+    }
+
+    /// Next line that is not empty, or an error naming what was `expected` if the file ended.
+    pub(crate) fn next(&mut self, expected: &'static str) -> Result<Line, PolyanyaFileError> {
+        self.next_line()?.ok_or(PolyanyaFileError::UnexpectedEnd {
+            line: self.number,
+            expected,
+        })
+    }
+
+    /// Check that there are only empty lines left.
+    pub(crate) fn end(&mut self) -> Result<(), PolyanyaFileError> {
+        match self.next_line()? {
+            Some(line) => Err(line.unexpected("the end of the file")),
+            None => Ok(()),
         }
     }
 }
 
-/// Create a `PolyanyaFile` from the v3 mesh format
-fn parse_v3(
+impl Line {
+    pub(crate) fn tokens(&self) -> Tokens<'_> {
+        Tokens {
+            line: self.number,
+            values: self.text.split_whitespace(),
+        }
+    }
+
+    pub(crate) fn unexpected(&self, expected: &'static str) -> PolyanyaFileError {
+        PolyanyaFileError::UnexpectedToken {
+            line: self.number,
+            token: self.text.clone(),
+            expected,
+        }
+    }
+}
+
+impl Tokens<'_> {
+    /// Parse the next token.
+    pub(crate) fn parse<T: FromStr>(
+        &mut self,
+        expected: &'static str,
+    ) -> Result<T, PolyanyaFileError> {
+        let token = self.values.next().ok_or(PolyanyaFileError::MissingToken {
+            line: self.line,
+            expected,
+        })?;
+        token
+            .parse()
+            .map_err(|_| PolyanyaFileError::UnexpectedToken {
+                line: self.line,
+                token: token.to_string(),
+                expected,
+            })
+    }
+
+    /// Parse the next `count` tokens.
+    pub(crate) fn parse_many<T: FromStr>(
+        &mut self,
+        count: usize,
+        expected: &'static str,
+    ) -> Result<Vec<T>, PolyanyaFileError> {
+        (0..count).map(|_| self.parse(expected)).collect()
+    }
+
+    /// Are there no tokens left.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.values.clone().next().is_none()
+    }
+
+    /// Check that there are no tokens left.
+    pub(crate) fn end(&mut self) -> Result<(), PolyanyaFileError> {
+        match self.values.next() {
+            Some(token) => Err(PolyanyaFileError::UnexpectedToken {
+                line: self.line,
+                token: token.to_string(),
+                expected: "the end of the line",
+            }),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Create a `PolyanyaFile` from the v2 mesh format
+fn parse_v2<R: BufRead>(
     mesh: &mut PolyanyaFile,
-    lines: Lines<BufReader<&[u8]>>,
-    mut nb_vertices: usize,
-    mut nb_polygons: usize,
-) {
-    let mut phase = 1;
+    lines: &mut MeshLines<R>,
+    nb_vertices: usize,
+    nb_polygons: usize,
+) -> Result<(), PolyanyaFileError> {
+    for _ in 0..nb_vertices {
+        let line = lines.next("a vertex")?;
+        let mut tokens = line.tokens();
+        let x = tokens.parse("the x coordinate of a vertex")?;
+        let y = tokens.parse("the y coordinate of a vertex")?;
+        let nb_neighbours = tokens.parse("the number of polygons around a vertex")?;
+        let neighbours: Vec<i64> = tokens.parse_many(nb_neighbours, "a polygon index")?;
+        tokens.end()?;
+        let vertex = Vertex::new(
+            Vec2::new(x, y),
+            neighbours
+                .into_iter()
+                .map(|polygon| u32::try_from(polygon).unwrap_or(u32::MAX))
+                .collect(),
+        );
+        mesh.vertices.push(vertex);
+    }
+    for _ in 0..nb_polygons {
+        let line = lines.next("a polygon")?;
+        let mut tokens = line.tokens();
+        let n = tokens.parse("the number of vertices of a polygon")?;
+        let vertices: Vec<u32> = tokens.parse_many(n, "a vertex index")?;
+        if let Some(vertex) = vertices
+            .iter()
+            .find(|vertex| **vertex as usize >= nb_vertices)
+        {
+            return Err(PolyanyaFileError::UnexpectedToken {
+                line: line.number,
+                token: vertex.to_string(),
+                expected: "a vertex index",
+            });
+        }
+        // The neighbouring polygons are optional
+        let neighbours: Vec<isize> = if tokens.is_empty() {
+            vec![]
+        } else {
+            tokens.parse_many(n, "a polygon index")?
+        };
+        tokens.end()?;
+        let polygon = Polygon::using(
+            n,
+            vertices
+                .into_iter()
+                .map(|vertex| vertex as isize)
+                .chain(neighbours)
+                .collect(),
+        );
+        mesh.polygons.push(polygon)
+    }
+    Ok(())
+}
 
+/// Create a `PolyanyaFile` from the v3 mesh format
+fn parse_v3<R: BufRead>(
+    mesh: &mut PolyanyaFile,
+    lines: &mut MeshLines<R>,
+    nb_vertices: usize,
+    nb_polygons: usize,
+) -> Result<(), PolyanyaFileError> {
     // These are 1-indexed in the v3 mesh file format.
-    let mut vertices = Vec::with_capacity(nb_vertices);
-    let mut vertex_polys: HashMap<usize, Vec<u32>> = HashMap::with_capacity(nb_vertices);
+    let mut vertices = vec![];
+    let mut vertex_polys: HashMap<usize, Vec<u32>> = HashMap::new();
     let mut polygon_index = 0;
 
-    for line in lines {
-        let line: String = line.unwrap();
-        if line.is_empty() {
-            continue;
+    for _ in 0..nb_vertices {
+        let line = lines.next("a vertex")?;
+        let mut tokens = line.tokens();
+        let x = tokens.parse("the x coordinate of a vertex")?;
+        let y = tokens.parse("the y coordinate of a vertex")?;
+        tokens.end()?;
+        vertices.push(Vec2::new(x, y));
+    }
+
+    for _ in 0..nb_polygons {
+        let line = lines.next("a polygon")?;
+        let mut tokens = line.tokens();
+        let is_traversable = match tokens.parse("the traversability of a polygon, `0` or `1`")? {
+            0 => false,
+            1 => true,
+            _ => return Err(line.unexpected("the traversability of a polygon, `0` or `1`")),
+        };
+
+        let num_edges: usize = tokens.parse("the number of edges of a polygon")?;
+        let vertex_indices: Vec<usize> = tokens.parse_many(num_edges, "a vertex index")?;
+        let neighbours: Vec<isize> = tokens.parse_many(num_edges, "a polygon index")?;
+        tokens.end()?;
+        if let Some(vertex) = vertex_indices
+            .iter()
+            .find(|vertex| **vertex == 0 || **vertex > nb_vertices)
+        {
+            return Err(PolyanyaFileError::UnexpectedToken {
+                line: line.number,
+                token: vertex.to_string(),
+                expected: "a vertex index",
+            });
         }
-        if phase == 1 {
-            if nb_vertices > 0 {
-                nb_vertices -= 1;
-                let mut values = line.split_whitespace();
-                let x = values.next().unwrap().parse().unwrap();
-                let y = values.next().unwrap().parse().unwrap();
-                vertices.push(Vec2::new(x, y));
-            } else {
-                phase = 2;
-            }
+
+        // We still need to add the non-traversable polygons to the vertices.
+        for vertex in &vertex_indices {
+            vertex_polys
+                .entry(*vertex)
+                .or_default()
+                .push(match is_traversable {
+                    true => polygon_index,
+                    false => u32::MAX,
+                });
         }
-        if phase == 2 {
-            if nb_polygons > 0 {
-                nb_polygons -= 1;
-                let mut values = line.split_whitespace();
-                let is_traversable = match values.next().unwrap() {
-                    "0" => false,
-                    "1" => true,
-                    _ => panic!("Invalid v3 mesh format."),
-                };
-
-                let num_edges: usize = values.next().unwrap().parse().unwrap();
-                let mut vertex_indices = Vec::with_capacity(num_edges);
-
-                // We still need to add the non-traversable polygons to the vertices.
-                let data = values
-                    .enumerate()
-                    .map(|(i, v)| {
-                        let num: isize = v.parse().unwrap();
-                        if i < num_edges {
-                            let vertex_neighbours = vertex_polys.entry(num as usize).or_default();
-                            vertex_neighbours.push(match is_traversable {
-                                true => polygon_index,
-                                false => u32::MAX,
-                            });
-                            vertex_indices.push(num as usize);
-                        } else {
-                            // Num here is the polygon index.
-                            if num <= -1 {
-                                // We don't care about the specific impassable polygons, just that they're impassable.
-                                return -1;
-                            } else if num == 0 {
-                                // In the mesh-v3 format 0 means that it's the edge of the map and there are no polygons.
-                                vertex_polys
-                                    .entry(vertex_indices[i - num_edges])
-                                    .or_insert(Vec::new())
-                                    .push(u32::MAX);
-                            }
-                        }
-                        // Subtract 1 here since it's 1 indexed, and we're using 0-indexed vecs.
-                        num - 1
-                    })
-                    .collect();
-                if is_traversable {
-                    // Only increment the polygon index if the polygon is traversable since we don't
-                    // care about the non-traversable polygons.
-                    polygon_index += 1;
-                    let polygon = Polygon::using(num_edges, data);
-                    mesh.polygons.push(polygon)
-                }
+        let mut data: Vec<isize> = vertex_indices
+            .iter()
+            // Subtract 1 here since it's 1 indexed, and we're using 0-indexed vecs.
+            .map(|vertex| *vertex as isize - 1)
+            .collect();
+        for (vertex, num) in vertex_indices.iter().zip(&neighbours) {
+            // Num here is the polygon index.
+            if *num <= -1 {
+                // We don't care about the specific impassable polygons, just that they're impassable.
+                data.push(-1);
             } else {
-                panic!("Failed to parse v3 mesh format, unexpected line.");
+                if *num == 0 {
+                    // In the mesh-v3 format 0 means that it's the edge of the map and there are no polygons.
+                    vertex_polys.entry(*vertex).or_default().push(u32::MAX);
+                }
+                data.push(num - 1);
             }
         }
+        if is_traversable {
+            // Only increment the polygon index if the polygon is traversable since we don't
+            // care about the non-traversable polygons.
+            polygon_index += 1;
+            let polygon = Polygon::using(num_edges, data);
+            mesh.polygons.push(polygon)
+        } else {
+            // Keep them to write the file back
+            let vertices = vertex_indices.iter().map(|v| *v as u32 - 1).collect();
+            mesh.obstacles.push(Polygon::new(vertices, false));
+        }
     }
 
     // Add the vertices to the mesh. We do this after parsing since the adjacent polygons for vertices are
     // calculated from the polygons associated with each vertex.
     // TODO: Do we need to de-duplicate adjacent impassable polygons for the vertices?
     for (i, coordinates) in vertices.into_iter().enumerate() {
-        let vert = Vertex::new(coordinates, vertex_polys.remove(&(i + 1)).unwrap());
+        let vert = Vertex::new(
+            coordinates,
+            vertex_polys.remove(&(i + 1)).unwrap_or_default(),
+        );
         mesh.vertices.push(vert);
     }
+    Ok(())
 }
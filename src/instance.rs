@@ -14,15 +14,19 @@ use hashbrown::{hash_map::Entry, HashMap};
 use crate::helpers::EPSILON;
 use crate::{
     helpers::{heuristic, line_intersect_segment, turning_point, Vec2Helper},
+    landmarks::{LandmarkBounds, UNREACHABLE},
+    policy::SearchPolicy,
     Mesh, Path, SearchNode, PRECISION,
 };
 
-pub(crate) struct Root(Vec2);
+/// A root of the search, with the previous root when the cost of a path depends on the direction
+/// it arrives from.
+pub(crate) struct Root(Vec2, Option<Vec2>);
 
 impl PartialEq for Root {
     #[inline(always)]
     fn eq(&self, other: &Self) -> bool {
-        self.0 == other.0
+        self.0 == other.0 && self.1 == other.1
     }
 }
 
@@ -33,6 +37,10 @@ impl std::hash::Hash for Root {
     fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
         ((self.0.x * PRECISION) as i32).hash(state);
         ((self.0.y * PRECISION) as i32).hash(state);
+        if let Some(previous) = self.1 {
+            ((previous.x * PRECISION) as i32).hash(state);
+            ((previous.y * PRECISION) as i32).hash(state);
+        }
     }
 }
 
@@ -57,17 +65,44 @@ pub(crate) struct Successor {
     ty: SuccessorType,
 }
 
+/// Restrictions and costs applied to a search, on top of the mesh.
+#[derive(Default)]
+pub(crate) struct SearchOptions<'m> {
+    /// Layers that can't be used
+    pub(crate) blocked_layers: HashSet<u8>,
+    /// If set, the search can only go through those polygons
+    pub(crate) corridor: Option<&'m HashSet<u32>>,
+    /// If set, the search can't go through those polygons
+    pub(crate) blocked_polygons: Option<&'m HashSet<u32>>,
+    /// If set, polygons steeper than this slope can't be used
+    pub(crate) max_slope: Option<f32>,
+    /// If set, replaces the length of segments and the heuristic, adds turn costs, and tests
+    /// paths reaching the goal
+    pub(crate) policy: Option<&'m dyn SearchPolicy>,
+    /// If set, only the length of the path is computed, without keeping track of the path itself
+    pub(crate) distance_only: bool,
+    /// If set, lower bounds of the distance from each vertex to the goal, for each layer, used to
+    /// improve the heuristic. [`UNREACHABLE`] vertices can't lead to the goal.
+    pub(crate) distances_to_goal: Option<&'m [Vec<f32>]>,
+}
+
 pub(crate) struct SearchInstance<'m> {
     pub(crate) queue: BinaryHeap<SearchNode>,
     pub(crate) node_buffer: Vec<SearchNode>,
     pub(crate) root_history: HashMap<Root, f32>,
-    #[cfg(feature = "detailed-layers")]
     pub(crate) from: (Vec2, u8),
     pub(crate) to: Vec2,
     pub(crate) polygon_from: u32,
     pub(crate) polygon_to: u32,
     pub(crate) mesh: &'m Mesh,
-    pub(crate) blocked_layers: HashSet<u8>,
+    pub(crate) options: SearchOptions<'m>,
+    /// Bounds from the landmarks of the mesh, used to improve the heuristic
+    pub(crate) landmark_bounds: Vec<LandmarkBounds>,
+    /// If set, the search explores the whole mesh and records the distance to every vertex
+    pub(crate) vertex_distances: Option<Vec<Vec<f32>>>,
+    /// Points the distance is recorded to when exploring the whole mesh, with the polygon they
+    /// are in and their distance
+    pub(crate) point_distances: Vec<(Vec2, u32, f32)>,
     #[cfg(feature = "stats")]
     pub(crate) start: Instant,
     #[cfg(feature = "stats")]
@@ -86,8 +121,46 @@ pub(crate) struct SearchInstance<'m> {
     pub(crate) fail_fast: i32,
 }
 
+impl SearchOptions<'_> {
+    /// Is this polygon too steep for this search
+    #[inline(always)]
+    pub(crate) fn is_too_steep(&self, mesh: &Mesh, polygon: u32) -> bool {
+        self.max_slope.is_some_and(|max_slope| {
+            mesh.layers[polygon.layer() as usize].slope(polygon.polygon() as usize) > max_slope
+        })
+    }
+
+    /// Is this polygon excluded from this search, because it's blocked or too steep
+    #[inline(always)]
+    pub(crate) fn excludes(&self, mesh: &Mesh, polygon: u32) -> bool {
+        self.blocked_polygons
+            .is_some_and(|blocked| blocked.contains(&polygon))
+            || self.is_too_steep(mesh, polygon)
+    }
+
+    /// Does the search minimize something else than the length of the path
+    pub(crate) fn has_costs(&self) -> bool {
+        self.policy.is_some()
+    }
+}
+
+/// Cost of turning at `corner` when going from `from` to `to`.
+#[inline(always)]
+fn turn_cost(policy: &dyn SearchPolicy, from: Vec2, corner: Vec2, to: Vec2) -> f32 {
+    if from == corner || corner == to {
+        return 0.0;
+    }
+    policy
+        .turn_cost(corner, (corner - from).angle_to(to - corner).abs())
+        .max(0.0)
+}
+
 pub(crate) enum InstanceStep {
-    Found(Path),
+    /// A path was found, with its cost
+    Found(
+        Path,
+        #[cfg_attr(not(feature = "detailed-layers"), allow(dead_code))] f32,
+    ),
     NotFound,
     Continue,
 }
@@ -122,23 +195,132 @@ impl<'m> SearchInstance<'m> {
         mesh: &'m Mesh,
         from: (Vec2, u32),
         to: (Vec2, u32),
-        blocked_layers: HashSet<u8>,
+        options: SearchOptions<'m>,
         #[cfg(feature = "stats")] start: Instant,
     ) -> Self {
-        let starting_polygon =
-            &mesh.layers[from.1.layer() as usize].polygons[from.1.polygon() as usize];
+        let mut search_instance = Self::new(
+            mesh,
+            from,
+            to,
+            options,
+            #[cfg(feature = "stats")]
+            start,
+        );
+        if let Some(landmarks) = mesh
+            .landmarks
+            .as_ref()
+            .filter(|_| search_instance.options.policy.is_none())
+        {
+            search_instance.landmark_bounds = landmarks.bounds(mesh, from, to);
+        }
+        search_instance.expand_start(from);
+        search_instance
+    }
+
+    /// Compute the length of the shortest path from a point to every vertex of the mesh.
+    ///
+    /// Vertices that can't be reached are at [`UNREACHABLE`].
+    pub(crate) fn distances_from(mesh: &'m Mesh, from: (Vec2, u32)) -> Vec<Vec<f32>> {
+        Self::flood(mesh, from, &[], SearchOptions::default()).0
+    }
+
+    /// Compute the length of the shortest path from a point to other points, with the polygon
+    /// they are in, staying in a corridor.
+    ///
+    /// Points that can't be reached are at [`UNREACHABLE`].
+    pub(crate) fn distances_to_points(
+        mesh: &'m Mesh,
+        from: (Vec2, u32),
+        points: &[(Vec2, u32)],
+        corridor: &'m HashSet<u32>,
+    ) -> Vec<f32> {
+        Self::flood(
+            mesh,
+            from,
+            points,
+            SearchOptions {
+                corridor: Some(corridor),
+                ..Default::default()
+            },
+        )
+        .1
+    }
 
+    /// Explore the mesh from a point, recording the length of the shortest path to every vertex
+    /// and to the given points.
+    fn flood(
+        mesh: &'m Mesh,
+        from: (Vec2, u32),
+        points: &[(Vec2, u32)],
+        options: SearchOptions<'m>,
+    ) -> (Vec<Vec<f32>>, Vec<f32>) {
+        let mut search_instance = Self::new(
+            mesh,
+            from,
+            (from.0, u32::MAX),
+            SearchOptions {
+                distance_only: true,
+                ..options
+            },
+            #[cfg(feature = "stats")]
+            Instant::now(),
+        );
+        let mut distances: Vec<Vec<f32>> = mesh
+            .layers
+            .iter()
+            .map(|layer| vec![UNREACHABLE; layer.vertices.len()])
+            .collect();
+        let from_layer = &mesh.layers[from.1.layer() as usize];
+        for vertex in &from_layer.polygons[from.1.polygon() as usize].vertices {
+            if let Some(v) = from_layer.vertices.get(*vertex as usize) {
+                distances[from.1.layer() as usize][*vertex as usize] =
+                    (v.coords + from_layer.offset).distance(from.0);
+            }
+        }
+        search_instance.vertex_distances = Some(distances);
+        search_instance.point_distances = points
+            .iter()
+            .map(|(point, polygon)| {
+                let distance = if *polygon == from.1 {
+                    point.distance(from.0)
+                } else {
+                    UNREACHABLE
+                };
+                (*point, *polygon, distance)
+            })
+            .collect();
+        search_instance.expand_start(from);
+        while !matches!(search_instance.next(), InstanceStep::NotFound) {}
+        (
+            search_instance.vertex_distances.unwrap(),
+            search_instance
+                .point_distances
+                .into_iter()
+                .map(|(_, _, distance)| distance)
+                .collect(),
+        )
+    }
+
+    fn new(
+        mesh: &'m Mesh,
+        from: (Vec2, u32),
+        to: (Vec2, u32),
+        options: SearchOptions<'m>,
+        #[cfg(feature = "stats")] start: Instant,
+    ) -> Self {
         let mut search_instance = SearchInstance {
             queue: BinaryHeap::with_capacity(15),
             node_buffer: Vec::with_capacity(10),
             root_history: HashMap::with_capacity(10),
-            #[cfg(feature = "detailed-layers")]
             from: (from.0, from.1.layer()),
             to: to.0,
             polygon_to: to.1,
             polygon_from: from.1,
             mesh,
-            blocked_layers,
+            options,
+            landmark_bounds: vec![],
+            vertex_distances: None,
+            point_distances: vec![],
             #[cfg(feature = "stats")]
             start,
             #[cfg(feature = "stats")]
@@ -156,7 +338,14 @@ impl<'m> SearchInstance<'m> {
             #[cfg(debug_assertions)]
             fail_fast: -1,
         };
-        search_instance.root_history.insert(Root(from.0), 0.0);
+        search_instance.root_history.insert(Root(from.0, None), 0.0);
+        search_instance
+    }
+
+    fn expand_start(&mut self, from: (Vec2, u32)) {
+        let mesh = self.mesh;
+        let starting_polygon =
+            &mesh.layers[from.1.layer() as usize].polygons[from.1.polygon() as usize];
 
         let empty_node = SearchNode {
             path: SmallVec::new(),
@@ -164,6 +353,8 @@ impl<'m> SearchInstance<'m> {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from.0,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (Vec2::new(0.0, 0.0), Vec2::new(0.0, 0.0)),
             edge: (0, 0),
             polygon_from: from.1,
@@ -193,20 +384,22 @@ impl<'m> SearchInstance<'m> {
                 .find(|poly| *poly != &from.1)
                 .unwrap_or(&u32::MAX);
 
-            if search_instance.blocked_layers.contains(&other_side.layer()) {
+            if self.is_blocked(*other_side) {
                 continue;
             }
 
-            if other_side == &to.1
-                || (other_side != &u32::MAX
-                    && !search_instance.mesh.layers[other_side.layer() as usize]
+            if other_side != &u32::MAX
+                && (other_side == &self.polygon_to
+                    || self.vertex_distances.is_some()
+                    || !mesh.layers[other_side.layer() as usize]
                         .polygons
                         .get(other_side.polygon() as usize)
                         .unwrap()
                         .is_one_way)
             {
-                search_instance.add_node(
+                self.add_node(
                     from.0,
+                    u32::MAX,
                     *other_side,
                     (start.coords + from_layer.offset, edge0),
                     (end.coords + from_layer.offset, edge1),
@@ -214,8 +407,27 @@ impl<'m> SearchInstance<'m> {
                 );
             }
         }
-        search_instance.flush_nodes();
-        search_instance
+        self.flush_nodes();
+    }
+
+    /// Is this polygon not available for this search, either because its layer is blocked,
+    /// because it's outside of the corridor, or because it's excluded.
+    #[inline(always)]
+    pub(crate) fn is_blocked(&self, polygon: u32) -> bool {
+        self.options.blocked_layers.contains(&polygon.layer())
+            || self
+                .options
+                .corridor
+                .is_some_and(|corridor| !corridor.contains(&polygon))
+            || (polygon != u32::MAX && self.options.excludes(self.mesh, polygon))
+    }
+
+    #[inline(always)]
+    fn has_restrictions(&self) -> bool {
+        !self.options.blocked_layers.is_empty()
+            || self.options.corridor.is_some()
+            || self.options.blocked_polygons.is_some()
+            || self.options.max_slope.is_some()
     }
 
     pub(crate) fn next(&mut self) -> InstanceStep {
@@ -227,7 +439,7 @@ impl<'m> SearchInstance<'m> {
                 self.popped += 1;
             }
 
-            if let Some(o) = self.root_history.get(&Root(next.root)) {
+            if let Some(o) = self.root_history.get(&self.root_key(next.root, &next.path)) {
                 // TODO: revisit this for layers with different height at the same coordinates
                 if o < &next.distance_start_to_root {
                     #[cfg(feature = "verbose")]
@@ -262,6 +474,19 @@ impl<'m> SearchInstance<'m> {
                     );
                     self.mesh.scenarios.set(self.mesh.scenarios.get() + 1);
                 }
+                if self.options.distance_only {
+                    let length = next.distance_start_to_root + next.heuristic;
+                    return InstanceStep::Found(
+                        Path {
+                            length,
+                            path: vec![],
+                            #[cfg(feature = "detailed-layers")]
+                            path_with_layers: vec![],
+                            path_through_polygons: vec![],
+                        },
+                        length,
+                    );
+                }
                 let mut path = next.path;
 
                 let mut path_with_layers_end = vec![];
@@ -317,10 +542,18 @@ impl<'m> SearchInstance<'m> {
                 let mut path_through_polygons = next.path_through_polygons;
                 path_through_polygons.insert(0, self.polygon_from);
 
-                return InstanceStep::Found(Path {
+                let path = Path {
                     path: path.to_vec(),
                     #[cfg(not(feature = "detailed-layers"))]
-                    length: next.distance_start_to_root + next.heuristic,
+                    length: if self.options.policy.is_none() {
+                        next.distance_start_to_root + next.heuristic
+                    } else {
+                        path.iter()
+                            .fold((0.0, self.from.0), |(length, previous), point| {
+                                (length + previous.distance(*point), *point)
+                            })
+                            .0
+                    },
                     #[cfg(feature = "detailed-layers")]
                     length: {
                         let a = path_with_layers.iter().fold((0.0, self.from), |acc, p| {
@@ -333,7 +566,20 @@ impl<'m> SearchInstance<'m> {
                     #[cfg(feature = "detailed-layers")]
                     path_with_layers: path_with_layers.to_vec(),
                     path_through_polygons: path_through_polygons.to_vec(),
-                });
+                };
+                if self
+                    .options
+                    .policy
+                    .is_some_and(|policy| !policy.is_goal(&path))
+                {
+                    return InstanceStep::Continue;
+                }
+                let cost = if self.options.has_costs() {
+                    next.distance_start_to_root + next.heuristic
+                } else {
+                    path.length
+                };
+                return InstanceStep::Found(path, cost);
             }
             self.successors(next);
             return InstanceStep::Continue;
@@ -544,6 +790,7 @@ impl<'m> SearchInstance<'m> {
     pub(crate) fn add_node(
         &mut self,
         root: Vec2,
+        root_vertex: u32,
         other_side: u32,
         start: (Vec2, u32),
         end: (Vec2, u32),
@@ -555,27 +802,49 @@ impl<'m> SearchInstance<'m> {
         }
 
         let mut new_f = node.distance_start_to_root;
+        let track_path = !self.options.distance_only;
 
-        let mut path = node.path.clone();
+        let mut path = if track_path {
+            node.path.clone()
+        } else {
+            SmallVec::new()
+        };
         #[cfg(feature = "detailed-layers")]
-        let mut path_with_layers = node.path_with_layers.clone();
+        let mut path_with_layers = if track_path {
+            node.path_with_layers.clone()
+        } else {
+            SmallVec::new()
+        };
         if root != node.root {
-            path.push(root);
-            #[cfg(feature = "detailed-layers")]
-            path_with_layers.push((root, root, node.polygon_to.layer()));
-            #[cfg(not(feature = "detailed-layers"))]
-            {
-                new_f += node.root.distance(root);
+            if track_path {
+                path.push(root);
+                #[cfg(feature = "detailed-layers")]
+                path_with_layers.push((root, root, node.polygon_to.layer()));
             }
-            #[cfg(feature = "detailed-layers")]
-            {
-                new_f += node
-                    .root
-                    .distance(root * self.mesh.layers[node.polygon_to.layer() as usize].scale);
+            if let Some(policy) = self.options.policy {
+                new_f += self.policy_cost(
+                    policy,
+                    &[node.root, root],
+                    &self.polygons_since_root(node.root_polygon_index, &node.path_through_polygons),
+                );
+                if let Some(previous) = self.previous_root(&node.path) {
+                    new_f += turn_cost(policy, previous, node.root, root);
+                }
+            } else {
+                #[cfg(not(feature = "detailed-layers"))]
+                {
+                    new_f += node.root.distance(root);
+                }
+                #[cfg(feature = "detailed-layers")]
+                {
+                    new_f += node
+                        .root
+                        .distance(root * self.mesh.layers[node.polygon_to.layer() as usize].scale);
+                }
             }
         }
         #[cfg(feature = "detailed-layers")]
-        if other_side.layer() != node.polygon_to.layer() {
+        if track_path && other_side.layer() != node.polygon_to.layer() {
             path_with_layers.push((start.0, end.0, other_side.layer()));
         }
 
@@ -595,6 +864,57 @@ impl<'m> SearchInstance<'m> {
                 ),
             );
         }
+        let path_through_polygons = if track_path {
+            let mut path_through_polygons = node.path_through_polygons.clone();
+            path_through_polygons.push(other_side);
+            path_through_polygons
+        } else {
+            SmallVec::new()
+        };
+        let root_polygon_index = if root != node.root {
+            node.path_through_polygons.len()
+        } else {
+            node.root_polygon_index
+        };
+
+        let heuristic_to_end = if self.vertex_distances.is_some() {
+            0.0
+        } else if other_side == self.polygon_to {
+            // The heuristic of nodes reaching the goal polygon is used for the final path cost,
+            // so it must be exact
+            if let Some(policy) = self.options.policy {
+                let mut points: SmallVec<[Vec2; 3]> = SmallVec::new();
+                points.push(root);
+                points.extend(turning_point(root, self.to, (start.0, end.0)));
+                points.push(self.to);
+                let polygons = self.polygons_since_root(root_polygon_index, &path_through_polygons);
+                self.policy_cost(policy, &points, &polygons)
+                    + self.turn_costs_to_goal(policy, &path, &points)
+            } else {
+                heuristic_to_end
+            }
+        } else if let Some(policy) = self.options.policy {
+            policy.heuristic(root, self.to, (start.0, end.0))
+        } else if let Some(distance) = self.options.distances_to_goal.and_then(|distances| {
+            distances
+                .get(root_vertex.layer() as usize)?
+                .get(root_vertex.polygon() as usize)
+        }) {
+            if *distance == UNREACHABLE {
+                return;
+            }
+            heuristic_to_end.max(*distance)
+        } else if !self.landmark_bounds.is_empty() {
+            heuristic_to_end.max(
+                self.mesh
+                    .landmarks
+                    .as_ref()
+                    .unwrap()
+                    .heuristic(&self.landmark_bounds, root_vertex),
+            )
+        } else {
+            heuristic_to_end
+        };
         if new_f.is_nan() || heuristic_to_end.is_nan() {
             #[cfg(debug_assertions)]
             if self.debug {
@@ -603,8 +923,6 @@ impl<'m> SearchInstance<'m> {
 
             return;
         }
-        let mut path_through_polygons = node.path_through_polygons.clone();
-        path_through_polygons.push(other_side);
 
         let new_node = SearchNode {
             path,
@@ -612,6 +930,8 @@ impl<'m> SearchInstance<'m> {
             path_with_layers,
             path_through_polygons,
             root,
+            root_vertex,
+            root_polygon_index,
             interval: (start.0, end.0),
             edge: (start.1, end.1),
             polygon_from: node.polygon_to,
@@ -621,7 +941,7 @@ impl<'m> SearchInstance<'m> {
             heuristic: heuristic_to_end,
         };
 
-        match self.root_history.entry(Root(root)) {
+        match self.root_history.entry(self.root_key(root, &new_node.path)) {
             Entry::Occupied(mut o) => {
                 if o.get() < &new_node.distance_start_to_root {
                     #[cfg(debug_assertions)]
@@ -695,6 +1015,9 @@ impl<'m> SearchInstance<'m> {
                 self.debug = true;
                 self.fail_fast = 3;
             }
+            if self.vertex_distances.is_some() {
+                self.record_distances(&node);
+            }
             for successor in self.edges_between(&node).iter() {
                 let [successor_edge_0, successor_edge_1] = successor.edge;
                 let target_layer = &self.mesh.layers[node.polygon_to.layer() as usize];
@@ -745,10 +1068,10 @@ impl<'m> SearchInstance<'m> {
                     continue;
                 }
 
-                if self.blocked_layers.contains(&other_side.layer()) {
+                if self.is_blocked(*other_side) {
                     #[cfg(debug_assertions)]
                     if self.debug {
-                        println!("x blocked layer");
+                        println!("x blocked polygon");
                     }
 
                     continue;
@@ -756,6 +1079,7 @@ impl<'m> SearchInstance<'m> {
 
                 // prune edges that only lead to one other polygon, and not the target: dead end pruning
                 if &self.polygon_to != other_side
+                    && self.vertex_distances.is_none()
                     && self.mesh.layers[other_side.layer() as usize].polygons
                         [other_side.polygon() as usize]
                         .is_one_way
@@ -778,7 +1102,7 @@ impl<'m> SearchInstance<'m> {
                 }
 
                 const EPSILON: f32 = 1.0e-10;
-                let root = match successor.ty {
+                let (root, root_vertex) = match successor.ty {
                     SuccessorType::RightNonObservable => {
                         if successor
                             .interval
@@ -797,16 +1121,23 @@ impl<'m> SearchInstance<'m> {
                             .get(node.edge.0 as usize)
                             .unwrap();
                         if (vertex.is_corner
-                            || (!self.blocked_layers.is_empty()
-                                && vertex.polygons.iter().any(|p| {
-                                    *p == u32::MAX || self.blocked_layers.contains(&p.layer())
-                                })))
+                            || (self.has_restrictions()
+                                && vertex
+                                    .polygons
+                                    .iter()
+                                    .any(|p| *p == u32::MAX || self.is_blocked(*p))))
                             && (vertex.coords
                                 + self.mesh.layers[node.previous_polygon_layer as usize].offset)
                                 .distance_squared(node.interval.0)
                                 < EPSILON
                         {
-                            node.interval.0
+                            (
+                                node.interval.0,
+                                u32::from_layer_and_polygon(
+                                    node.previous_polygon_layer,
+                                    node.edge.0,
+                                ),
+                            )
                         } else {
                             #[cfg(debug_assertions)]
                             if self.debug {
@@ -815,7 +1146,7 @@ impl<'m> SearchInstance<'m> {
                             continue;
                         }
                     }
-                    SuccessorType::Observable => node.root,
+                    SuccessorType::Observable => (node.root, node.root_vertex),
                     SuccessorType::LeftNonObservable => {
                         if (successor.interval.1).distance_squared(end.coords + target_layer.offset)
                             > EPSILON
@@ -831,16 +1162,23 @@ impl<'m> SearchInstance<'m> {
                             .get(node.edge.1 as usize)
                             .unwrap();
                         if (vertex.is_corner
-                            || (!self.blocked_layers.is_empty()
-                                && vertex.polygons.iter().any(|p| {
-                                    *p == u32::MAX || self.blocked_layers.contains(&p.layer())
-                                })))
+                            || (self.has_restrictions()
+                                && vertex
+                                    .polygons
+                                    .iter()
+                                    .any(|p| *p == u32::MAX || self.is_blocked(*p))))
                             && (vertex.coords
                                 + self.mesh.layers[node.previous_polygon_layer as usize].offset)
                                 .distance_squared(node.interval.1)
                                 < EPSILON
                         {
-                            node.interval.1
+                            (
+                                node.interval.1,
+                                u32::from_layer_and_polygon(
+                                    node.previous_polygon_layer,
+                                    node.edge.1,
+                                ),
+                            )
                         } else {
                             #[cfg(debug_assertions)]
                             if self.debug {
@@ -867,6 +1205,7 @@ impl<'m> SearchInstance<'m> {
 
                 self.add_node(
                     root,
+                    root_vertex,
                     *other_side,
                     (successor.interval.0, successor_edge_0),
                     (successor.interval.1, successor_edge_1),
@@ -921,4 +1260,110 @@ impl<'m> SearchInstance<'m> {
         }
         self.flush_nodes();
     }
+
+    /// Polygons a path goes through since its root, from the polygons it went through since the start.
+    #[inline(always)]
+    fn polygons_since_root(
+        &self,
+        root_polygon_index: usize,
+        path_through_polygons: &[u32],
+    ) -> SmallVec<[u32; 10]> {
+        std::iter::once(self.polygon_from)
+            .chain(path_through_polygons.iter().copied())
+            .skip(root_polygon_index)
+            .collect()
+    }
+
+    /// Cost of a path following a policy.
+    #[inline(always)]
+    fn policy_cost(&self, policy: &dyn SearchPolicy, points: &[Vec2], polygons: &[u32]) -> f32 {
+        let mut cost = 0.0;
+        let mut previous: Option<Vec2> = None;
+        self.mesh
+            .heights_along(points, polygons, |point, _, polygon| {
+                if let Some(previous) = previous {
+                    cost += policy
+                        .segment_cost(previous, point, polygon.layer(), polygon.polygon())
+                        .max(0.0);
+                }
+                previous = Some(point);
+            });
+        cost
+    }
+
+    /// Cost of the turns of a policy when going from the root to the goal through `points`.
+    /// `path` is the path from the start to the root.
+    #[inline(always)]
+    fn turn_costs_to_goal(&self, policy: &dyn SearchPolicy, path: &[Vec2], points: &[Vec2]) -> f32 {
+        self.previous_root(path)
+            .into_iter()
+            .chain(points.iter().copied())
+            .collect::<SmallVec<[Vec2; 4]>>()
+            .windows(3)
+            .map(|corner| turn_cost(policy, corner[0], corner[1], corner[2]))
+            .sum::<f32>()
+    }
+
+    /// Root before the last one of a path, if the last root is not the start of the search.
+    #[inline(always)]
+    fn previous_root(&self, path: &[Vec2]) -> Option<Vec2> {
+        match path.len() {
+            0 => None,
+            1 => Some(self.from.0),
+            n => Some(path[n - 2]),
+        }
+    }
+
+    /// Key of a root in the history. The previous root is part of it when the policy may have
+    /// turn costs, as the best way to a root then depends on where it's coming from.
+    #[inline(always)]
+    fn root_key(&self, root: Vec2, path: &[Vec2]) -> Root {
+        if self.options.policy.is_some() {
+            Root(root, self.previous_root(path))
+        } else {
+            Root(root, None)
+        }
+    }
+
+    /// Record the distance to the vertices of the polygon the node is going to, and to the points
+    /// in it, if they are visible from its root.
+    fn record_distances(&mut self, node: &SearchNode) {
+        let mesh = self.mesh;
+        let Some(distances) = self.vertex_distances.as_mut() else {
+            return;
+        };
+        let layer = &mesh.layers[node.polygon_to.layer() as usize];
+        let distances = &mut distances[node.polygon_to.layer() as usize];
+        for vertex in &layer.polygons[node.polygon_to.polygon() as usize].vertices {
+            let Some(v) = layer.vertices.get(*vertex as usize) else {
+                continue;
+            };
+            let distance = &mut distances[*vertex as usize];
+            *distance = distance.min(
+                node.distance_start_to_root
+                    + Self::distance_in_polygon(node, v.coords + layer.offset),
+            );
+        }
+        for (point, polygon, distance) in self.point_distances.iter_mut() {
+            if *polygon == node.polygon_to {
+                *distance = distance
+                    .min(node.distance_start_to_root + Self::distance_in_polygon(node, *point));
+            }
+        }
+    }
+
+    /// Distance from the root of a node to a point in the polygon it's going to.
+    fn distance_in_polygon(node: &SearchNode, point: Vec2) -> f32 {
+        // Points outside of the interval can still be reached by turning at its ends, as the
+        // polygon is convex. This is how the path to a goal in this polygon would end, so each
+        // distance is the length of a path and no shorter than any path through this node, and
+        // the shortest one recorded for a point is exact
+        if point.side((node.root, node.interval.0)) == EdgeSide::Right {
+            node.root.distance(node.interval.0) + node.interval.0.distance(point)
+        } else if point.side((node.root, node.interval.1)) == EdgeSide::Left {
+            node.root.distance(node.interval.1) + node.interval.1.distance(point)
+        } else {
+            node.root.distance(point)
+        }
+    }
 }
@@ -0,0 +1,245 @@
+use glam::Vec2;
+#[cfg(feature = "tracing")]
+use tracing::instrument;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    instance::{SearchInstance, U32Layer},
+    Mesh,
+};
+
+/// Distance used for vertices that can't be reached from a landmark.
+pub(crate) const UNREACHABLE: f32 = f32::MAX;
+
+/// Landmarks of a [`Mesh`], baked by [`Mesh::bake_landmarks`].
+///
+/// The exact distance from each landmark to every vertex of the mesh is stored, and used during
+/// searches to get a better estimate of the remaining distance to the goal thanks to the
+/// triangle inequality (ALT heuristic).
+///
+/// When layers are stitched one way, distances are not the same in both directions, and only
+/// the distances from the landmarks can be used, which gives a weaker estimate.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Landmarks {
+    /// Vertex of each landmark, with its layer.
+    pub(crate) vertices: Vec<u32>,
+    /// Distance from each landmark to each vertex, for each layer. [`UNREACHABLE`] if there is no path.
+    pub(crate) distances: Vec<Vec<Vec<f32>>>,
+    /// If the distance between two points is the same in both directions.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub(crate) symmetric: bool,
+}
+
+/// Bounds of the distances from a landmark to the start and to the goal of a search.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct LandmarkBounds {
+    landmark: usize,
+    start: (f32, f32),
+    goal: (f32, f32),
+}
+
+impl Landmarks {
+    /// Number of landmarks.
+    pub fn count(&self) -> usize {
+        self.vertices.len()
+    }
+
+    /// Vertex used as a landmark, as its layer and its index in the layer.
+    pub fn landmark(&self, landmark: usize) -> Option<(u8, u32)> {
+        self.vertices
+            .get(landmark)
+            .map(|vertex| (vertex.layer(), vertex.polygon()))
+    }
+
+    /// Length of the shortest path from a landmark to a vertex, or `None` if there is no path.
+    pub fn distance(&self, landmark: usize, layer: u8, vertex: u32) -> Option<f32> {
+        self.distances
+            .get(landmark)
+            .and_then(|layers| layers.get(layer as usize))
+            .and_then(|vertices| vertices.get(vertex as usize))
+            .copied()
+            .filter(|distance| *distance != UNREACHABLE)
+    }
+
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    fn build(mesh: &Mesh, count: usize) -> Self {
+        let mut landmarks = Landmarks {
+            vertices: vec![],
+            distances: vec![],
+            symmetric: !mesh.has_one_way_links(),
+        };
+        let Some(first) = mesh
+            .layers
+            .iter()
+            .enumerate()
+            .flat_map(|(layer_index, layer)| {
+                (0..layer.vertices.len() as u32)
+                    .map(move |vertex| u32::from_layer_and_polygon(layer_index as u8, vertex))
+            })
+            .find(|vertex| mesh.vertex_location(*vertex).is_some())
+        else {
+            return landmarks;
+        };
+        if count == 0 {
+            return landmarks;
+        }
+
+        // Start from the vertex farthest from an arbitrary vertex, then keep adding the vertex
+        // farthest from all the landmarks already selected
+        let mut closest_landmark =
+            SearchInstance::distances_from(mesh, mesh.vertex_location(first).unwrap());
+        while landmarks.count() < count {
+            let Some((vertex, distance)) = closest_landmark
+                .iter()
+                .enumerate()
+                .flat_map(|(layer_index, distances)| {
+                    distances.iter().enumerate().map(move |(vertex, distance)| {
+                        (
+                            u32::from_layer_and_polygon(layer_index as u8, vertex as u32),
+                            *distance,
+                        )
+                    })
+                })
+                .filter(|(vertex, _)| mesh.vertex_location(*vertex).is_some())
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+            else {
+                break;
+            };
+            if distance == 0.0 {
+                break;
+            }
+
+            let distances =
+                SearchInstance::distances_from(mesh, mesh.vertex_location(vertex).unwrap());
+            if landmarks.vertices.is_empty() {
+                closest_landmark = distances.clone();
+            } else {
+                for (closest, new) in closest_landmark
+                    .iter_mut()
+                    .flatten()
+                    .zip(distances.iter().flatten())
+                {
+                    *closest = closest.min(*new);
+                }
+            }
+            landmarks.vertices.push(vertex);
+            landmarks.distances.push(distances);
+        }
+        landmarks
+    }
+
+    /// Lowest and highest possible distance from a landmark to a point in a polygon.
+    fn bounds_in_polygon(
+        &self,
+        mesh: &Mesh,
+        landmark: usize,
+        point: Vec2,
+        polygon: u32,
+    ) -> Option<(f32, f32)> {
+        let layer = &mesh.layers[polygon.layer() as usize];
+        let distances = &self.distances[landmark][polygon.layer() as usize];
+        layer.polygons[polygon.polygon() as usize]
+            .vertices
+            .iter()
+            .filter_map(|vertex| {
+                let distance = *distances.get(*vertex as usize)?;
+                if distance == UNREACHABLE {
+                    return None;
+                }
+                let to_point =
+                    (layer.vertices[*vertex as usize].coords + layer.offset).distance(point);
+                Some((distance - to_point, distance + to_point))
+            })
+            .reduce(|a, b| (a.0.max(b.0), a.1.min(b.1)))
+    }
+
+    /// Bounds of the distances from each landmark to the start and to the goal of a search.
+    pub(crate) fn bounds(
+        &self,
+        mesh: &Mesh,
+        from: (Vec2, u32),
+        to: (Vec2, u32),
+    ) -> Vec<LandmarkBounds> {
+        (0..self.count())
+            .filter_map(|landmark| {
+                Some(LandmarkBounds {
+                    landmark,
+                    start: self.bounds_in_polygon(mesh, landmark, from.0, from.1)?,
+                    goal: self.bounds_in_polygon(mesh, landmark, to.0, to.1)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Lower bound of the distance from a root to the goal. `root_vertex` is the vertex of the
+    /// root with its layer, or `u32::MAX` if the root is the start of the search.
+    #[inline(always)]
+    pub(crate) fn heuristic(&self, bounds: &[LandmarkBounds], root_vertex: u32) -> f32 {
+        bounds
+            .iter()
+            .filter_map(|bounds| {
+                let (low, high) = if root_vertex == u32::MAX {
+                    bounds.start
+                } else {
+                    let distance = *self.distances[bounds.landmark]
+                        .get(root_vertex.layer() as usize)?
+                        .get(root_vertex.polygon() as usize)?;
+                    if distance == UNREACHABLE {
+                        return None;
+                    }
+                    (distance, distance)
+                };
+                // From the landmark, the goal can't be closer than by going through the root
+                let from_landmark = bounds.goal.0 - high;
+                if self.symmetric {
+                    // To the landmark, the root can't be closer than by going through the goal
+                    Some(from_landmark.max(low - bounds.goal.1))
+                } else {
+                    Some(from_landmark)
+                }
+            })
+            .fold(0.0, f32::max)
+    }
+}
+
+impl Mesh {
+    /// Pre-compute landmarks to speed up searches with a better heuristic.
+    ///
+    /// `count` landmarks are picked as far as possible from each other, and the length of the
+    /// shortest path from each of them to every vertex of the mesh is stored. This takes memory
+    /// proportional to `count` times the number of vertices, and baking can take a while on big
+    /// meshes. A few landmarks are usually enough.
+    ///
+    /// Like the other pre-computed optimizations, this must be called again after the mesh is modified.
+    /// When deserializing a mesh, the landmarks are dropped if they don't match the mesh anymore.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn bake_landmarks(&mut self, count: usize) {
+        self.landmarks = None;
+        self.landmarks = Some(Landmarks::build(self, count));
+    }
+
+    /// The landmarks baked by [`Mesh::bake_landmarks`], if any.
+    pub fn landmarks(&self) -> Option<&Landmarks> {
+        self.landmarks.as_ref()
+    }
+
+    /// Position of a vertex with a polygon of its layer it belongs to, if it's part of one.
+    fn vertex_location(&self, vertex: u32) -> Option<(Vec2, u32)> {
+        let layer = &self.layers[vertex.layer() as usize];
+        let v = &layer.vertices[vertex.polygon() as usize];
+        v.polygons
+            .iter()
+            .find(|polygon| {
+                **polygon != u32::MAX
+                    && polygon.layer() == vertex.layer()
+                    && self.layers[polygon.layer() as usize]
+                        .polygons
+                        .get(polygon.polygon() as usize)
+                        .is_some_and(|polygon| !polygon.vertices.is_empty())
+            })
+            .map(|polygon| (v.coords + layer.offset, *polygon))
+    }
+}
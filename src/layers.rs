@@ -2,16 +2,22 @@
 use tracing::instrument;
 
 use bvh2d::bvh2d::BVH2d;
-use glam::{vec2, Vec2};
+use glam::{vec2, Vec2, Vec3, Vec3Swizzles};
 
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 
+#[cfg(not(feature = "no-default-baking"))]
+use crate::instance::U32Layer;
 use crate::{helpers::Vec2Helper, instance::EdgeSide, BoundedPolygon, MeshError, Polygon, Vertex};
 
 /// Layer of a NavMesh
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize),
+    serde(from = "crate::serialization::SerializedLayer")
+)]
 pub struct Layer {
     /// List of `Vertex` in this mesh
     pub vertices: Vec<Vertex>,
@@ -25,7 +31,10 @@ pub struct Layer {
     pub scale: Vec2,
     pub(crate) baked_polygons: Option<BVH2d>,
     pub(crate) islands: Option<Vec<usize>>,
+    pub(crate) slopes: Option<Vec<f32>>,
     /// Height of each vertex. Must either have zero elements to ignore heights, or the same length as vertices.
+    ///
+    /// Baked slopes are not updated when heights change, call [`Self::bake_slopes`] again.
     pub height: Vec<f32>,
 }
 
@@ -39,6 +48,7 @@ impl Default for Layer {
             scale: Vec2::ONE,
             baked_polygons: None,
             islands: None,
+            slopes: None,
             height: vec![],
         }
     }
@@ -50,6 +60,7 @@ impl Layer {
     pub fn unbake(&mut self) {
         self.baked_polygons = None;
         self.islands = None;
+        self.slopes = None;
     }
 
     /// Pre-compute optimizations on the mesh
@@ -59,9 +70,91 @@ impl Layer {
     /// Optimisations available are:
     /// - [`Self::bake_polygon_finder`]
     /// - [`Self::bake_islands_detection`]
+    /// - [`Self::bake_slopes`]
     pub fn bake(&mut self) {
         self.bake_polygon_finder();
-        self.bake_islands_detection()
+        self.bake_islands_detection();
+        self.bake_slopes();
+    }
+
+    /// Bake a layer that was loaded, keeping what was already baked.
+    ///
+    /// Islands detection only supports polygons without a layer in their index, so it is skipped
+    /// when a vertex of the layer references a polygon of another layer than `0`. This is the case
+    /// for every layer but the first one once a mesh is stitched, and for the first one when it's
+    /// stitched to other layers.
+    #[cfg(not(feature = "no-default-baking"))]
+    pub(crate) fn bake_loaded(&mut self) {
+        if self.baked_polygons.is_none() {
+            self.bake_polygon_finder();
+        }
+        if self.slopes.is_none() {
+            self.bake_slopes();
+        }
+        if self.islands.is_none()
+            && self
+                .vertices
+                .iter()
+                .flat_map(|vertex| vertex.polygons.iter())
+                .all(|polygon| *polygon == u32::MAX || polygon.layer() == 0)
+        {
+            self.bake_islands_detection();
+        }
+    }
+
+    /// Speed up searches with a maximum slope, like [`Mesh::path_with_max_slope`](crate::Mesh::path_with_max_slope).
+    ///
+    /// Computes the slope of each polygon from the height of its vertices. Must be called again
+    /// after changing [`Self::height`], as baked slopes are not updated.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn bake_slopes(&mut self) {
+        let slopes = (0..self.polygons.len())
+            .map(|polygon| self.compute_slope(polygon))
+            .collect();
+        self.slopes = Some(slopes);
+    }
+
+    /// Slope of a polygon, in radians. `0.0` is flat, and `PI / 2` is vertical.
+    ///
+    /// Uses the value from [`Self::bake_slopes`] if available.
+    #[inline]
+    pub fn slope(&self, polygon: usize) -> f32 {
+        match self.slopes.as_ref() {
+            Some(slopes) => slopes.get(polygon).copied().unwrap_or(0.0),
+            None => self.compute_slope(polygon),
+        }
+    }
+
+    fn compute_slope(&self, polygon: usize) -> f32 {
+        if self.height.is_empty() {
+            return 0.0;
+        }
+        let Some(polygon) = self.polygons.get(polygon) else {
+            return 0.0;
+        };
+        // Newell's method, to support polygons that are not planar
+        let point = |vertex: u32| {
+            self.vertices[vertex as usize]
+                .coords
+                .extend(self.height.get(vertex as usize).copied().unwrap_or(0.0))
+        };
+        let normal = polygon
+            .vertices
+            .iter()
+            .zip(polygon.vertices.iter().cycle().skip(1))
+            .fold(Vec3::ZERO, |normal, (a, b)| {
+                let (a, b) = (point(*a), point(*b));
+                normal
+                    + Vec3::new(
+                        (a.y - b.y) * (a.z + b.z),
+                        (a.z - b.z) * (a.x + b.x),
+                        (a.x - b.x) * (a.y + b.y),
+                    )
+            });
+        if normal == Vec3::ZERO {
+            return 0.0;
+        }
+        normal.xy().length().atan2(normal.z.abs())
     }
 
     /// Speed up bailing out if two points are not reachable.
@@ -397,7 +490,7 @@ impl Layer {
 
 #[cfg(test)]
 mod tests {
-    use std::{collections::HashSet, u32};
+    use std::collections::HashSet;
 
     #[cfg(feature = "detailed-layers")]
     use crate::helpers::line_intersect_segment;
@@ -479,6 +572,28 @@ mod tests {
         );
     }
 
+    #[test]
+    fn polygon_slopes() {
+        let mut layer = mesh_u_grid().layers.remove(0);
+        assert_eq!(layer.slope(0), 0.0);
+        layer.unbake();
+
+        // Raise the top side of the middle polygon, which is a corner of the other two
+        layer.height = vec![0.0, 0.0, 0.0, 0.0, 0.0, 1.0, 1.0, 0.0];
+        assert!((layer.slope(1) - std::f32::consts::FRAC_PI_4).abs() < 0.0001);
+        assert!((layer.slope(0) - layer.slope(2)).abs() < 0.0001);
+        assert!(layer.slope(0) > 0.0);
+        assert!(layer.slope(0) < layer.slope(1));
+
+        let computed = (0..3)
+            .map(|polygon| layer.slope(polygon))
+            .collect::<Vec<_>>();
+        layer.bake_slopes();
+        assert_eq!(layer.slopes, Some(computed));
+        layer.unbake();
+        assert_eq!(layer.slopes, None);
+    }
+
     #[test]
     fn successors_straight_line() {
         let mesh = mesh_u_grid();
@@ -491,6 +606,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(0.0, 1.0), vec2(1.0, 1.0)),
             edge: (0, 1),
             polygon_from: mesh.get_point_location(from),
@@ -534,6 +651,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(0.0, 1.0), vec2(1.0, 1.0)),
             edge: (4, 5),
             polygon_from: mesh.get_point_location(from),
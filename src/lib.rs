@@ -33,31 +33,64 @@ use thiserror::Error;
 use tracing::instrument;
 
 #[cfg(feature = "serde")]
-use serde::{Deserialize, Serialize};
+use serde::Deserialize;
 
+mod alternatives;
 #[cfg(feature = "async")]
 mod async_helpers;
+mod cached_search;
+mod costs;
+mod heights;
 mod helpers;
+mod hierarchy;
 mod input;
 mod instance;
+mod landmarks;
 mod layers;
+mod line_of_sight;
 mod merger;
 mod mesh_cleanup;
+mod policy;
 mod primitives;
+mod repair;
+mod sampling;
+mod segments;
+#[cfg(feature = "serde")]
+mod serialization;
+mod smoothing;
 mod stitching;
+mod waypoints;
 
 #[cfg(feature = "async")]
 pub use async_helpers::FuturePath;
+pub use cached_search::CachedSearch;
+pub use costs::{CostZone, TurnPenalty, ZoneShape};
 pub use geo;
-pub use input::polyanya_file::PolyanyaFile;
+#[cfg(feature = "geojson")]
+pub use geojson;
+pub use heights::HeightCost;
+pub use hierarchy::Hierarchy;
+pub use input::binary_mesh::BinaryMeshError;
+pub use input::contours::ScalarField;
+#[cfg(feature = "recast")]
+pub use input::detour::{DetourTile, DetourTileError, OffMeshConnection};
+#[cfg(feature = "geojson")]
+pub use input::geojson::GeoJsonError;
+pub use input::obj_file::ObjFileError;
+pub use input::polyanya_file::{PolyanyaFile, PolyanyaFileError};
 #[cfg(feature = "recast")]
 pub use input::recast::{RecastFullMesh, RecastPolyMesh, RecastPolyMeshDetail};
 pub use input::triangulation::Triangulation;
 pub use input::trimesh::Trimesh;
+pub use landmarks::Landmarks;
 pub use layers::Layer;
+pub use policy::{SearchPolicy, ShortestPath};
 pub use primitives::{Polygon, Vertex};
+pub use repair::PathError;
+pub use segments::{PathSegment, PortalCrossing};
+pub use waypoints::{WaypointError, WaypointPath};
 
-use crate::instance::SearchInstance;
+use crate::instance::{SearchInstance, SearchOptions};
 
 /// A path between two points.
 #[derive(Debug, PartialEq)]
@@ -177,7 +210,11 @@ impl Path {
 
 /// A navigation mesh
 #[derive(Debug, Clone)]
-#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(
+    feature = "serde",
+    derive(Deserialize),
+    serde(from = "crate::serialization::SerializedMesh")
+)]
 pub struct Mesh {
     /// Layers of the NavMesh
     pub layers: Vec<Layer>,
@@ -185,6 +222,8 @@ pub struct Mesh {
     pub search_delta: f32,
     /// Number of steps before stopping searching for a point in a mesh
     pub search_steps: u32,
+    pub(crate) hierarchy: Option<Hierarchy>,
+    pub(crate) landmarks: Option<Landmarks>,
     #[cfg(feature = "stats")]
     pub(crate) scenarios: Cell<u32>,
 }
@@ -299,6 +338,8 @@ impl Default for Mesh {
             layers: vec![],
             search_delta: 0.1,
             search_steps: 2,
+            hierarchy: None,
+            landmarks: None,
             #[cfg(feature = "stats")]
             scenarios: Cell::new(0),
         }
@@ -356,6 +397,8 @@ impl Mesh {
         for layer in self.layers.iter_mut() {
             layer.unbake();
         }
+        self.hierarchy = None;
+        self.landmarks = None;
     }
 
     /// Compute a path between two points.
@@ -377,11 +420,30 @@ impl Mesh {
     ///
     /// This will be a [`Path`] if a path is found, or `None` if not.
     ///
-    /// This method is blocking, to get the path in an async way use [`Self::get_path`].
+    /// This method is blocking, to get the path in an async way use [`Self::get_path`]. It uses
+    /// the default [`ShortestPath`] policy, see [`Self::path_with_policy`] for other policies.
     #[cfg_attr(feature = "tracing", instrument(skip_all))]
     #[inline(always)]
     pub fn path(&self, from: impl Into<Coords>, to: impl Into<Coords>) -> Option<Path> {
-        self.path_on_layers(from, to, HashSet::default())
+        self.path_with_policy(from, to, &ShortestPath)
+    }
+
+    /// Compute the length of the shortest path between two points, or `None` if there is no path.
+    ///
+    /// This is faster and uses less memory than [`Self::path`], as the path itself is not kept
+    /// during the search.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    #[inline(always)]
+    pub fn distance(&self, from: impl Into<Coords>, to: impl Into<Coords>) -> Option<f32> {
+        self.path_with_options(
+            from.into(),
+            to.into(),
+            SearchOptions {
+                distance_only: true,
+                ..Default::default()
+            },
+        )
+        .map(|path| path.length)
     }
 
     /// Compute a path between two points.
@@ -396,25 +458,43 @@ impl Mesh {
         from: impl Into<Coords>,
         to: impl Into<Coords>,
         blocked_layers: HashSet<u8>,
+    ) -> Option<Path> {
+        self.path_with_options(
+            from.into(),
+            to.into(),
+            SearchOptions {
+                blocked_layers,
+                ..Default::default()
+            },
+        )
+    }
+
+    /// Compute a path between two points, with restrictions and costs from `options`.
+    pub(crate) fn path_with_options(
+        &self,
+        from: Coords,
+        to: Coords,
+        options: SearchOptions,
     ) -> Option<Path> {
         #[cfg(feature = "stats")]
         let start = Instant::now();
 
-        let from = from.into();
-        let to = to.into();
-
         let starting_polygon_index = if from.polygon_index != u32::MAX {
             from.polygon_index
         } else {
-            self.get_closest_point_on_layers(from, blocked_layers.clone())?
+            self.get_closest_point_on_layers(from, options.blocked_layers.clone())?
                 .polygon_index
         };
         let ending_polygon = if to.polygon_index != u32::MAX {
             to.polygon_index
         } else {
-            self.get_closest_point_on_layers(to, blocked_layers.clone())?
+            self.get_closest_point_on_layers(to, options.blocked_layers.clone())?
                 .polygon_index
         };
+        if options.excludes(self, starting_polygon_index) || options.excludes(self, ending_polygon)
+        {
+            return None;
+        }
         // TODO: fix islands detection with multiple layers, even if start and end are on the same layer
         if self.layers.len() == 1 {
             if let Some(islands) = self.layers[starting_polygon_index.layer() as usize]
@@ -430,6 +510,16 @@ impl Mesh {
         }
 
         if starting_polygon_index == ending_polygon {
+            let path = Path {
+                length: from.pos.distance(to.pos),
+                path: vec![to.pos],
+                #[cfg(feature = "detailed-layers")]
+                path_with_layers: vec![(to.pos, ending_polygon.layer())],
+                path_through_polygons: vec![ending_polygon],
+            };
+            if options.policy.is_some_and(|policy| !policy.is_goal(&path)) {
+                return None;
+            }
             #[cfg(feature = "stats")]
             {
                 if self.scenarios.get() == 0 {
@@ -445,32 +535,26 @@ impl Mesh {
                 );
                 self.scenarios.set(self.scenarios.get() + 1);
             }
-            return Some(Path {
-                length: from.pos.distance(to.pos),
-                path: vec![to.pos],
-                #[cfg(feature = "detailed-layers")]
-                path_with_layers: vec![(to.pos, ending_polygon.layer())],
-                path_through_polygons: vec![ending_polygon],
-            });
+            return Some(path);
         }
 
         let mut search_instance = SearchInstance::setup(
             self,
             (from.pos, starting_polygon_index),
             (to.pos, ending_polygon),
-            blocked_layers,
+            options,
             #[cfg(feature = "stats")]
             start,
         );
 
-        let mut paths: Vec<Path> = vec![];
+        let mut paths: Vec<(Path, f32)> = vec![];
         // Limit search to avoid an infinite loop.
         for _ in 0..self.layers.iter().map(|l| l.polygons.len()).sum::<usize>() * 10 {
             let _potential_path = match search_instance.next() {
                 #[cfg(not(feature = "detailed-layers"))]
-                InstanceStep::Found(path) => return Some(path),
+                InstanceStep::Found(path, _) => return Some(path),
                 #[cfg(feature = "detailed-layers")]
-                InstanceStep::Found(path) => Some(path),
+                InstanceStep::Found(path, cost) => Some((path, cost)),
                 InstanceStep::NotFound => {
                     if paths.is_empty() {
                         None
@@ -486,11 +570,11 @@ impl Mesh {
             }
         }
         #[cfg(feature = "detailed-layers")]
-        paths.sort_unstable_by(|p1, p2| p1.length.partial_cmp(&p2.length).unwrap());
+        paths.sort_unstable_by(|p1, p2| p1.1.partial_cmp(&p2.1).unwrap());
         if paths.is_empty() {
             None
         } else {
-            Some(paths.remove(0))
+            Some(paths.remove(0).0)
         }
     }
 
@@ -536,13 +620,15 @@ impl Mesh {
             queue: BinaryHeap::new(),
             node_buffer: Vec::new(),
             root_history: HashMap::new(),
-            #[cfg(feature = "detailed-layers")]
             from: (node.root, 0),
             to,
             polygon_to: self.get_point_location(to),
             polygon_from: 0,
             mesh: self,
-            blocked_layers: HashSet::default(),
+            options: SearchOptions::default(),
+            landmark_bounds: vec![],
+            vertex_distances: None,
+            point_distances: vec![],
             #[cfg(feature = "stats")]
             pushed: 0,
             #[cfg(feature = "stats")]
@@ -575,13 +661,15 @@ impl Mesh {
             queue: BinaryHeap::new(),
             node_buffer: Vec::new(),
             root_history: HashMap::new(),
-            #[cfg(feature = "detailed-layers")]
             from: (Vec2::ZERO, 0),
             to: Vec2::ZERO,
             polygon_to: self.get_point_location(vec2(0.0, 0.0)),
             polygon_from: self.get_point_location(vec2(0.0, 0.0)),
             mesh: self,
-            blocked_layers: HashSet::default(),
+            options: SearchOptions::default(),
+            landmark_bounds: vec![],
+            vertex_distances: None,
+            point_distances: vec![],
             #[cfg(feature = "stats")]
             pushed: 0,
             #[cfg(feature = "stats")]
@@ -894,6 +982,11 @@ struct SearchNode {
     path_with_layers: SmallVec<[(Vec2, Vec2, u8); 10]>,
     path_through_polygons: SmallVec<[u32; 10]>,
     root: Vec2,
+    /// Vertex of the root with its layer, or `u32::MAX` if the root is the start of the search
+    root_vertex: u32,
+    /// Index of the polygon where the segment from the root starts, counting the starting polygon
+    /// before `path_through_polygons`
+    root_polygon_index: usize,
     interval: (Vec2, Vec2),
     edge: (u32, u32),
     polygon_from: u32,
@@ -951,7 +1044,7 @@ mod tests {
         ($x:expr, $y:expr) => {
             let val = $x;
             let expected = $y;
-            if !((val - expected).abs() < 0.01) {
+            if (val - expected).abs() >= 0.01 || val.is_nan() {
                 assert_eq!(val, expected);
             }
         };
@@ -1018,6 +1111,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(1.0, 0.0), vec2(1.0, 1.0)),
             edge: (1, 5),
             polygon_from: mesh.get_point_location(from),
@@ -1062,6 +1157,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(2.0, 1.0), vec2(2.0, 0.0)),
             edge: (6, 2),
             polygon_from: mesh.get_point_location(from),
@@ -1105,6 +1202,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(0.0, 1.0), vec2(1.0, 1.0)),
             edge: (4, 5),
             polygon_from: mesh.get_point_location(from),
@@ -1156,6 +1255,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(1.0, 0.0), vec2(1.0, 1.0)),
             edge: (1, 5),
             polygon_from: 0,
@@ -1268,6 +1369,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(11.0, 3.0), vec2(7.0, 0.0)),
             edge: (16, 15),
             polygon_from: mesh.get_point_location(from),
@@ -1319,6 +1422,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(11.0, 3.0), vec2(7.0, 0.0)),
             edge: (16, 15),
             polygon_from: mesh.get_point_location(from),
@@ -1397,6 +1502,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(11.0, 3.0), vec2(7.0, 0.0)),
             edge: (16, 15),
             polygon_from: mesh.get_point_location(from),
@@ -1469,6 +1576,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(11.0, 3.0), vec2(7.0, 0.0)),
             edge: (16, 15),
             polygon_from: mesh.get_point_location(from),
@@ -1541,6 +1650,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(11.0, 3.0), vec2(7.0, 0.0)),
             edge: (16, 15),
             polygon_from: mesh.get_point_location(from),
@@ -1564,6 +1675,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: from,
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(9.75, 6.75), vec2(7.0, 4.0)),
             edge: (11, 10),
             polygon_from: 4,
@@ -1587,6 +1700,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: vec2(11.0, 3.0),
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(10.0, 7.0), vec2(7.0, 4.0)),
             edge: (11, 10),
             polygon_from: 4,
@@ -1613,6 +1728,8 @@ mod tests {
             path_with_layers: SmallVec::new(),
             path_through_polygons: SmallVec::new(),
             root: vec2(0.0, 0.0),
+            root_vertex: u32::MAX,
+            root_polygon_index: 0,
             interval: (vec2(1.0, 0.0), vec2(1.0, 1.0)),
             edge: (1, 5),
             polygon_from: 0,
@@ -0,0 +1,231 @@
+use glam::Vec2;
+use hashbrown::HashSet;
+
+use crate::{helpers::EPSILON, instance::U32Layer, Coords, Mesh, Path};
+
+/// Part of a segment inside a polygon.
+struct Clip {
+    /// Where the segment enters the polygon, `0.0` if it starts in it.
+    enter: f32,
+    /// Where the segment leaves the polygon, `1.0` if it ends in it.
+    exit: f32,
+    /// Edge through which the segment leaves the polygon, `None` if it ends in it.
+    edge: Option<[u32; 2]>,
+}
+
+impl Mesh {
+    /// Walk along the straight segment from `from` to `to`, from polygon to polygon, without
+    /// searching.
+    ///
+    /// Returns each polygon the segment goes through, in order, with the point where the segment
+    /// enters it, or `None` if the segment leaves the mesh.
+    pub(crate) fn line_of_sight(&self, from: Coords, to: Vec2) -> Option<Vec<(u32, Vec2)>> {
+        let start = if from.polygon_index != u32::MAX {
+            from.polygon_index
+        } else {
+            self.get_point_location(from)
+        };
+        if start == u32::MAX {
+            return None;
+        }
+        // Tolerance along the segment
+        let epsilon = EPSILON / from.pos.distance(to).max(EPSILON);
+
+        let mut polygons = vec![(start, from.pos)];
+        let mut visited = HashSet::new();
+        visited.insert(start);
+        let mut current = start;
+        loop {
+            let clip = self.clip(current, from.pos, to)?;
+            let Some(edge) = clip.edge else {
+                return Some(polygons);
+            };
+            let exit = from.pos.lerp(to, clip.exit);
+            let layer = &self.layers[current.layer() as usize];
+            let next = match edge.iter().find(|vertex| {
+                (layer.vertices[**vertex as usize].coords + layer.offset).distance(exit) < EPSILON
+            }) {
+                // Through a vertex, continue in the polygon around it that goes the furthest
+                Some(vertex) => layer.vertices[*vertex as usize]
+                    .polygons
+                    .iter()
+                    .filter(|polygon| **polygon != u32::MAX && !visited.contains(*polygon))
+                    .filter_map(|polygon| Some((*polygon, self.clip(*polygon, from.pos, to)?)))
+                    .filter(|(_, other)| {
+                        other.enter <= clip.exit + epsilon && other.exit > clip.exit + epsilon
+                    })
+                    .max_by(|(_, a), (_, b)| {
+                        (a.edge.is_none(), a.exit)
+                            .partial_cmp(&(b.edge.is_none(), b.exit))
+                            .unwrap()
+                    })
+                    .map(|(polygon, _)| polygon),
+                None => self
+                    .polygon_neighbours_with_edges(current)
+                    .find(|(other_edge, other_side)| {
+                        *other_edge == edge && !visited.contains(other_side)
+                    })
+                    .map(|(_, other_side)| other_side),
+            }?;
+            visited.insert(next);
+            polygons.push((next, exit));
+            current = next;
+        }
+    }
+
+    /// A path going straight from `from` to `to`, or `None` if the segment leaves the mesh.
+    pub(crate) fn straight_path(&self, from: Coords, to: Vec2) -> Option<Path> {
+        let polygons = self.line_of_sight(from, to)?;
+        #[cfg(feature = "detailed-layers")]
+        let path_with_layers = {
+            let mut path_with_layers: Vec<(Vec2, u8)> = polygons
+                .windows(2)
+                .filter(|pair| pair[0].0.layer() != pair[1].0.layer())
+                .map(|pair| (pair[1].1, pair[1].0.layer()))
+                .collect();
+            path_with_layers.push((to, polygons.last().unwrap().0.layer()));
+            path_with_layers
+        };
+        Some(Path {
+            #[cfg(not(feature = "detailed-layers"))]
+            length: from.pos.distance(to),
+            #[cfg(feature = "detailed-layers")]
+            length: path_with_layers
+                .iter()
+                .fold(
+                    (0.0, (from.pos, polygons[0].0.layer())),
+                    |(length, previous), point| {
+                        let scale = self.layers[previous.1 as usize].scale;
+                        (
+                            length + (previous.0 * scale).distance(point.0 * scale),
+                            *point,
+                        )
+                    },
+                )
+                .0,
+            path: vec![to],
+            #[cfg(feature = "detailed-layers")]
+            path_with_layers,
+            path_through_polygons: polygons.into_iter().map(|(polygon, _)| polygon).collect(),
+        })
+    }
+
+    /// Clip the segment from `from` to `to` to a polygon, or `None` if it doesn't go through it.
+    fn clip(&self, polygon: u32, from: Vec2, to: Vec2) -> Option<Clip> {
+        let layer = &self.layers[polygon.layer() as usize];
+        let mut clip = Clip {
+            enter: 0.0,
+            exit: 1.0,
+            edge: None,
+        };
+        for edge in layer.polygons[polygon.polygon() as usize].edges_index() {
+            let start = layer.vertices[edge[0] as usize].coords + layer.offset;
+            let end = layer.vertices[edge[1] as usize].coords + layer.offset;
+            // Distances to the edge, positive inside of the polygon
+            let direction = (end - start).normalize_or_zero();
+            let (from_side, to_side) = (
+                direction.perp_dot(from - start),
+                direction.perp_dot(to - start),
+            );
+            match (from_side < -EPSILON, to_side < -EPSILON) {
+                (true, true) => return None,
+                (false, true) => {
+                    let exit = (from_side / (from_side - to_side)).max(0.0);
+                    if exit < clip.exit || clip.edge.is_none() {
+                        clip.exit = exit;
+                        clip.edge = Some(edge);
+                    }
+                }
+                (true, false) => {
+                    clip.enter = clip.enter.max(from_side / (from_side - to_side));
+                }
+                (false, false) => (),
+            }
+        }
+        (clip.enter <= clip.exit).then_some(clip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::vec2;
+
+    use crate::{Coords, Mesh, Polygon, Vertex};
+
+    /// Four squares in a row:
+    ///
+    /// ```text
+    /// 5---6---7---8---9
+    /// | 0 | 1 | 2 | 3 |
+    /// 0---1---2---3---4
+    /// ```
+    fn squares() -> Mesh {
+        Mesh::new(
+            vec![
+                Vertex::new(vec2(0., 0.), vec![0, u32::MAX]),
+                Vertex::new(vec2(1., 0.), vec![1, 0, u32::MAX]),
+                Vertex::new(vec2(2., 0.), vec![2, 1, u32::MAX]),
+                Vertex::new(vec2(3., 0.), vec![3, 2, u32::MAX]),
+                Vertex::new(vec2(4., 0.), vec![3, u32::MAX]),
+                Vertex::new(vec2(0., 1.), vec![u32::MAX, 0]),
+                Vertex::new(vec2(1., 1.), vec![u32::MAX, 0, 1]),
+                Vertex::new(vec2(2., 1.), vec![u32::MAX, 1, 2]),
+                Vertex::new(vec2(3., 1.), vec![u32::MAX, 2, 3]),
+                Vertex::new(vec2(4., 1.), vec![u32::MAX, 3]),
+            ],
+            vec![
+                Polygon::new(vec![0, 1, 6, 5], false),
+                Polygon::new(vec![1, 2, 7, 6], false),
+                Polygon::new(vec![2, 3, 8, 7], false),
+                Polygon::new(vec![3, 4, 9, 8], false),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn through_edges_and_vertices() {
+        let mesh = squares();
+        let polygons = |from, to| {
+            mesh.line_of_sight(Coords::on_mesh(from), to)
+                .map(|polygons| polygons.iter().map(|(p, _)| *p).collect::<Vec<_>>())
+        };
+        assert_eq!(
+            polygons(vec2(0.5, 0.5), vec2(3.5, 0.5)),
+            Some(vec![0, 1, 2, 3])
+        );
+        assert_eq!(
+            polygons(vec2(3.5, 0.2), vec2(0.5, 0.8)),
+            Some(vec![3, 2, 1, 0])
+        );
+        // From and to vertices
+        assert_eq!(polygons(vec2(0.0, 0.0), vec2(2.0, 1.0)), Some(vec![0, 1]));
+        assert_eq!(
+            polygons(vec2(0.5, 0.0), vec2(2.5, 1.0)),
+            Some(vec![0, 1, 2])
+        );
+        // Along the border, through the vertices between the squares
+        assert_eq!(
+            polygons(vec2(0.0, 1.0), vec2(4.0, 1.0)),
+            Some(vec![0, 1, 2, 3])
+        );
+        assert_eq!(
+            polygons(vec2(3.5, 0.0), vec2(0.5, 0.0)),
+            Some(vec![3, 2, 1, 0])
+        );
+        // Out of the mesh
+        assert_eq!(polygons(vec2(0.5, 0.5), vec2(1.5, 1.5)), None);
+        assert_eq!(polygons(vec2(0.5, 1.5), vec2(1.5, 0.5)), None);
+    }
+
+    #[test]
+    fn straight_path() {
+        let mesh = squares();
+        let path = mesh
+            .straight_path(Coords::on_mesh(vec2(0.5, 0.5)), vec2(3.5, 0.5))
+            .unwrap();
+        assert_eq!(path.path, vec![vec2(3.5, 0.5)]);
+        assert_eq!(path.length, 3.0);
+        assert_eq!(path.path_through_polygons, vec![0, 1, 2, 3]);
+    }
+}
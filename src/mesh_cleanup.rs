@@ -107,8 +107,8 @@ impl Mesh {
 
             reordered_neighbors.push(reordered_neighbors_in_layer);
         }
-        for (layer, new) in self.layers.iter_mut().zip(reordered_neighbors.into_iter()) {
-            for (vertex, new) in layer.vertices.iter_mut().zip(new.into_iter()) {
+        for (layer, new) in self.layers.iter_mut().zip(reordered_neighbors) {
+            for (vertex, new) in layer.vertices.iter_mut().zip(new) {
                 vertex.is_corner = new.contains(&u32::MAX);
                 vertex.polygons = new;
             }
@@ -0,0 +1,147 @@
+use glam::Vec2;
+#[cfg(feature = "tracing")]
+use tracing::instrument;
+
+use crate::{helpers::heuristic, instance::SearchOptions, Coords, Mesh, Path};
+
+/// How a search evaluates paths: the cost of each segment and turn, the estimate of the
+/// remaining cost, and which paths are acceptable.
+///
+/// This lets features like weighted areas, danger maps or custom 3D costs live outside of this
+/// crate. The default implementation of each method is the behaviour of [`Mesh::path`], see
+/// [`ShortestPath`]. [`TurnPenalty`](crate::TurnPenalty), [`CostZone`](crate::CostZone) and
+/// [`HeightCost`](crate::HeightCost) are policies too.
+///
+/// Two policies can be combined with a tuple: the costs each of them adds on top of the length
+/// of the path add up, and paths must be accepted by both. This expects each of them to cost at
+/// least the length of the path.
+pub trait SearchPolicy {
+    /// Cost of going in a straight line from `from` to `to` inside a polygon, identified by its
+    /// layer and its index in the layer.
+    ///
+    /// Costs must not be negative.
+    fn segment_cost(&self, from: Vec2, to: Vec2, layer: u8, polygon: u32) -> f32 {
+        let _ = (layer, polygon);
+        from.distance(to)
+    }
+
+    /// Cost of turning at a corner of the path, on top of the cost of its segments.
+    ///
+    /// `angle` is the absolute angle between the segment arriving at the corner and the segment
+    /// leaving it, in radians: `0.0` when going straight, and up to `PI` when going back. Costs
+    /// must not be negative.
+    fn turn_cost(&self, corner: Vec2, angle: f32) -> f32 {
+        let _ = (corner, angle);
+        0.0
+    }
+
+    /// Estimate of the cost from `root` to `goal`, for a path that goes through `interval`.
+    ///
+    /// This must never be higher than the actual cost, otherwise the path found may not be the
+    /// cheapest one. The default is the length of the shortest path through the interval,
+    /// ignoring obstacles.
+    fn heuristic(&self, root: Vec2, goal: Vec2, interval: (Vec2, Vec2)) -> f32 {
+        heuristic(root, goal, interval)
+    }
+
+    /// Goal test, to accept or reject a path reaching the goal.
+    ///
+    /// Paths are tested from the cheapest one, and the search continues while they are rejected.
+    /// As the search only keeps the best way to reach each corner, not all paths are tested.
+    fn is_goal(&self, path: &Path) -> bool {
+        let _ = path;
+        true
+    }
+
+    /// If this policy finds the shortest path, like [`ShortestPath`], so that the search can
+    /// only track the length of paths, and use the landmarks baked with [`Mesh::bake_landmarks`].
+    fn is_shortest_path(&self) -> bool {
+        false
+    }
+}
+
+/// The default policy, used by [`Mesh::path`], finding the shortest path.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ShortestPath;
+
+impl SearchPolicy for ShortestPath {
+    fn is_shortest_path(&self) -> bool {
+        true
+    }
+}
+
+impl<T: SearchPolicy + ?Sized> SearchPolicy for &T {
+    fn segment_cost(&self, from: Vec2, to: Vec2, layer: u8, polygon: u32) -> f32 {
+        (**self).segment_cost(from, to, layer, polygon)
+    }
+
+    fn turn_cost(&self, corner: Vec2, angle: f32) -> f32 {
+        (**self).turn_cost(corner, angle)
+    }
+
+    fn heuristic(&self, root: Vec2, goal: Vec2, interval: (Vec2, Vec2)) -> f32 {
+        (**self).heuristic(root, goal, interval)
+    }
+
+    fn is_goal(&self, path: &Path) -> bool {
+        (**self).is_goal(path)
+    }
+
+    fn is_shortest_path(&self) -> bool {
+        (**self).is_shortest_path()
+    }
+}
+
+impl<A: SearchPolicy, B: SearchPolicy> SearchPolicy for (A, B) {
+    fn segment_cost(&self, from: Vec2, to: Vec2, layer: u8, polygon: u32) -> f32 {
+        self.0.segment_cost(from, to, layer, polygon)
+            + self.1.segment_cost(from, to, layer, polygon)
+            - from.distance(to)
+    }
+
+    fn turn_cost(&self, corner: Vec2, angle: f32) -> f32 {
+        self.0.turn_cost(corner, angle) + self.1.turn_cost(corner, angle)
+    }
+
+    fn heuristic(&self, root: Vec2, goal: Vec2, interval: (Vec2, Vec2)) -> f32 {
+        self.0
+            .heuristic(root, goal, interval)
+            .max(self.1.heuristic(root, goal, interval))
+    }
+
+    fn is_goal(&self, path: &Path) -> bool {
+        self.0.is_goal(path) && self.1.is_goal(path)
+    }
+
+    fn is_shortest_path(&self) -> bool {
+        self.0.is_shortest_path() && self.1.is_shortest_path()
+    }
+}
+
+impl Mesh {
+    /// Compute a path between two points, following a [`SearchPolicy`].
+    ///
+    /// The path returned is the cheapest one accepted by the policy. Unless the policy finds the
+    /// shortest path, landmarks baked with [`Mesh::bake_landmarks`] are not used, as they are only
+    /// valid for the length of paths, and the search keeps the best way to reach each corner from
+    /// each direction, as the cost of a path can depend on its turns. This explores more of the
+    /// mesh than [`Mesh::path`].
+    ///
+    /// [`Path::length`] is the length of the path, not its cost.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn path_with_policy<P: SearchPolicy>(
+        &self,
+        from: impl Into<Coords>,
+        to: impl Into<Coords>,
+        policy: &P,
+    ) -> Option<Path> {
+        self.path_with_options(
+            from.into(),
+            to.into(),
+            SearchOptions {
+                policy: (!policy.is_shortest_path()).then_some(policy),
+                ..Default::default()
+            },
+        )
+    }
+}
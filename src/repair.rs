@@ -0,0 +1,95 @@
+use thiserror::Error;
+#[cfg(feature = "tracing")]
+use tracing::instrument;
+
+use crate::{instance::U32Layer, Coords, Mesh, Path};
+
+/// Errors found when checking a path against the current mesh with [`Mesh::validate_path`]
+#[derive(Error, Debug, Copy, Clone, PartialEq)]
+pub enum PathError {
+    /// The path can't go in a straight line between two of its steps anymore.
+    ///
+    /// `segment` is the index of the step at the end of the segment in [`Path::path`], the first
+    /// segment going from the start of the path to its first step.
+    #[error("Segment {segment} of the path is blocked")]
+    BlockedSegment {
+        /// Index of the segment.
+        segment: usize,
+    },
+}
+
+impl Mesh {
+    /// Check that a path computed earlier is still valid, after the mesh was modified.
+    ///
+    /// Each segment of the path, starting from `from`, must still be a straight line in the
+    /// mesh. Returns the first segment that isn't.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn validate_path(&self, from: impl Into<Coords>, path: &Path) -> Result<(), PathError> {
+        match self.valid_prefix(from.into(), path).1 {
+            Some(segment) => Err(PathError::BlockedSegment { segment }),
+            None => Ok(()),
+        }
+    }
+
+    /// Repair a path computed earlier, after the mesh was modified.
+    ///
+    /// The part of the path before the first blocked segment is kept, and a new path is computed
+    /// from there to the destination. The polygons the path goes through are updated to the
+    /// current mesh. Returns `None` if the destination can't be reached anymore.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn repair_path(&self, from: impl Into<Coords>, path: Path) -> Option<Path> {
+        let from = from.into();
+        let (segments, blocked) = self.valid_prefix(from, &path);
+        let mut repaired = Path {
+            length: 0.0,
+            path: vec![],
+            #[cfg(feature = "detailed-layers")]
+            path_with_layers: vec![],
+            path_through_polygons: vec![],
+        };
+        let mut last = from;
+        for segment in segments {
+            last = end_of(&segment);
+            repaired.join(from.pos, segment);
+        }
+        if blocked.is_some() {
+            repaired.join(from.pos, self.path(last, *path.path.last()?)?);
+        }
+        Some(repaired)
+    }
+
+    /// Path of each segment of a path that is still a straight line in the mesh, until the first
+    /// one that is not.
+    ///
+    /// Each segment is checked by walking along it from polygon to polygon.
+    fn valid_prefix(&self, from: Coords, path: &Path) -> (Vec<Path>, Option<usize>) {
+        let mut segments = Vec::with_capacity(path.path.len());
+        let mut start = from;
+        for (index, step) in path.path.iter().enumerate() {
+            match self.straight_path(start, *step) {
+                Some(segment) => {
+                    start = end_of(&segment);
+                    segments.push(segment);
+                }
+                None => return (segments, Some(index)),
+            }
+        }
+        (segments, None)
+    }
+}
+
+/// End of a path, in the polygon it ends in.
+fn end_of(path: &Path) -> Coords {
+    Coords {
+        pos: *path.path.last().unwrap(),
+        layer: path
+            .path_through_polygons
+            .last()
+            .map(|polygon| polygon.layer()),
+        polygon_index: path
+            .path_through_polygons
+            .last()
+            .copied()
+            .unwrap_or(u32::MAX),
+    }
+}
@@ -0,0 +1,126 @@
+use glam::Vec2;
+
+use crate::{instance::U32Layer, Coords, Mesh, Path, PathSegment};
+
+impl PathSegment {
+    /// Point at a distance from the start of the segment, in the polygon the segment goes
+    /// through there.
+    ///
+    /// The distance is clamped to the length of the segment.
+    pub fn point_at_distance(&self, distance: f32) -> Coords {
+        let length = self.length();
+        let pos = if length == 0.0 {
+            self.start.pos
+        } else {
+            self.start
+                .pos
+                .lerp(self.end.pos, (distance / length).clamp(0.0, 1.0))
+        };
+        let (layer, polygon) = self
+            .crossings
+            .iter()
+            .take_while(|crossing| self.start.pos.distance(crossing.point) <= distance)
+            .last()
+            .map(|crossing| crossing.to)
+            .unwrap_or((
+                self.start.polygon_index.layer(),
+                self.start.polygon_index.polygon(),
+            ));
+        Coords {
+            pos,
+            layer: Some(layer),
+            polygon_index: u32::from_layer_and_polygon(layer, polygon),
+        }
+    }
+}
+
+impl Path {
+    /// Each straight segment of the path, as its start and its end.
+    fn steps(&self, from: Vec2) -> impl Iterator<Item = (Vec2, Vec2)> + '_ {
+        std::iter::once(from)
+            .chain(self.path.iter().copied())
+            .zip(self.path.iter().copied())
+    }
+
+    /// Point at a distance along the path, from its start `from`.
+    ///
+    /// The point has the layer and the polygon it's in, so its height can be found with
+    /// [`Coords::position_with_height`]. Distances are measured in 2D, and are clamped to the
+    /// path.
+    pub fn point_at_distance(&self, mesh: &Mesh, from: Vec2, distance: f32) -> Coords {
+        let mut travelled = 0.0;
+        let mut last = None;
+        for segment in self.segments(mesh, from) {
+            let length = segment.length();
+            if distance <= travelled + length {
+                return segment.point_at_distance(distance - travelled);
+            }
+            travelled += length;
+            last = Some(segment.end);
+        }
+        last.unwrap_or(Coords::on_mesh(from))
+    }
+
+    /// Direction of the path at a distance along it, from its start `from`.
+    ///
+    /// On a corner, this is the direction of the segment after it. Returns [`Vec2::ZERO`] for an
+    /// empty path.
+    pub fn direction_at(&self, from: Vec2, distance: f32) -> Vec2 {
+        let mut travelled = 0.0;
+        let mut direction = Vec2::ZERO;
+        for (start, end) in self.steps(from) {
+            direction = (end - start).normalize_or_zero();
+            travelled += start.distance(end);
+            if distance < travelled {
+                break;
+            }
+        }
+        direction
+    }
+
+    /// Distance along the path, from its start `from`, of the point of the path closest to
+    /// `point`.
+    pub fn project(&self, from: Vec2, point: Vec2) -> f32 {
+        let mut travelled = 0.0;
+        let mut closest = (f32::INFINITY, 0.0);
+        for (start, end) in self.steps(from) {
+            let length = start.distance(end);
+            let t = if length == 0.0 {
+                0.0
+            } else {
+                ((point - start).dot(end - start) / (length * length)).clamp(0.0, 1.0)
+            };
+            let distance = point.distance_squared(start.lerp(end, t));
+            if distance < closest.0 {
+                closest = (distance, travelled + t * length);
+            }
+            travelled += length;
+        }
+        closest.1
+    }
+
+    /// Points along the path, from its start `from`, every `step`. The destination is always
+    /// the last point.
+    ///
+    /// Each point has the layer and the polygon it's in, like with [`Path::point_at_distance`].
+    /// `step` must be positive.
+    pub fn resample(&self, mesh: &Mesh, from: Vec2, step: f32) -> Vec<Coords> {
+        let mut points = vec![];
+        let mut travelled = 0.0;
+        let mut end = None;
+        for segment in self.segments(mesh, from) {
+            let length = segment.length();
+            while step > 0.0 && points.len() as f32 * step <= travelled + length {
+                points.push(segment.point_at_distance(points.len() as f32 * step - travelled));
+            }
+            travelled += length;
+            end = Some(segment.end);
+        }
+        if let Some(end) = end {
+            if points.last().map(|point| point.pos) != Some(end.pos) {
+                points.push(end);
+            }
+        }
+        points
+    }
+}
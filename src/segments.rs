@@ -0,0 +1,140 @@
+use glam::Vec2;
+
+use crate::{
+    helpers::{line_intersect_segment, Vec2Helper},
+    instance::U32Layer,
+    Coords, Mesh, Path,
+};
+
+/// A straight segment of a [`Path`], from one step to the next, built by [`Path::segments`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct PathSegment {
+    /// Start of the segment, in the first polygon it goes through.
+    pub start: Coords,
+    /// End of the segment, in the last polygon it goes through.
+    pub end: Coords,
+    /// Polygons the segment goes through, in order, identified by their layer and their index in
+    /// the layer.
+    ///
+    /// When the path turns on a vertex, the polygons around it that the path goes through are
+    /// part of the next segment.
+    pub polygons: Vec<(u8, u32)>,
+    /// Edges crossed by the segment, from one polygon to the next.
+    pub crossings: Vec<PortalCrossing>,
+}
+
+/// The crossing of an edge between two polygons along a [`PathSegment`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PortalCrossing {
+    /// Where the edge is crossed.
+    pub point: Vec2,
+    /// Ends of the edge crossed.
+    pub edge: (Vec2, Vec2),
+    /// Polygon left, with its layer and its index in the layer.
+    pub from: (u8, u32),
+    /// Polygon entered, with its layer and its index in the layer.
+    pub to: (u8, u32),
+}
+
+impl PortalCrossing {
+    /// Does this crossing go from one layer to another, through a stitch between them.
+    pub fn changes_layer(&self) -> bool {
+        self.from.0 != self.to.0
+    }
+}
+
+impl PathSegment {
+    /// Length of the segment.
+    pub fn length(&self) -> f32 {
+        self.start.pos.distance(self.end.pos)
+    }
+
+    /// Crossings of this segment going from one layer to another.
+    pub fn layer_changes(&self) -> impl Iterator<Item = &PortalCrossing> + '_ {
+        self.crossings
+            .iter()
+            .filter(|crossing| crossing.changes_layer())
+    }
+}
+
+impl Path {
+    /// Returns each straight segment of the path, with the polygons it goes through and the
+    /// edges it crosses.
+    ///
+    /// `from` is the start of the path, and `mesh` the mesh it was computed on.
+    pub fn segments(&self, mesh: &Mesh, from: Vec2) -> impl Iterator<Item = PathSegment> {
+        let polygons = &self.path_through_polygons;
+        let mut segments = Vec::with_capacity(self.path.len());
+        let Some(&first) = polygons.first() else {
+            return segments.into_iter();
+        };
+        let points: Vec<Vec2> = std::iter::once(from)
+            .chain(self.path.iter().copied())
+            .collect();
+        let coords = |pos: Vec2, polygon: u32| Coords {
+            pos,
+            layer: Some(polygon.layer()),
+            polygon_index: polygon,
+        };
+        let mut current = PathSegment {
+            start: coords(from, first),
+            end: coords(from, first),
+            polygons: vec![(first.layer(), first.polygon())],
+            crossings: vec![],
+        };
+        let mut segment = 0;
+
+        for pair in polygons.windows(2) {
+            let edge = mesh
+                .polygon_neighbours_with_edges(pair[0])
+                .find(|(_, other_side)| *other_side == pair[1])
+                .map(|([edge0, edge1], _)| {
+                    let layer = &mesh.layers[pair[0].layer() as usize];
+                    (
+                        layer.vertices[edge0 as usize].coords + layer.offset,
+                        layer.vertices[edge1 as usize].coords + layer.offset,
+                    )
+                })
+                .unwrap_or((points[segment], points[segment]));
+            // The path turns on this edge, before the destination
+            while segment + 2 < points.len() && points[segment + 1].on_segment(edge) {
+                let turn = points[segment + 1];
+                current.end = coords(turn, pair[0]);
+                segments.push(std::mem::replace(
+                    &mut current,
+                    PathSegment {
+                        start: coords(turn, pair[0]),
+                        end: coords(turn, pair[0]),
+                        polygons: vec![(pair[0].layer(), pair[0].polygon())],
+                        crossings: vec![],
+                    },
+                ));
+                segment += 1;
+            }
+            let (start, end) = (points[segment], points[segment + 1]);
+            let point = line_intersect_segment((start, end), edge)
+                .filter(|crossing| crossing.in_bounding_box((start, end)))
+                .unwrap_or(start);
+            current.crossings.push(PortalCrossing {
+                point,
+                edge,
+                from: (pair[0].layer(), pair[0].polygon()),
+                to: (pair[1].layer(), pair[1].polygon()),
+            });
+            current.polygons.push((pair[1].layer(), pair[1].polygon()));
+        }
+
+        let last = *polygons.last().unwrap();
+        current.end = coords(points[segment + 1], last);
+        segments.push(current);
+        for pair in points[segment + 1..].windows(2) {
+            segments.push(PathSegment {
+                start: coords(pair[0], last),
+                end: coords(pair[1], last),
+                polygons: vec![(last.layer(), last.polygon())],
+                crossings: vec![],
+            });
+        }
+        segments.into_iter()
+    }
+}
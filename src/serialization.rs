@@ -0,0 +1,357 @@
+#[cfg(feature = "stats")]
+use std::cell::Cell;
+
+use bvh2d::bvh2d::BVH2d;
+use glam::Vec2;
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::{hierarchy::Hierarchy, landmarks::Landmarks, Layer, Mesh, Polygon, Vertex};
+
+/// Version of the baked data. Changed when baking changes, so that baked data serialized by an
+/// earlier version is baked again when deserialized.
+const BAKED_DATA_VERSION: u32 = 2;
+
+/// Written with the baked data of a layer or a mesh, to check when deserializing it that it was
+/// baked by this version from the same geometry. Only the source data is hashed, the baked data
+/// itself is trusted.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+struct BakedGuard {
+    version: u32,
+    checksum: u64,
+}
+
+/// A layer as serialized, borrowing from the layer.
+#[derive(Serialize)]
+#[serde(rename = "Layer")]
+struct LayerRef<'a> {
+    vertices: &'a [Vertex],
+    polygons: &'a [Polygon],
+    offset: Vec2,
+    #[cfg(feature = "detailed-layers")]
+    scale: Vec2,
+    baked_polygons: &'a Option<BVH2d>,
+    islands: &'a Option<Vec<usize>>,
+    slopes: &'a Option<Vec<f32>>,
+    height: &'a [f32],
+    baked_guard: BakedGuard,
+}
+
+/// A layer as deserialized, before checking its baked data.
+#[derive(Deserialize)]
+#[serde(rename = "Layer")]
+pub(crate) struct SerializedLayer {
+    vertices: Vec<Vertex>,
+    polygons: Vec<Polygon>,
+    offset: Vec2,
+    #[cfg(feature = "detailed-layers")]
+    scale: Vec2,
+    #[serde(default)]
+    baked_polygons: Option<BVH2d>,
+    #[serde(default)]
+    islands: Option<Vec<usize>>,
+    #[serde(default)]
+    slopes: Option<Vec<f32>>,
+    height: Vec<f32>,
+    #[serde(default)]
+    baked_guard: Option<BakedGuard>,
+}
+
+/// A mesh as serialized, borrowing from the mesh.
+#[derive(Serialize)]
+#[serde(rename = "Mesh")]
+struct MeshRef<'a> {
+    layers: &'a [Layer],
+    search_delta: f32,
+    search_steps: u32,
+    hierarchy: &'a Option<Hierarchy>,
+    landmarks: &'a Option<Landmarks>,
+    #[cfg(feature = "stats")]
+    scenarios: &'a Cell<u32>,
+    baked_guard: BakedGuard,
+}
+
+/// A mesh as deserialized, before checking its baked data.
+#[derive(Deserialize)]
+#[serde(rename = "Mesh")]
+pub(crate) struct SerializedMesh {
+    layers: Vec<Layer>,
+    search_delta: f32,
+    search_steps: u32,
+    #[serde(default)]
+    hierarchy: Option<Hierarchy>,
+    #[serde(default)]
+    landmarks: Option<Landmarks>,
+    #[cfg(feature = "stats")]
+    #[serde(default)]
+    scenarios: Cell<u32>,
+    #[serde(default)]
+    baked_guard: Option<BakedGuard>,
+}
+
+impl Serialize for Mesh {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MeshRef {
+            layers: &self.layers,
+            search_delta: self.search_delta,
+            search_steps: self.search_steps,
+            hierarchy: &self.hierarchy,
+            landmarks: &self.landmarks,
+            #[cfg(feature = "stats")]
+            scenarios: &self.scenarios,
+            baked_guard: BakedGuard {
+                version: BAKED_DATA_VERSION,
+                checksum: self.baked_checksum(),
+            },
+        }
+        .serialize(serializer)
+    }
+}
+
+impl From<SerializedMesh> for Mesh {
+    fn from(serialized: SerializedMesh) -> Self {
+        let mut mesh = Mesh {
+            layers: serialized.layers,
+            search_delta: serialized.search_delta,
+            search_steps: serialized.search_steps,
+            hierarchy: serialized.hierarchy,
+            landmarks: serialized.landmarks,
+            #[cfg(feature = "stats")]
+            scenarios: serialized.scenarios,
+        };
+        let expected = BakedGuard {
+            version: BAKED_DATA_VERSION,
+            checksum: mesh.baked_checksum(),
+        };
+        if serialized.baked_guard != Some(expected) {
+            // The hierarchy and the landmarks depend on parameters that are not serialized, they
+            // can't be baked again
+            mesh.hierarchy = None;
+            mesh.landmarks = None;
+        }
+        mesh
+    }
+}
+
+impl Serialize for Layer {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        LayerRef {
+            vertices: &self.vertices,
+            polygons: &self.polygons,
+            offset: self.offset,
+            #[cfg(feature = "detailed-layers")]
+            scale: self.scale,
+            baked_polygons: &self.baked_polygons,
+            islands: &self.islands,
+            slopes: &self.slopes,
+            height: &self.height,
+            baked_guard: BakedGuard {
+                version: BAKED_DATA_VERSION,
+                checksum: self.baked_checksum(),
+            },
+        }
+        .serialize(serializer)
+    }
+}
+
+impl From<SerializedLayer> for Layer {
+    fn from(serialized: SerializedLayer) -> Self {
+        let mut layer = Layer {
+            vertices: serialized.vertices,
+            polygons: serialized.polygons,
+            offset: serialized.offset,
+            #[cfg(feature = "detailed-layers")]
+            scale: serialized.scale,
+            baked_polygons: serialized.baked_polygons,
+            islands: serialized.islands,
+            slopes: serialized.slopes,
+            height: serialized.height,
+        };
+        let expected = BakedGuard {
+            version: BAKED_DATA_VERSION,
+            checksum: layer.baked_checksum(),
+        };
+        if serialized.baked_guard != Some(expected) {
+            // The baked data is from another version, or doesn't match the layer anymore
+            layer.unbake();
+            #[cfg(not(feature = "no-default-baking"))]
+            layer.bake_loaded();
+        }
+        layer
+    }
+}
+
+/// FNV-1a hash, stable between platforms and versions of Rust.
+struct Fnv64(u64);
+
+impl Fnv64 {
+    fn write(&mut self, bytes: &[u8]) {
+        for byte in bytes {
+            self.0 ^= *byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.write(&value.to_le_bytes());
+    }
+
+    fn f32(&mut self, value: f32) {
+        self.write(&value.to_le_bytes());
+    }
+}
+
+impl Default for Fnv64 {
+    fn default() -> Self {
+        Fnv64(0xcbf29ce484222325)
+    }
+}
+
+impl Mesh {
+    /// Checksum of the geometry of all the layers, which the hierarchy and the landmarks are
+    /// baked from.
+    fn baked_checksum(&self) -> u64 {
+        let mut hash = Fnv64::default();
+        hash.u32(self.layers.len() as u32);
+        for layer in &self.layers {
+            layer.hash_geometry(&mut hash);
+        }
+        hash.0
+    }
+}
+
+impl Layer {
+    /// Checksum of the geometry the baked data is computed from.
+    fn baked_checksum(&self) -> u64 {
+        let mut hash = Fnv64::default();
+        self.hash_geometry(&mut hash);
+        hash.0
+    }
+
+    /// Hash the geometry of the layer.
+    fn hash_geometry(&self, hash: &mut Fnv64) {
+        hash.f32(self.offset.x);
+        hash.f32(self.offset.y);
+        hash.u32(self.vertices.len() as u32);
+        for vertex in &self.vertices {
+            hash.f32(vertex.coords.x);
+            hash.f32(vertex.coords.y);
+            hash.u32(vertex.polygons.len() as u32);
+            vertex
+                .polygons
+                .iter()
+                .for_each(|polygon| hash.u32(*polygon));
+        }
+        hash.u32(self.polygons.len() as u32);
+        for polygon in &self.polygons {
+            hash.u32(polygon.vertices.len() as u32);
+            polygon.vertices.iter().for_each(|vertex| hash.u32(*vertex));
+            hash.write(&[polygon.is_one_way as u8]);
+        }
+        hash.u32(self.height.len() as u32);
+        self.height.iter().for_each(|height| hash.f32(*height));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glam::vec2;
+
+    use crate::{Layer, Mesh, Polygon, Vertex};
+
+    fn two_islands() -> Layer {
+        let mut layer = Layer::new(
+            vec![
+                Vertex::new(vec2(0., 0.), vec![0, u32::MAX]),
+                Vertex::new(vec2(1., 0.), vec![0, u32::MAX]),
+                Vertex::new(vec2(0., 1.), vec![0, u32::MAX]),
+                Vertex::new(vec2(2., 0.), vec![1, u32::MAX]),
+                Vertex::new(vec2(3., 0.), vec![1, u32::MAX]),
+                Vertex::new(vec2(2., 1.), vec![1, u32::MAX]),
+            ],
+            vec![
+                Polygon::new(vec![0, 1, 2], false),
+                Polygon::new(vec![3, 4, 5], false),
+            ],
+        )
+        .unwrap();
+        layer.bake();
+        layer
+    }
+
+    #[test]
+    fn keep_baked_data() {
+        let mut layer = two_islands();
+        // Mark the baked data, to check it's not baked again
+        layer.islands = Some(vec![1, 1]);
+        layer.slopes = Some(vec![0.5, 0.5]);
+        let json = serde_json::to_string(&layer).unwrap();
+        let read: Layer = serde_json::from_str(&json).unwrap();
+        assert_eq!(read.islands, Some(vec![1, 1]));
+        assert_eq!(read.slopes, Some(vec![0.5, 0.5]));
+        assert!(read.baked_polygons.is_some());
+        assert_eq!(read.get_point_location(vec2(2.2, 0.2), 0.1), Some(1));
+    }
+
+    #[test]
+    #[cfg(not(feature = "no-default-baking"))]
+    fn rebake_mismatched_baked_data() {
+        let layer = two_islands();
+        let mut json: serde_json::Value = serde_json::to_value(&layer).unwrap();
+        json["vertices"][4]["coords"] = serde_json::json!([4.0, 0.0]);
+        json["islands"] = serde_json::json!([0, 0]);
+        let read: Layer = serde_json::from_value(json).unwrap();
+        assert_eq!(read.islands, Some(vec![0, 1]));
+
+        let mut json: serde_json::Value = serde_json::to_value(&layer).unwrap();
+        json["baked_guard"]["version"] = serde_json::json!(0);
+        json["slopes"] = serde_json::json!([0.5, 0.5]);
+        let read: Layer = serde_json::from_value(json).unwrap();
+        assert_eq!(read.slopes, Some(vec![0.0, 0.0]));
+
+        let mut json: serde_json::Value = serde_json::to_value(&layer).unwrap();
+        json.as_object_mut().unwrap().remove("baked_guard");
+        json["islands"] = serde_json::json!([0, 0]);
+        let read: Layer = serde_json::from_value(json).unwrap();
+        assert_eq!(read.islands, Some(vec![0, 1]));
+    }
+
+    #[test]
+    fn mesh_baked_data() {
+        let mut mesh = Mesh {
+            layers: vec![two_islands()],
+            ..Default::default()
+        };
+        mesh.bake_hierarchy(1);
+        mesh.bake_landmarks(2);
+        let json = serde_json::to_string(&mesh).unwrap();
+        let read: Mesh = serde_json::from_str(&json).unwrap();
+        assert!(read.hierarchy().is_some());
+        assert!(read.landmarks().is_some());
+
+        // Baked data that doesn't match the mesh is dropped
+        let mut json: serde_json::Value = serde_json::to_value(&mesh).unwrap();
+        json["layers"][0]["vertices"][4]["coords"] = serde_json::json!([4.0, 0.0]);
+        let read: Mesh = serde_json::from_value(json).unwrap();
+        assert!(read.hierarchy().is_none());
+        assert!(read.landmarks().is_none());
+
+        let mut json: serde_json::Value = serde_json::to_value(&mesh).unwrap();
+        json.as_object_mut().unwrap().remove("baked_guard");
+        let read: Mesh = serde_json::from_value(json).unwrap();
+        assert!(read.hierarchy().is_none());
+        assert!(read.landmarks().is_none());
+    }
+
+    #[test]
+    fn mesh_ready_to_query() {
+        let mesh = Mesh {
+            layers: vec![two_islands()],
+            ..Default::default()
+        };
+        let json = serde_json::to_string(&mesh).unwrap();
+        let read: Mesh = serde_json::from_str(&json).unwrap();
+        assert!(read.layers[0].baked_polygons.is_some());
+        assert!(read.point_in_mesh(vec2(0.2, 0.2)));
+        assert!(read.path(vec2(0.2, 0.2), vec2(2.2, 0.2)).is_none());
+    }
+}
@@ -0,0 +1,111 @@
+use glam::Vec2;
+
+use crate::{helpers::EPSILON, instance::U32Layer, Coords, Mesh, Path};
+
+impl Path {
+    /// Smooth the path into a curve going through each of its steps, from its start `from`.
+    ///
+    /// Each segment becomes a cubic curve, with the tangent at each step along the direction from
+    /// the step before it to the step after it, like a Catmull-Rom spline. As the path is taut
+    /// around obstacles, the curve goes around the corners on their free side. Tangents are at
+    /// most `max_radius` long, which keeps the curve within a third of `max_radius` of the path.
+    ///
+    /// Each chord between two consecutive points of the curve is checked by walking along it
+    /// through the mesh. When one of them leaves the mesh, the tangents of its segment are halved
+    /// until it stays in the mesh, or until they are shorter than `resolution` and the segment is
+    /// kept straight.
+    ///
+    /// Returns points along the curve, at most about `resolution` apart, starting with `from`
+    /// and ending with the destination.
+    pub fn smooth(&self, mesh: &Mesh, from: Vec2, resolution: f32, max_radius: f32) -> Vec<Vec2> {
+        let points: Vec<Vec2> = std::iter::once(from)
+            .chain(self.path.iter().copied())
+            .collect();
+        let tangent = |index: usize| {
+            let before = points[index.saturating_sub(1)];
+            let after = points[(index + 1).min(points.len() - 1)];
+            (after - before).normalize_or_zero()
+        };
+        let on_polygon = |pos: Vec2, polygon: Option<u32>| match polygon {
+            Some(polygon) => Coords {
+                pos,
+                layer: Some(polygon.layer()),
+                polygon_index: polygon,
+            },
+            None => Coords::on_mesh(pos),
+        };
+
+        let mut smoothed = vec![from];
+        let mut current = on_polygon(from, self.path_through_polygons.first().copied());
+        for index in 0..points.len() - 1 {
+            let (start, end) = (points[index], points[index + 1]);
+            let length = start.distance(end);
+            let steps = if resolution > 0.0 {
+                ((length / resolution).ceil() as usize).max(1)
+            } else {
+                1
+            };
+
+            let turns = [tangent(index), tangent(index + 1)]
+                .iter()
+                .any(|tangent| tangent.perp_dot(end - start).abs() > EPSILON * length);
+
+            let mut radius = length.min(max_radius.max(0.0));
+            let (curve, reached) = loop {
+                if !turns || steps == 1 || radius < resolution {
+                    let polygon = mesh
+                        .line_of_sight(current, end)
+                        .and_then(|polygons| polygons.last().map(|(polygon, _)| *polygon));
+                    let line = (1..=steps)
+                        .map(|step| start.lerp(end, step as f32 / steps as f32))
+                        .collect();
+                    break (line, on_polygon(end, polygon));
+                }
+                let curve = (1..=steps)
+                    .map(|step| {
+                        hermite(
+                            (start, tangent(index) * radius),
+                            (end, tangent(index + 1) * radius),
+                            step as f32 / steps as f32,
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                if let Some(reached) = walk(mesh, current, &curve) {
+                    break (curve, reached);
+                }
+                radius /= 2.0;
+            };
+            smoothed.extend(curve);
+            // Keep the steps of the path exactly
+            *smoothed.last_mut().unwrap() = end;
+            current = Coords {
+                pos: end,
+                ..reached
+            };
+        }
+        smoothed
+    }
+}
+
+/// Walk along each chord of a curve from `from`, returning where it ends, or `None` if one of
+/// them leaves the mesh.
+fn walk(mesh: &Mesh, from: Coords, curve: &[Vec2]) -> Option<Coords> {
+    curve.iter().try_fold(from, |at, point| {
+        let (polygon, _) = *mesh.line_of_sight(at, *point)?.last()?;
+        Some(Coords {
+            pos: *point,
+            layer: Some(polygon.layer()),
+            polygon_index: polygon,
+        })
+    })
+}
+
+/// Point of a cubic Hermite curve between two points with their tangents, at `t` between `0.0`
+/// and `1.0`.
+fn hermite(start: (Vec2, Vec2), end: (Vec2, Vec2), t: f32) -> Vec2 {
+    let (t2, t3) = (t * t, t * t * t);
+    start.0 * (2.0 * t3 - 3.0 * t2 + 1.0)
+        + start.1 * (t3 - 2.0 * t2 + t)
+        + end.0 * (-2.0 * t3 + 3.0 * t2)
+        + end.1 * (t3 - t2)
+}
@@ -242,7 +242,7 @@ impl Mesh {
 
 #[cfg(test)]
 mod tests {
-    use crate::{Layer, Mesh, Path, Polygon, Triangulation, Vertex};
+    use crate::{Layer, Mesh, Path, Polygon, PortalCrossing, Triangulation, Vertex};
     use glam::{vec2, Vec2};
 
     fn basic_mesh_with_layers() -> Mesh {
@@ -636,10 +636,7 @@ mod tests {
         let indices_from = mesh.layers[0].get_vertices_on_segment(vec2(1.0, 0.0), vec2(1.0, 1.0));
         let indices_to = mesh.layers[1].get_vertices_on_segment(vec2(0.0, 0.0), vec2(0.0, 1.0));
 
-        let stitch_indices = indices_from
-            .into_iter()
-            .zip(indices_to.into_iter())
-            .collect();
+        let stitch_indices = indices_from.into_iter().zip(indices_to).collect();
 
         mesh.stitch_at_vertices(vec![((0, 1), stitch_indices)], false);
 
@@ -675,10 +672,7 @@ mod tests {
         let indices_from = mesh.layers[0].get_vertices_on_segment(vec2(1.0, 0.0), vec2(1.0, 1.0));
         let indices_to = mesh.layers[1].get_vertices_on_segment(vec2(0.0, 0.0), vec2(0.0, 1.0));
 
-        let stitch_indices = indices_from
-            .into_iter()
-            .zip(indices_to.into_iter())
-            .collect();
+        let stitch_indices = indices_from.into_iter().zip(indices_to).collect();
 
         mesh.stitch_at_vertices(vec![((0, 1), stitch_indices)], false);
 
@@ -706,6 +700,41 @@ mod tests {
         );
     }
 
+    #[test]
+    fn path_segments_stitch_layers_different_coordinates() {
+        let mut mesh = layers_different_coordinates();
+        let indices_from = mesh.layers[0].get_vertices_on_segment(vec2(1.0, 0.0), vec2(1.0, 1.0));
+        let indices_to = mesh.layers[1].get_vertices_on_segment(vec2(0.0, 0.0), vec2(0.0, 1.0));
+
+        let stitch_indices = indices_from.into_iter().zip(indices_to).collect();
+
+        mesh.stitch_at_vertices(vec![((0, 1), stitch_indices)], false);
+
+        let path = mesh.path(vec2(0.5, 0.5), vec2(1.5, 0.25)).unwrap();
+        let segments = path.segments(&mesh, vec2(0.5, 0.5)).collect::<Vec<_>>();
+        assert_eq!(segments.len(), 1);
+        assert_eq!(segments[0].start.layer(), Some(0));
+        assert_eq!(segments[0].end.layer(), Some(1));
+        assert_eq!(segments[0].polygons, vec![(0, 0), (1, 0)]);
+        assert_eq!(
+            segments[0].layer_changes().collect::<Vec<_>>(),
+            vec![&PortalCrossing {
+                point: vec2(1.0, 0.375),
+                edge: (vec2(1.0, 0.0), vec2(1.0, 1.0)),
+                from: (0, 0),
+                to: (1, 0),
+            }]
+        );
+        assert_eq!(
+            path.point_at_distance(&mesh, vec2(0.5, 0.5), 0.25).layer(),
+            Some(0)
+        );
+        assert_eq!(
+            path.point_at_distance(&mesh, vec2(0.5, 0.5), 0.75).layer(),
+            Some(1)
+        );
+    }
+
     #[test]
     fn path_with_obstacle_stitch_layers_different_coordinates() {
         let base_mesh = layers_different_coordinates();
@@ -758,10 +787,7 @@ mod tests {
         let indices_from = mesh.layers[0].get_vertices_on_segment(vec2(1.0, 0.0), vec2(1.0, 1.0));
         let indices_to = mesh.layers[1].get_vertices_on_segment(vec2(0.0, 0.0), vec2(0.0, 1.0));
 
-        let stitch_indices = indices_from
-            .into_iter()
-            .zip(indices_to.into_iter())
-            .collect();
+        let stitch_indices = indices_from.into_iter().zip(indices_to).collect();
 
         mesh.stitch_at_vertices(vec![((0, 1), stitch_indices)], false);
 
@@ -809,17 +835,11 @@ mod tests {
 
         let indices_from = mesh.layers[0].get_vertices_on_segment(vec2(1.0, 0.0), vec2(1.0, 1.0));
         let indices_to = mesh.layers[1].get_vertices_on_segment(vec2(0.0, 0.0), vec2(0.0, 1.0));
-        let stitch_indices_0_1 = indices_from
-            .into_iter()
-            .zip(indices_to.into_iter())
-            .collect();
+        let stitch_indices_0_1 = indices_from.into_iter().zip(indices_to).collect();
 
         let indices_from = mesh.layers[1].get_vertices_on_segment(vec2(1.0, 0.0), vec2(1.0, 1.0));
         let indices_to = mesh.layers[2].get_vertices_on_segment(vec2(0.0, 0.0), vec2(0.0, 1.0));
-        let stitch_indices_1_2 = indices_from
-            .into_iter()
-            .zip(indices_to.into_iter())
-            .collect();
+        let stitch_indices_1_2 = indices_from.into_iter().zip(indices_to).collect();
 
         mesh.stitch_at_vertices(
             vec![
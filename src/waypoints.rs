@@ -0,0 +1,112 @@
+use glam::Vec2;
+use thiserror::Error;
+#[cfg(feature = "tracing")]
+use tracing::instrument;
+
+use crate::{helpers::Vec2Helper, Coords, Mesh, Path};
+
+/// A path going through a list of waypoints, built by [`Mesh::path_through`].
+#[derive(Debug, PartialEq)]
+pub struct WaypointPath {
+    /// The complete path, from the first waypoint to the last one.
+    ///
+    /// Waypoints that the path goes through in a straight line are not steps of the path.
+    pub path: Path,
+    /// Length of each leg, from one waypoint to the next.
+    pub legs: Vec<f32>,
+}
+
+/// Errors that can happen when computing a path through waypoints with [`Mesh::path_through`]
+#[derive(Error, Debug, Copy, Clone, PartialEq)]
+pub enum WaypointError {
+    /// At least two waypoints are needed.
+    #[error("At least two waypoints are needed")]
+    NotEnoughWaypoints,
+    /// There is no path for a leg, between the waypoints at index `leg` and `leg + 1`.
+    #[error("No path for leg {leg}, from waypoint {leg} to waypoint {}", leg + 1)]
+    UnreachableLeg {
+        /// Index of the leg, which is also the index of the waypoint where it starts.
+        leg: usize,
+    },
+}
+
+impl Mesh {
+    /// Compute a path going through each waypoint in order.
+    ///
+    /// Each leg is the shortest path from one waypoint to the next, and they are joined into a
+    /// single [`Path`]. The length of each leg is also returned.
+    #[cfg_attr(feature = "tracing", instrument(skip_all))]
+    pub fn path_through(&self, waypoints: &[Coords]) -> Result<WaypointPath, WaypointError> {
+        if waypoints.len() < 2 {
+            return Err(WaypointError::NotEnoughWaypoints);
+        }
+        let mut legs = Vec::with_capacity(waypoints.len() - 1);
+        let mut joined = Path {
+            length: 0.0,
+            path: vec![],
+            #[cfg(feature = "detailed-layers")]
+            path_with_layers: vec![],
+            path_through_polygons: vec![],
+        };
+        for (leg, pair) in waypoints.windows(2).enumerate() {
+            let path = self
+                .path(pair[0], pair[1])
+                .ok_or(WaypointError::UnreachableLeg { leg })?;
+            legs.push(path.length);
+            joined.join(waypoints[0].pos, path);
+        }
+        Ok(WaypointPath { path: joined, legs })
+    }
+}
+
+impl Path {
+    /// Append a path starting where this one ends. `origin` is the start of this path.
+    pub(crate) fn join(&mut self, origin: Vec2, mut next: Path) {
+        self.length += next.length;
+
+        // The next leg starts where this path ends
+        if let Some(end) = self.path.last() {
+            if next.path.first() == Some(end) {
+                next.path.remove(0);
+            }
+        }
+        #[cfg(feature = "detailed-layers")]
+        if let Some(end) = self.path_with_layers.last() {
+            if next.path_with_layers.first() == Some(end) {
+                next.path_with_layers.remove(0);
+            }
+        }
+
+        // Merge the join if the path goes through the waypoint in a straight line
+        if let (Some(&after), Some(&waypoint)) = (next.path.first(), self.path.last()) {
+            let before = self.path.iter().rev().nth(1).copied().unwrap_or(origin);
+            if waypoint.on_segment((before, after)) {
+                self.path.pop();
+            }
+        }
+        #[cfg(feature = "detailed-layers")]
+        if let (Some(&(after, next_layer)), Some(&(waypoint, layer))) =
+            (next.path_with_layers.first(), self.path_with_layers.last())
+        {
+            let before = self
+                .path_with_layers
+                .iter()
+                .rev()
+                .nth(1)
+                .map(|(point, _)| *point)
+                .unwrap_or(origin);
+            if layer == next_layer && waypoint.on_segment((before, after)) {
+                self.path_with_layers.pop();
+            }
+        }
+
+        if self.path_through_polygons.last() == next.path_through_polygons.first() {
+            next.path_through_polygons.remove(0);
+        }
+        self.path.append(&mut next.path);
+        #[cfg(feature = "detailed-layers")]
+        self.path_with_layers.append(&mut next.path_with_layers);
+        self.path_through_polygons
+            .append(&mut next.path_through_polygons);
+    }
+}
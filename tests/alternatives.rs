@@ -0,0 +1,54 @@
+use glam::Vec2;
+use polyanya::{Mesh, PolyanyaFile};
+
+fn arena_mesh() -> Mesh {
+    PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn different_routes() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let to = Vec2::new(45.0, 40.0);
+
+    let paths = mesh.alternative_paths(from, to, 4, 0.5);
+    assert_eq!(paths.len(), 4);
+    assert_eq!(Some(&paths[0]), mesh.path(from, to).as_ref());
+    for (index, path) in paths.iter().enumerate() {
+        assert_eq!(path.path.last(), Some(&to));
+        for other in &paths[index + 1..] {
+            assert!(path.length <= other.length);
+            assert!(path.dissimilarity(other) >= 0.5);
+            assert!(other.dissimilarity(path) >= 0.5);
+        }
+    }
+}
+
+#[test]
+fn not_different_enough() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let to = Vec2::new(45.0, 40.0);
+
+    let paths = mesh.alternative_paths(from, to, 4, 0.9);
+    assert!(paths.len() < 4);
+    assert_eq!(Some(&paths[0]), mesh.path(from, to).as_ref());
+
+    // A single route through a corridor
+    let paths = mesh.alternative_paths(Vec2::new(30.0, 1.0), Vec2::new(35.0, 1.0), 4, 0.3);
+    assert_eq!(paths.len(), 1);
+}
+
+#[test]
+fn no_routes() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let to = Vec2::new(45.0, 40.0);
+
+    assert!(mesh.alternative_paths(from, to, 0, 0.5).is_empty());
+    assert!(mesh
+        .alternative_paths(from, Vec2::new(0.0, 0.0), 4, 0.5)
+        .is_empty());
+}
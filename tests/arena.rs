@@ -1,10 +1,12 @@
+use std::io::{BufRead, BufReader};
+
 use glam::Vec2;
 use polyanya::{Mesh, PolyanyaFile};
 
 macro_rules! assert_delta {
     ($x:expr, $y:expr) => {
         let val = $x.unwrap().length;
-        if !((val - $y).abs() < 0.0001) {
+        if (val - $y).abs() >= 0.0001 || val.is_nan() {
             assert_eq!(val, $y);
         }
     };
@@ -37,7 +39,7 @@ fn arena() {
     );
     assert_delta!(
         arena.path(Vec2::new(1.0, 40.0), Vec2::new(2.0, 39.0)),
-        1.41421
+        std::f32::consts::SQRT_2
     );
     assert_delta!(arena.path(Vec2::new(1.0, 41.0), Vec2::new(1.0, 39.0)), 2.0);
     assert_delta!(arena.path(Vec2::new(1.0, 41.0), Vec2::new(1.0, 44.0)), 3.0);
@@ -628,3 +630,26 @@ fn arena() {
         60.4531
     );
 }
+
+#[test]
+fn arena_distance() {
+    let arena = arena_mesh();
+
+    let file = std::fs::File::open("scenarios/arena.scen").unwrap();
+    for line in BufReader::new(file).lines().skip(1).map_while(Result::ok) {
+        let values = line
+            .split('\t')
+            .skip(4)
+            .take(4)
+            .map(|v| v.parse::<f32>().unwrap())
+            .collect::<Vec<_>>();
+        let from = Vec2::new(values[0], values[1]);
+        let to = Vec2::new(values[2], values[3]);
+        let distance = arena.distance(from, to).unwrap();
+        assert_delta!(arena.path(from, to), distance);
+    }
+    assert_eq!(
+        arena.distance(Vec2::new(1.0, 10.0), Vec2::new(0.0, 0.0)),
+        None
+    );
+}
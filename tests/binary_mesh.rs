@@ -0,0 +1,133 @@
+use glam::{vec2, Vec2};
+use polyanya::{BinaryMeshError, Layer, Mesh, MeshError, PolyanyaFile, Polygon, Vertex};
+
+fn square_layer(offset: Vec2) -> Layer {
+    let mut layer = Layer::new(
+        vec![
+            Vertex::new(vec2(0., 0.), vec![0, u32::MAX]),
+            Vertex::new(vec2(1., 0.), vec![0, u32::MAX]),
+            Vertex::new(vec2(0., 1.), vec![0, u32::MAX]),
+            Vertex::new(vec2(1., 1.), vec![0, u32::MAX]),
+        ],
+        vec![Polygon::new(vec![0, 1, 3, 2], false)],
+    )
+    .unwrap();
+    layer.offset = offset;
+    layer
+}
+
+fn stitched_mesh() -> Mesh {
+    let mut mesh = Mesh::default();
+    mesh.layers = vec![square_layer(Vec2::ZERO), square_layer(vec2(1.0, 0.0))];
+    mesh.layers[1].height = vec![0.0, 0.0, 1.0, 1.0];
+    mesh.set_search_delta(0.25).set_search_steps(3);
+    mesh.bake();
+    mesh.stitch_at_vertices(vec![((0, 1), vec![(1, 0), (3, 2)])], false);
+    mesh
+}
+
+fn assert_same_mesh(a: &Mesh, b: &Mesh) {
+    assert_eq!(a.layers.len(), b.layers.len());
+    for (a, b) in a.layers.iter().zip(&b.layers) {
+        assert_eq!(a.vertices, b.vertices);
+        assert_eq!(a.polygons, b.polygons);
+        assert_eq!(a.offset, b.offset);
+        assert_eq!(a.height, b.height);
+    }
+    assert_eq!(a.search_delta(), b.search_delta());
+    assert_eq!(a.search_steps(), b.search_steps());
+}
+
+#[test]
+fn round_trip_layers() {
+    let mesh = stitched_mesh();
+    let read = Mesh::from_binary(&mesh.to_binary()).unwrap();
+    assert_same_mesh(&mesh, &read);
+
+    assert_eq!(
+        read.path(vec2(0.5, 0.5), vec2(1.5, 0.5)),
+        mesh.path(vec2(0.5, 0.5), vec2(1.5, 0.5))
+    );
+    assert!(read.path(vec2(0.5, 0.5), vec2(1.5, 0.5)).is_some());
+    assert!(read.point_in_mesh(vec2(1.5, 0.5)));
+}
+
+#[test]
+fn round_trip_arena() {
+    let mesh: Mesh = PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap();
+    let bytes = mesh.to_binary();
+    let read = Mesh::from_binary(&bytes).unwrap();
+    assert_same_mesh(&mesh, &read);
+    assert_eq!(read.to_binary(), bytes);
+    assert_eq!(
+        read.path(vec2(3.0, 10.0), vec2(45.0, 40.0)),
+        mesh.path(vec2(3.0, 10.0), vec2(45.0, 40.0))
+    );
+}
+
+#[test]
+fn invalid_bytes() {
+    let bytes = stitched_mesh().to_binary();
+
+    assert_eq!(
+        Mesh::from_binary(b"navmesh").unwrap_err(),
+        BinaryMeshError::InvalidMagic
+    );
+    let mut future = bytes.clone();
+    future[4] = 2;
+    assert_eq!(
+        Mesh::from_binary(&future).unwrap_err(),
+        BinaryMeshError::UnsupportedVersion(2)
+    );
+    for len in [6, 20, bytes.len() / 2, bytes.len() - 1] {
+        assert_eq!(
+            Mesh::from_binary(&bytes[..len]).unwrap_err(),
+            BinaryMeshError::UnexpectedEnd { offset: len }
+        );
+    }
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(
+        Mesh::from_binary(&trailing).unwrap_err(),
+        BinaryMeshError::TrailingBytes {
+            offset: bytes.len()
+        }
+    );
+
+    // A huge number of vertices must fail without allocating for them
+    let mut huge = bytes[..35].to_vec();
+    huge.extend_from_slice(&u32::MAX.to_le_bytes());
+    assert!(matches!(
+        Mesh::from_binary(&huge),
+        Err(BinaryMeshError::UnexpectedEnd { .. })
+    ));
+
+    let mut empty = bytes[..14].to_vec();
+    empty.extend_from_slice(&0_u32.to_le_bytes());
+    assert_eq!(
+        Mesh::from_binary(&empty).unwrap_err(),
+        BinaryMeshError::Mesh(MeshError::EmptyMesh)
+    );
+
+    let mut degenerate = stitched_mesh();
+    degenerate.layers[0].polygons[0].vertices.truncate(2);
+    assert_eq!(
+        Mesh::from_binary(&degenerate.to_binary()).unwrap_err(),
+        BinaryMeshError::Mesh(MeshError::InvalidMesh)
+    );
+}
+
+#[test]
+fn rebuild_polygon_finder() {
+    let mut mesh = Mesh::default();
+    mesh.layers = vec![square_layer(Vec2::ZERO)];
+    mesh.bake();
+    let bytes = mesh.to_binary();
+    // The baked polygon finder is built again, even without default baking
+    let read = Mesh::from_binary(&bytes).unwrap();
+    assert_eq!(read.to_binary(), bytes);
+    assert!(read.point_in_mesh(vec2(0.5, 0.5)));
+    assert!(!read.point_in_mesh(vec2(1.5, 0.5)));
+}
@@ -0,0 +1,136 @@
+use std::io::{BufRead, BufReader};
+
+use glam::{vec2, Vec2};
+use polyanya::{Mesh, PolyanyaFile, Triangulation};
+
+fn arena_mesh() -> Mesh {
+    PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap()
+}
+
+fn arena_targets() -> Vec<Vec2> {
+    let file = std::fs::File::open("scenarios/arena.scen").unwrap();
+    BufReader::new(file)
+        .lines()
+        .skip(1)
+        .map_while(Result::ok)
+        .map(|line| {
+            let values = line
+                .split('\t')
+                .skip(6)
+                .take(2)
+                .map(|v| v.parse::<f32>().unwrap())
+                .collect::<Vec<_>>();
+            Vec2::new(values[0], values[1])
+        })
+        .collect()
+}
+
+#[test]
+fn follow_moving_target() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let search = mesh.cached_search(from).unwrap();
+
+    for to in arena_targets() {
+        let path = mesh.path(from, to).unwrap();
+        let cached = search.path_to(to).unwrap();
+        assert!(
+            (path.length - cached.length).abs() < 0.001,
+            "{from} -> {to}: {} != {}",
+            path.length,
+            cached.length
+        );
+        assert_eq!(cached.path.last(), Some(&to));
+        assert_eq!(mesh.validate_path(from, &cached), Ok(()));
+        assert_eq!(cached.polygons().first(), path.polygons().first());
+        assert_eq!(cached.polygons().last(), path.polygons().last());
+    }
+}
+
+#[test]
+fn block_and_unblock() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let to = Vec2::new(45.0, 40.0);
+    let mut search = mesh.cached_search(from).unwrap();
+
+    let path = search.path_to(to).unwrap();
+    let polygons = path.polygons();
+    let blocked = polygons[polygons.len() / 2];
+    search.block_polygons([blocked]);
+    assert_eq!(search.blocked_polygons().collect::<Vec<_>>(), vec![blocked]);
+
+    let around = search.path_to(to).unwrap();
+    assert!(around.length > path.length);
+    assert!(!around.polygons().contains(&blocked));
+    assert_eq!(mesh.validate_path(from, &around), Ok(()));
+
+    // Same path when computing the distances again with the polygon blocked
+    let mut fresh = mesh.cached_search(Vec2::new(3.0, 11.0)).unwrap();
+    fresh.block_polygons([blocked]);
+    assert!(fresh.set_start(from));
+    assert_eq!(fresh.path_to(to).unwrap().length, around.length);
+
+    search.unblock_polygons([blocked]);
+    assert_eq!(search.blocked_polygons().count(), 0);
+    assert_eq!(search.path_to(to).unwrap().length, path.length);
+}
+
+#[test]
+fn blocked_start() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let mut search = mesh.cached_search(from).unwrap();
+    let start = search.path_to(Vec2::new(45.0, 40.0)).unwrap().polygons()[0];
+
+    search.block_polygons([start]);
+    assert_eq!(search.path_to(Vec2::new(45.0, 40.0)), None);
+    assert!(!search.set_start(from));
+}
+
+#[test]
+fn unreachable_target() {
+    let mesh = arena_mesh();
+    let mut search = mesh.cached_search(Vec2::new(3.0, 10.0)).unwrap();
+    assert_eq!(search.path_to(Vec2::new(0.0, 0.0)), None);
+    assert!(!search.set_start(Vec2::new(0.0, 0.0)));
+    assert!(mesh.cached_search(Vec2::new(0.0, 0.0)).is_none());
+}
+
+#[test]
+fn one_way_stitches() {
+    // Two squares side by side, the second one on its own layer, that can only be entered
+    let left = Triangulation::from_outer_edges(&[
+        vec2(0.0, 0.0),
+        vec2(1.0, 0.0),
+        vec2(1.0, 1.0),
+        vec2(0.0, 1.0),
+    ]);
+    let right = Triangulation::from_outer_edges(&[
+        vec2(1.0, 0.0),
+        vec2(2.0, 0.0),
+        vec2(2.0, 1.0),
+        vec2(1.0, 1.0),
+    ]);
+    let mut mesh = Mesh::default();
+    mesh.layers = vec![
+        left.as_navmesh().layers.remove(0),
+        right.as_navmesh().layers.remove(0),
+    ];
+    mesh.bake();
+    mesh.stitch_at_points(vec![((0, 1), vec![vec2(1.0, 0.0), vec2(1.0, 1.0)])], true);
+
+    assert!(mesh.path(vec2(1.5, 0.5), vec2(0.5, 0.5)).is_none());
+
+    // Paths are searched from the start
+    let search = mesh.cached_search(vec2(0.5, 0.5)).unwrap();
+    assert_eq!(
+        search.path_to(vec2(1.5, 0.5)),
+        mesh.path(vec2(0.5, 0.5), vec2(1.5, 0.5))
+    );
+    assert!(search.path_to(vec2(1.5, 0.5)).is_some());
+    let search = mesh.cached_search(vec2(1.5, 0.5)).unwrap();
+    assert_eq!(search.path_to(vec2(0.5, 0.5)), None);
+}
@@ -0,0 +1,139 @@
+use geo::Area;
+use glam::vec2;
+use polyanya::{Mesh, ScalarField, Triangulation};
+
+fn field(rows: &[&str]) -> ScalarField {
+    ScalarField {
+        width: rows[0].len(),
+        height: rows.len(),
+        values: rows
+            .iter()
+            .flat_map(|row| row.bytes().map(|value| (value - b'0') as f32))
+            .collect(),
+    }
+}
+
+fn mesh(polygon: geo::Polygon<f32>) -> Mesh {
+    let mut mesh = Triangulation::from_geo_polygon(polygon).as_navmesh();
+    while mesh.merge_polygons() {}
+    mesh
+}
+
+#[test]
+fn square() {
+    let field = field(&["00000", "01110", "01110", "01110", "00000"]);
+    let contours = field.contours(0.5, 0.0);
+    assert_eq!(contours.len(), 1);
+    assert!(contours[0].interiors().is_empty());
+    // The contour is halfway between the values inside and outside, cutting the corners
+    assert_eq!(contours[0].unsigned_area(), 8.5);
+
+    let mesh = mesh(contours[0].clone());
+    assert!(mesh.point_in_mesh(vec2(2.0, 2.0)));
+    assert!(mesh.point_in_mesh(vec2(0.6, 2.0)));
+    assert!(!mesh.point_in_mesh(vec2(0.55, 0.55)));
+}
+
+#[test]
+fn interpolated_threshold() {
+    let field = ScalarField {
+        width: 5,
+        height: 2,
+        values: [0.0, 0.25, 0.5, 0.75, 1.0].repeat(2),
+    };
+    let contours = field.contours(0.6, 0.0);
+    assert_eq!(contours.len(), 1);
+    let mut points: Vec<_> = contours[0]
+        .exterior()
+        .points()
+        .map(|point| vec2(point.x(), point.y()))
+        .collect();
+    points.pop();
+    points.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+    // Areas touching the border are closed along the last values
+    assert_eq!(
+        points,
+        vec![
+            vec2(2.4, 0.0),
+            vec2(2.4, 1.0),
+            vec2(4.0, 0.0),
+            vec2(4.0, 1.0)
+        ]
+    );
+}
+
+#[test]
+fn holes_and_islands() {
+    let field = field(&[
+        "1111111", "1000001", "1011101", "1010101", "1011101", "1000001", "1111111",
+    ]);
+    let mut contours = field.contours(0.5, 0.0);
+    assert_eq!(contours.len(), 2);
+    contours.sort_by(|a, b| b.unsigned_area().total_cmp(&a.unsigned_area()));
+    // The outer ring has a hole, containing the inner ring with its own hole
+    assert_eq!(contours[0].interiors().len(), 1);
+    assert_eq!(contours[1].interiors().len(), 1);
+
+    let outer = mesh(contours[0].clone());
+    assert!(outer.point_in_mesh(vec2(0.2, 3.0)));
+    assert!(!outer.point_in_mesh(vec2(1.0, 3.0)));
+    assert!(!outer.point_in_mesh(vec2(2.0, 3.0)));
+    let inner = mesh(contours[1].clone());
+    assert!(inner.point_in_mesh(vec2(2.0, 3.0)));
+    assert!(!inner.point_in_mesh(vec2(3.0, 3.0)));
+}
+
+#[test]
+fn saddle() {
+    // Opposite corners above the threshold are connected if the center is above it too
+    let field = field(&["0000", "0900", "0090", "0000"]);
+    assert_eq!(field.contours(2.0, 0.0).len(), 1);
+    assert_eq!(field.contours(5.0, 0.0).len(), 2);
+}
+
+#[test]
+fn simplification() {
+    let size = 41;
+    let values = (0..size * size)
+        .map(|index| {
+            let point = vec2((index % size) as f32, (index / size) as f32);
+            20.0 - point.distance(vec2(20.0, 20.0))
+        })
+        .collect();
+    let field = ScalarField {
+        width: size,
+        height: size,
+        values,
+    };
+    let detailed = field.contours(5.0, 0.0);
+    let simplified = field.contours(5.0, 0.5);
+    assert_eq!(simplified.len(), 1);
+    assert!(simplified[0].exterior().0.len() < detailed[0].exterior().0.len() / 2);
+    assert!((simplified[0].unsigned_area() - detailed[0].unsigned_area()).abs() < 10.0);
+}
+
+#[test]
+fn alpha_mask_with_heights() {
+    let alpha = [0, 0, 0, 0, 0, 255, 255, 0, 0, 255, 255, 0, 0, 0, 0, 0];
+    let mask = ScalarField::from_alpha_mask(4, 4, &alpha);
+    assert_eq!(mask.value(1, 1), Some(1.0));
+    assert_eq!(mask.value(4, 1), None);
+    let contours = mask.contours(0.5, 0.0);
+    assert_eq!(contours.len(), 1);
+
+    let heightmap = ScalarField {
+        width: 4,
+        height: 4,
+        values: (0..16).map(|index| (index % 4) as f32).collect(),
+    };
+    assert_eq!(heightmap.sample(vec2(1.5, 2.0)), 1.5);
+    assert_eq!(heightmap.sample(vec2(10.0, -1.0)), 3.0);
+
+    let mut mesh = mesh(contours[0].clone());
+    mesh.layers[0].sample_heights(&heightmap);
+    let layer = &mesh.layers[0];
+    assert_eq!(layer.height.len(), layer.vertices.len());
+    for (vertex, height) in layer.vertices.iter().zip(&layer.height) {
+        assert_eq!(*height, vertex.coords.x);
+    }
+}
@@ -0,0 +1,102 @@
+use std::io::{BufRead, BufReader};
+
+use glam::Vec2;
+use polyanya::{Mesh, PolyanyaFile, SearchPolicy, TurnPenalty};
+
+fn arena_mesh() -> Mesh {
+    PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap()
+}
+
+fn arena_scenarios() -> Vec<(Vec2, Vec2)> {
+    let file = std::fs::File::open("scenarios/arena.scen").unwrap();
+    BufReader::new(file)
+        .lines()
+        .skip(1)
+        .map_while(Result::ok)
+        .map(|line| {
+            let values = line
+                .split('\t')
+                .skip(4)
+                .take(4)
+                .map(|v| v.parse::<f32>().unwrap())
+                .collect::<Vec<_>>();
+            (
+                Vec2::new(values[0], values[1]),
+                Vec2::new(values[2], values[3]),
+            )
+        })
+        .collect()
+}
+
+/// Cost of a path for a policy, with segments on the first layer
+fn cost(policy: &impl SearchPolicy, from: Vec2, path: &[Vec2]) -> f32 {
+    let points = std::iter::once(from)
+        .chain(path.iter().copied())
+        .collect::<Vec<_>>();
+    let segments = points
+        .windows(2)
+        .map(|s| policy.segment_cost(s[0], s[1], 0, 0))
+        .sum::<f32>();
+    let turns = points
+        .windows(3)
+        .map(|c| policy.turn_cost(c[1], (c[1] - c[0]).angle_to(c[2] - c[1]).abs()))
+        .sum::<f32>();
+    segments + turns
+}
+
+#[test]
+fn no_turn_penalty() {
+    let mesh = arena_mesh();
+    for (from, to) in arena_scenarios() {
+        let path = mesh.path(from, to).unwrap();
+        let with_policy = mesh
+            .path_with_policy(from, to, &TurnPenalty::new(0.0))
+            .unwrap();
+        assert!(
+            (path.length - with_policy.length).abs() < 0.001,
+            "{from} -> {to}: {} != {}",
+            path.length,
+            with_policy.length
+        );
+    }
+}
+
+#[test]
+fn prefer_gentle_turns() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(1.0, 10.0);
+    let to = Vec2::new(43.0, 40.0);
+
+    let path = mesh.path(from, to).unwrap();
+    assert_eq!(
+        path.path,
+        vec![Vec2::new(15.0, 19.0), Vec2::new(35.0, 31.0), to]
+    );
+
+    // A slightly longer path with a single turn is better
+    let path = mesh
+        .path_with_policy(from, to, &TurnPenalty::new(5.0))
+        .unwrap();
+    assert_eq!(path.path, vec![Vec2::new(31.0, 35.0), to]);
+}
+
+#[test]
+fn never_costs_more() {
+    let mesh = arena_mesh();
+    let policy = TurnPenalty::new(5.0);
+    for (from, to) in arena_scenarios() {
+        let path = mesh.path(from, to).unwrap();
+        let with_policy = mesh.path_with_policy(from, to, &policy).unwrap();
+        let (shortest, cheapest) = (
+            cost(&policy, from, &path.path),
+            cost(&policy, from, &with_policy.path),
+        );
+        assert!(
+            cheapest <= shortest + 0.001,
+            "{from} -> {to}: {cheapest} > {shortest}"
+        );
+        assert_eq!(path.path.last(), with_policy.path.last());
+    }
+}
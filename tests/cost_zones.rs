@@ -0,0 +1,93 @@
+use glam::Vec2;
+use polyanya::{CostZone, Mesh, PolyanyaFile, SearchPolicy};
+
+macro_rules! assert_delta {
+    ($x:expr, $y:expr) => {
+        let val = $x;
+        if (val - $y).abs() >= 0.001 || val.is_nan() {
+            panic!("assertion failed: {} != {}", val, $y);
+        }
+    };
+}
+
+fn arena_mesh() -> Mesh {
+    PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn no_zones() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let to = Vec2::new(45.0, 40.0);
+    assert_eq!(
+        mesh.path_with_cost_zones(from, to, &[]),
+        mesh.path(from, to)
+    );
+}
+
+#[test]
+fn avoid_expensive_zone() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let to = Vec2::new(45.0, 40.0);
+
+    let path = mesh.path(from, to).unwrap();
+    assert_eq!(
+        path.path,
+        vec![Vec2::new(15.0, 19.0), Vec2::new(35.0, 31.0), to]
+    );
+
+    // Going through the zone is still cheaper than going around
+    let cheap = [CostZone::circle(Vec2::new(9.0, 14.5), 1.0, 1.5)];
+    let through = mesh.path_with_cost_zones(from, to, &cheap).unwrap();
+    assert_eq!(through.path, path.path);
+    assert_delta!(through.length, path.length);
+
+    let expensive = [CostZone::circle(Vec2::new(9.0, 14.5), 1.0, 3.0)];
+    let around = mesh.path_with_cost_zones(from, to, &expensive).unwrap();
+    assert_eq!(around.path, vec![Vec2::new(19.0, 15.0), to]);
+    assert_delta!(around.length, 52.83243);
+}
+
+#[test]
+fn traverse_zone_when_needed() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let to = Vec2::new(45.0, 40.0);
+
+    let zones = [CostZone::polygon(
+        vec![
+            Vec2::new(40.0, 35.0),
+            Vec2::new(50.0, 35.0),
+            Vec2::new(50.0, 50.0),
+            Vec2::new(40.0, 50.0),
+        ],
+        100.0,
+    )];
+    let path = mesh.path_with_cost_zones(from, to, &zones).unwrap();
+    assert_eq!(*path.path.last().unwrap(), to);
+}
+
+#[test]
+fn overlapping_zones() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let to = Vec2::new(45.0, 40.0);
+
+    // The extra costs of the zones add up, so they cost three times the length where they overlap
+    let zones = [
+        CostZone::circle(Vec2::new(9.0, 14.5), 1.0, 2.0),
+        CostZone::circle(Vec2::new(9.0, 14.5), 1.0, 2.0),
+    ];
+    let segment = (Vec2::new(8.0, 14.5), Vec2::new(10.0, 14.5));
+    assert_delta!(zones.segment_cost(segment.0, segment.1, 0, 0), 6.0);
+    assert_delta!(zones[..1].segment_cost(segment.0, segment.1, 0, 0), 4.0);
+
+    // Like a single zone of weight 3
+    let around = mesh.path_with_cost_zones(from, to, &zones).unwrap();
+    assert_eq!(around.path, vec![Vec2::new(19.0, 15.0), to]);
+    let single = [CostZone::circle(Vec2::new(9.0, 14.5), 1.0, 3.0)];
+    assert_eq!(mesh.path_with_cost_zones(from, to, &single), Some(around));
+}
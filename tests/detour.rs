@@ -0,0 +1,335 @@
+#![cfg(feature = "recast")]
+
+use std::fs::File;
+
+use glam::{vec2, vec3, Vec3, Vec3Swizzles};
+use polyanya::{
+    DetourTile, DetourTileError, Mesh, OffMeshConnection, RecastFullMesh, RecastPolyMesh,
+    RecastPolyMeshDetail,
+};
+
+/// A ground polygon: its area id, its vertices followed by the vertices of its detail mesh, the
+/// number of vertices of the polygon, and the triangles of its detail mesh.
+struct TilePolygon {
+    area: u8,
+    vertices: Vec<Vec3>,
+    nb_polygon_vertices: usize,
+    triangles: Vec<[u8; 3]>,
+}
+
+fn put_u32(bytes: &mut Vec<u8>, value: u32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_f32(bytes: &mut Vec<u8>, value: f32) {
+    bytes.extend_from_slice(&value.to_le_bytes());
+}
+
+fn put_vec3(bytes: &mut Vec<u8>, value: Vec3) {
+    for coordinate in value.to_array() {
+        put_f32(bytes, coordinate);
+    }
+}
+
+/// Encode polygons and off-mesh connections like `dtCreateNavMeshData`, each polygon having
+/// its own vertices.
+fn tile_bytes(polygons: &[TilePolygon], off_mesh_connections: &[OffMeshConnection]) -> Vec<u8> {
+    let nb_vertices: usize = polygons
+        .iter()
+        .map(|p| p.nb_polygon_vertices)
+        .sum::<usize>()
+        + off_mesh_connections.len() * 2;
+    let nb_detail_vertices: usize = polygons
+        .iter()
+        .map(|p| p.vertices.len() - p.nb_polygon_vertices)
+        .sum();
+    let nb_triangles: usize = polygons.iter().map(|p| p.triangles.len()).sum();
+
+    let mut bytes = vec![];
+    bytes.extend_from_slice(b"VAND");
+    for value in [
+        7,
+        0,
+        0,
+        0,
+        0,
+        (polygons.len() + off_mesh_connections.len()) as u32,
+        nb_vertices as u32,
+        0,
+        polygons.len() as u32,
+        nb_detail_vertices as u32,
+        nb_triangles as u32,
+        0,
+        off_mesh_connections.len() as u32,
+        polygons.len() as u32,
+    ] {
+        put_u32(&mut bytes, value);
+    }
+    // Agent settings, bounds and quantization factor
+    bytes.extend_from_slice(&[0; 40]);
+
+    for polygon in polygons {
+        for vertex in &polygon.vertices[..polygon.nb_polygon_vertices] {
+            put_vec3(&mut bytes, *vertex);
+        }
+    }
+    for connection in off_mesh_connections {
+        put_vec3(&mut bytes, connection.start);
+        put_vec3(&mut bytes, connection.end);
+    }
+
+    let mut first_vertex = 0;
+    let polygon_vertices = polygons
+        .iter()
+        .map(|polygon| (polygon.nb_polygon_vertices, polygon.area))
+        .chain(off_mesh_connections.iter().map(|c| (2, 1 << 6 | c.area)));
+    for (nb_polygon_vertices, area_and_type) in polygon_vertices {
+        put_u32(&mut bytes, 0);
+        for index in 0..6 {
+            let vertex = if index < nb_polygon_vertices {
+                first_vertex + index
+            } else {
+                0
+            };
+            bytes.extend_from_slice(&(vertex as u16).to_le_bytes());
+        }
+        bytes.extend_from_slice(&[0; 12]);
+        bytes.extend_from_slice(&1u16.to_le_bytes());
+        bytes.extend_from_slice(&[nb_polygon_vertices as u8, area_and_type]);
+        first_vertex += nb_polygon_vertices;
+    }
+
+    let (mut vertex_base, mut triangle_base) = (0, 0);
+    for polygon in polygons {
+        let nb_detail_vertices = polygon.vertices.len() - polygon.nb_polygon_vertices;
+        put_u32(&mut bytes, vertex_base);
+        put_u32(&mut bytes, triangle_base);
+        bytes.extend_from_slice(&[
+            nb_detail_vertices as u8,
+            polygon.triangles.len() as u8,
+            0,
+            0,
+        ]);
+        vertex_base += nb_detail_vertices as u32;
+        triangle_base += polygon.triangles.len() as u32;
+    }
+    for polygon in polygons {
+        for vertex in &polygon.vertices[polygon.nb_polygon_vertices..] {
+            put_vec3(&mut bytes, *vertex);
+        }
+    }
+    for polygon in polygons {
+        for triangle in &polygon.triangles {
+            bytes.extend_from_slice(triangle);
+            bytes.push(0);
+        }
+    }
+
+    for (index, connection) in off_mesh_connections.iter().enumerate() {
+        put_vec3(&mut bytes, connection.start);
+        put_vec3(&mut bytes, connection.end);
+        put_f32(&mut bytes, connection.radius);
+        bytes.extend_from_slice(&((polygons.len() + index) as u16).to_le_bytes());
+        bytes.extend_from_slice(&[connection.bidirectional as u8, 0xff]);
+        put_u32(&mut bytes, connection.user_id);
+    }
+    bytes
+}
+
+fn recast_meshes() -> (RecastPolyMesh, RecastPolyMeshDetail) {
+    (
+        serde_json::from_reader(File::open("meshes/recast/poly_mesh.json").unwrap()).unwrap(),
+        serde_json::from_reader(File::open("meshes/recast/detail_mesh.json").unwrap()).unwrap(),
+    )
+}
+
+/// The polygons of the recast meshes, as they would be saved in a Detour tile.
+fn recast_polygons() -> Vec<TilePolygon> {
+    let (rasterised, detailed) = recast_meshes();
+    rasterised
+        .polygons()
+        .zip(&detailed.meshes)
+        .zip(&rasterised.areas)
+        .map(|((vertices, mesh), area)| TilePolygon {
+            area: area.0 & 0x3f,
+            vertices: detailed.vertices[mesh.base_vertex_index as usize..]
+                [..mesh.vertex_count as usize]
+                .to_vec(),
+            nb_polygon_vertices: vertices.count(),
+            triangles: detailed.triangles[mesh.base_triangle_index as usize..]
+                [..mesh.triangle_count as usize]
+                .to_vec(),
+        })
+        .collect()
+}
+
+fn jump() -> OffMeshConnection {
+    OffMeshConnection {
+        start: vec3(10.0, 0.0, 10.0),
+        end: vec3(12.0, 2.0, 10.0),
+        radius: 0.5,
+        area: 5,
+        flags: 1,
+        bidirectional: true,
+        user_id: 42,
+    }
+}
+
+#[test]
+fn same_as_recast() {
+    let tile = DetourTile::from_bytes(&tile_bytes(&recast_polygons(), &[jump()])).unwrap();
+    assert_eq!(tile.off_mesh_connections, vec![jump()]);
+    let mesh: Mesh = tile.into();
+
+    let (rasterised, detailed) = recast_meshes();
+    let recast: Mesh = RecastFullMesh::new(rasterised, detailed).into();
+    // Areas are the same layers, with the default walkable area first
+    assert_eq!(mesh.layers.len(), 5);
+    for (layer, recast_layer) in mesh.layers.iter().zip(&recast.layers) {
+        assert_eq!(layer.polygons, recast_layer.polygons);
+        assert_eq!(layer.height, recast_layer.height);
+    }
+
+    let start = vec3(46.998413, 9.998184, 1.717747);
+    let end = vec3(20.703018, 18.651773, -80.770_2);
+    let path = mesh.path(start.xz(), end.xz()).unwrap();
+    let expected = recast.path(start.xz(), end.xz()).unwrap();
+    assert_eq!(path.path, expected.path);
+    assert_eq!(path.length, expected.length);
+}
+
+#[test]
+fn navmesh_set() {
+    let polygons = recast_polygons();
+    let (first, second) = polygons.split_at(polygons.len() / 2);
+
+    let mut bytes = vec![];
+    bytes.extend_from_slice(b"TESM");
+    put_u32(&mut bytes, 1);
+    put_u32(&mut bytes, 2);
+    bytes.extend_from_slice(&[0; 28]);
+    for (reference, tile) in [(1, tile_bytes(first, &[])), (2, tile_bytes(second, &[]))] {
+        put_u32(&mut bytes, reference);
+        put_u32(&mut bytes, tile.len() as u32);
+        bytes.extend_from_slice(&tile);
+    }
+
+    let tiles = DetourTile::from_navmesh_set(&bytes).unwrap();
+    assert_eq!(tiles.len(), 2);
+    // Tiles are connected by their common vertices
+    let mesh = Mesh::from_detour_tiles(&tiles);
+    let start = vec3(46.998413, 9.998184, 1.717747);
+    let end = vec3(20.703018, 18.651773, -80.770_2);
+    let path = mesh.path(start.xz(), end.xz()).unwrap();
+    assert!((path.length - 126.75868).abs() < 0.0001);
+}
+
+#[test]
+fn areas_as_layers() {
+    let square = |area: u8, x: f32| TilePolygon {
+        area,
+        vertices: vec![
+            vec3(x, 1.0, 0.0),
+            vec3(x, 1.0, 1.0),
+            vec3(x + 1.0, 1.0, 1.0),
+            vec3(x + 1.0, 1.0, 0.0),
+        ],
+        nb_polygon_vertices: 4,
+        triangles: vec![[0, 1, 2], [0, 2, 3]],
+    };
+    let tile = DetourTile::from_bytes(&tile_bytes(
+        &[square(7, 0.0), square(3, 2.0), square(63, 4.0)],
+        &[],
+    ))
+    .unwrap();
+    let mesh: Mesh = tile.into();
+    // The default walkable area is the first layer, then area 3 and area 7
+    assert_eq!(mesh.layers.len(), 3);
+    let layers = |point| {
+        mesh.get_point_layer(point)
+            .iter()
+            .map(|coords| coords.layer())
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(layers(vec2(4.5, 0.5)), vec![Some(0)]);
+    assert_eq!(layers(vec2(2.5, 0.5)), vec![Some(1)]);
+    assert_eq!(layers(vec2(0.5, 0.5)), vec![Some(2)]);
+    assert!(mesh
+        .layers
+        .iter()
+        .all(|layer| layer.height.iter().all(|h| *h == 1.0)));
+}
+
+#[test]
+fn invalid_tiles() {
+    let polygon = TilePolygon {
+        area: 0,
+        vertices: vec![
+            vec3(0.0, 0.0, 0.0),
+            vec3(0.0, 0.0, 1.0),
+            vec3(1.0, 0.0, 0.0),
+        ],
+        nb_polygon_vertices: 3,
+        triangles: vec![[0, 1, 2]],
+    };
+    let bytes = tile_bytes(&[polygon], &[]);
+    assert!(DetourTile::from_bytes(&bytes).is_ok());
+
+    assert_eq!(
+        DetourTile::from_bytes(&bytes[..150]).unwrap_err(),
+        DetourTileError::UnexpectedEnd { offset: 150 }
+    );
+    let mut wrong = bytes.clone();
+    wrong[0] = b'X';
+    assert_eq!(
+        DetourTile::from_bytes(&wrong).unwrap_err(),
+        DetourTileError::InvalidMagic
+    );
+    let mut wrong = bytes.clone();
+    wrong[4] = 8;
+    assert_eq!(
+        DetourTile::from_bytes(&wrong).unwrap_err(),
+        DetourTileError::UnsupportedVersion(8)
+    );
+    // The triangle of the detail mesh uses a fourth vertex
+    let mut wrong = bytes.clone();
+    let triangle = wrong.len() - 4;
+    wrong[triangle] = 3;
+    assert_eq!(
+        DetourTile::from_bytes(&wrong).unwrap_err(),
+        DetourTileError::InvalidPolygon { polygon: 0 }
+    );
+    assert_eq!(
+        DetourTile::from_navmesh_set(&bytes).unwrap_err(),
+        DetourTileError::InvalidMagic
+    );
+}
+
+/// A navigation mesh set saved like RecastDemo saves a solo mesh, from the Recast meshes, with
+/// its links and bounding volume tree. It's generated by `meshes/detour/make_solo_navmesh.py`.
+#[test]
+fn recast_demo_navmesh_set() {
+    let bytes = std::fs::read("meshes/detour/solo_navmesh.bin").unwrap();
+    let tiles = DetourTile::from_navmesh_set(&bytes).unwrap();
+    assert_eq!(tiles.len(), 1);
+    assert_eq!((tiles[0].x, tiles[0].y, tiles[0].layer), (0, 0, 0));
+    assert!(tiles[0].off_mesh_connections.is_empty());
+
+    // The ground area is the first layer, then water, road, door and grass
+    let mesh = Mesh::from_detour_tiles(&tiles);
+    assert_eq!(
+        mesh.layers
+            .iter()
+            .map(|layer| layer.polygons.len())
+            .collect::<Vec<_>>(),
+        vec![285, 8, 20, 3, 7]
+    );
+
+    let start = vec3(46.998413, 9.998184, 1.717747);
+    let end = vec3(20.703018, 18.651773, -80.770_2);
+    let path = mesh.path(start.xz(), end.xz()).unwrap();
+    assert_eq!(path.path.len(), 10);
+    assert_eq!(path.path[0], vec2(8.584785, -2.1412811));
+    assert_eq!(path.path.last(), Some(&end.xz()));
+    assert!((path.length - 126.75868).abs() < 0.0001);
+}
@@ -0,0 +1,178 @@
+#![cfg(feature = "geojson")]
+
+use glam::vec2;
+use polyanya::{
+    geojson::{feature::Id, GeoJson, JsonValue, Value},
+    GeoJsonError, Layer, Mesh, Polygon, Triangulation, Vertex,
+};
+
+fn two_squares() -> Mesh {
+    let mut layer = Layer::new(
+        vec![
+            Vertex::new(vec2(0., 0.), vec![0, u32::MAX]),
+            Vertex::new(vec2(1., 0.), vec![1, 0, u32::MAX]),
+            Vertex::new(vec2(2., 0.), vec![1, u32::MAX]),
+            Vertex::new(vec2(0., 1.), vec![0, u32::MAX]),
+            Vertex::new(vec2(1., 1.), vec![0, 1, u32::MAX]),
+            Vertex::new(vec2(2., 2.), vec![1, u32::MAX]),
+        ],
+        vec![
+            Polygon::new(vec![0, 1, 4, 3], false),
+            Polygon::new(vec![1, 2, 5, 4], false),
+        ],
+    )
+    .unwrap();
+    layer.offset = vec2(10.0, 0.0);
+    let mut mesh = Mesh::default();
+    mesh.layers = vec![layer.clone(), layer];
+    mesh
+}
+
+#[test]
+fn layer_to_feature_collection() {
+    let mesh = two_squares();
+    let collections = mesh.to_geojson();
+    assert_eq!(collections.len(), 2);
+    let features = &collections[1].features;
+    assert_eq!(features.len(), 2);
+
+    let feature = &features[1];
+    assert_eq!(feature.id, Some(Id::Number((1u32 << 24 | 1).into())));
+    assert_eq!(feature.property("index"), Some(&JsonValue::from(1)));
+    assert_eq!(feature.property("layer"), Some(&JsonValue::from(1)));
+    assert_eq!(feature.property("area"), Some(&JsonValue::from(1.5)));
+    match &feature.geometry.as_ref().unwrap().value {
+        Value::Polygon(rings) => assert_eq!(
+            rings,
+            &vec![vec![
+                vec![11.0, 0.0],
+                vec![12.0, 0.0],
+                vec![12.0, 2.0],
+                vec![11.0, 1.0],
+                vec![11.0, 0.0],
+            ]]
+        ),
+        _ => panic!("expected a polygon"),
+    }
+
+    #[cfg(not(feature = "no-default-baking"))]
+    assert_eq!(
+        features[0].property("island"),
+        features[1].property("island")
+    );
+    let mut unbaked = mesh.layers[0].clone();
+    unbaked.unbake();
+    assert_eq!(
+        unbaked.to_geojson(0).features[0].property("island"),
+        Some(&JsonValue::Null)
+    );
+}
+
+#[test]
+fn skip_empty_polygons() {
+    let mut layer = two_squares().layers[0].clone();
+    layer.polygons[0].vertices.clear();
+    let features = layer.to_geojson(0).features;
+    assert_eq!(features.len(), 1);
+    assert_eq!(features[0].property("index"), Some(&JsonValue::from(1)));
+}
+
+#[test]
+fn path_to_line_string() {
+    let mut mesh = two_squares();
+    mesh.layers.truncate(1);
+    mesh.layers[0].offset = vec2(0.0, 0.0);
+    #[cfg(feature = "no-default-baking")]
+    mesh.bake();
+    let path = mesh.path(vec2(0.5, 0.5), vec2(1.8, 1.5)).unwrap();
+    let feature = path.to_geojson(vec2(0.5, 0.5));
+    assert_eq!(
+        feature.property("length"),
+        Some(&JsonValue::from(path.length))
+    );
+    match &feature.geometry.as_ref().unwrap().value {
+        Value::LineString(points) => {
+            assert_eq!(points.len(), path.path.len() + 1);
+            assert_eq!(points[0], vec![0.5, 0.5]);
+            assert_eq!(points.last().unwrap(), &vec![1.8f32 as f64, 1.5]);
+        }
+        _ => panic!("expected a line string"),
+    }
+}
+
+#[test]
+fn triangulation_from_feature_collection() {
+    let geojson: GeoJson = r#"{
+        "type": "FeatureCollection",
+        "features": [
+            {
+                "type": "Feature",
+                "properties": {},
+                "geometry": { "type": "Point", "coordinates": [5.0, 5.0] }
+            },
+            {
+                "type": "Feature",
+                "properties": {},
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [[[7.0, 1.0], [9.0, 1.0], [9.0, 3.0], [7.0, 3.0], [7.0, 1.0]]]
+                }
+            },
+            {
+                "type": "Feature",
+                "properties": {},
+                "geometry": {
+                    "type": "Polygon",
+                    "coordinates": [
+                        [[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]],
+                        [[2.0, 2.0], [2.0, 4.0], [4.0, 4.0], [4.0, 2.0], [2.0, 2.0]]
+                    ]
+                }
+            }
+        ]
+    }"#
+    .parse()
+    .unwrap();
+    let mut mesh = Triangulation::from_geojson(&geojson).unwrap().as_navmesh();
+    while mesh.merge_polygons() {}
+    #[cfg(feature = "no-default-baking")]
+    mesh.bake();
+    assert!(mesh.point_in_mesh(vec2(5.0, 5.0)));
+    assert!(!mesh.point_in_mesh(vec2(3.0, 3.0)));
+    assert!(!mesh.point_in_mesh(vec2(8.0, 2.0)));
+    assert!(mesh.path(vec2(1.0, 1.0), vec2(9.5, 9.5)).is_some());
+
+    // The polygons of the mesh cover the walkable area
+    let area: f64 = mesh.to_geojson()[0]
+        .features
+        .iter()
+        .map(|feature| feature.property("area").unwrap().as_f64().unwrap())
+        .sum();
+    assert!((area - 92.0).abs() < 1e-3);
+}
+
+#[test]
+fn invalid_geojson() {
+    let point: GeoJson = r#"{ "type": "Point", "coordinates": [5.0, 5.0] }"#.parse().unwrap();
+    assert!(matches!(
+        Triangulation::from_geojson(&point),
+        Err(GeoJsonError::NoPolygon)
+    ));
+
+    let with_holes: GeoJson = r#"{
+        "type": "MultiPolygon",
+        "coordinates": [
+            [[[0.0, 0.0], [10.0, 0.0], [10.0, 10.0], [0.0, 10.0], [0.0, 0.0]]],
+            [
+                [[2.0, 2.0], [5.0, 2.0], [5.0, 5.0], [2.0, 5.0], [2.0, 2.0]],
+                [[3.0, 3.0], [3.0, 4.0], [4.0, 4.0], [4.0, 3.0], [3.0, 3.0]]
+            ]
+        ]
+    }"#
+    .parse()
+    .unwrap();
+    assert!(matches!(
+        Triangulation::from_geojson(&with_holes),
+        Err(GeoJsonError::ObstacleWithHoles { index: 1 })
+    ));
+}
@@ -0,0 +1,126 @@
+use glam::vec2;
+use polyanya::{Mesh, MeshError, PolyanyaFileError};
+
+fn grid(rows: &[&str]) -> Mesh {
+    let walkable: Vec<bool> = rows
+        .iter()
+        .flat_map(|row| row.bytes().map(|cell| cell == b'.'))
+        .collect();
+    Mesh::from_grid(rows[0].len(), rows.len(), &walkable).unwrap()
+}
+
+#[test]
+fn open_grid() {
+    let mesh = grid(&["....", "....", "...."]);
+    assert_eq!(mesh.layers.len(), 1);
+    assert_eq!(mesh.layers[0].polygons.len(), 1);
+    assert!(mesh.point_in_mesh(vec2(3.9, 2.9)));
+    assert!(!mesh.point_in_mesh(vec2(4.5, 2.9)));
+    let path = mesh.path(vec2(0.5, 0.5), vec2(3.5, 2.5)).unwrap();
+    assert_eq!(path.path, vec![vec2(3.5, 2.5)]);
+}
+
+#[test]
+fn around_obstacle() {
+    let mesh = grid(&["....", ".##.", "...."]);
+    assert!(!mesh.point_in_mesh(vec2(1.5, 1.5)));
+    let path = mesh.path(vec2(0.5, 1.5), vec2(3.5, 1.5)).unwrap();
+    assert_eq!(
+        path.path,
+        vec![vec2(1.0, 1.0), vec2(3.0, 1.0), vec2(3.5, 1.5)]
+    );
+}
+
+#[test]
+fn separate_regions() {
+    // Regions touching only at a corner are not connected
+    let mesh = grid(&["..#..", "..#..", "##...", "..#.."]);
+    assert!(mesh.point_in_mesh(vec2(0.5, 3.5)));
+    assert!(mesh.path(vec2(0.5, 0.5), vec2(4.5, 0.5)).is_none());
+    assert!(mesh.path(vec2(0.5, 3.5), vec2(4.5, 0.5)).is_none());
+    assert!(mesh.path(vec2(3.5, 3.5), vec2(4.5, 0.5)).is_some());
+}
+
+#[test]
+fn obstacle_touching_border_at_corner() {
+    // The blocked cell in the middle touches the blocked corner only at a point
+    let mesh = grid(&["#...", ".#..", "....", "...."]);
+    assert!(!mesh.point_in_mesh(vec2(0.5, 0.5)));
+    assert!(!mesh.point_in_mesh(vec2(1.5, 1.5)));
+    assert!(mesh.point_in_mesh(vec2(0.5, 1.5)));
+    assert!(mesh.point_in_mesh(vec2(1.5, 0.5)));
+    let path = mesh.path(vec2(0.5, 1.9), vec2(1.9, 0.5)).unwrap();
+    assert!(path.length > 2.0);
+}
+
+#[test]
+fn moving_ai_map() {
+    let map = "type octile
+height 4
+width 6
+map
+@@@@@@
+@..T.@
+@.GT.@
+@....@
+";
+    let mesh = Mesh::from_moving_ai_map_bytes(map.as_bytes()).unwrap();
+    assert!(mesh.point_in_mesh(vec2(2.5, 2.5)));
+    assert!(!mesh.point_in_mesh(vec2(3.5, 1.5)));
+    let path = mesh.path(vec2(1.5, 1.5), vec2(4.5, 1.5)).unwrap();
+    assert_eq!(
+        path.path,
+        vec![vec2(3.0, 3.0), vec2(4.0, 3.0), vec2(4.5, 1.5)]
+    );
+}
+
+#[test]
+fn invalid_grids() {
+    assert!(matches!(
+        Mesh::from_grid(2, 2, &[true; 3]),
+        Err(MeshError::InvalidMesh)
+    ));
+    assert!(matches!(
+        Mesh::from_grid(2, 2, &[false; 4]),
+        Err(MeshError::EmptyMesh)
+    ));
+    assert!(matches!(
+        Mesh::from_moving_ai_map_bytes(b"type octile\nheight 2\nwidth 2\nmap\n..\n...\n"),
+        Err(PolyanyaFileError::UnexpectedToken { line: 6, .. })
+    ));
+    assert!(matches!(
+        Mesh::from_moving_ai_map_bytes(b"type octile\nheight 2\nmap\n..\n..\n"),
+        Err(PolyanyaFileError::UnexpectedToken { line: 3, .. })
+    ));
+    assert!(matches!(
+        Mesh::from_moving_ai_map_bytes(b"type octile\nheight 2\nwidth 2\nmap\n..\n"),
+        Err(PolyanyaFileError::UnexpectedEnd { .. })
+    ));
+}
+
+#[test]
+fn oversized_grids() {
+    assert!(matches!(
+        Mesh::from_grid(usize::MAX, 2, &[true; 2]),
+        Err(MeshError::InvalidMesh)
+    ));
+    assert!(matches!(
+        Mesh::from_grid(i32::MAX as usize + 1, 0, &[]),
+        Err(MeshError::InvalidMesh)
+    ));
+    assert!(matches!(
+        Mesh::from_grid(1 << 16, 1 << 16, &[]),
+        Err(MeshError::InvalidMesh)
+    ));
+    // The header is checked before reading the rows
+    assert!(matches!(
+        Mesh::from_moving_ai_map_bytes(b"type octile\nheight 65536\nwidth 65536\nmap\n"),
+        Err(PolyanyaFileError::UnexpectedToken { line: 4, .. })
+    ));
+    assert!(matches!(
+        Mesh::from_moving_ai_map_bytes(
+            b"type octile\nheight 18446744073709551615\nwidth 2\nmap\n..\n"
+        ),
+        Err(PolyanyaFileError::UnexpectedToken { line: 4, .. })
+    ));
+}
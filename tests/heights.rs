@@ -0,0 +1,147 @@
+use glam::Vec2;
+use polyanya::{Mesh, Trimesh};
+
+macro_rules! assert_delta {
+    ($x:expr, $y:expr) => {
+        let val = $x;
+        if (val - $y).abs() >= 0.001 || val.is_nan() {
+            panic!("assertion failed: {} != {}", val, $y);
+        }
+    };
+}
+
+/// A 3x3 grid with a hole in the middle, and a hill on the bottom side of the hole
+fn mesh_with_hill(hill: f32) -> Mesh {
+    let vertices = (0..4)
+        .flat_map(|y| (0..4).map(move |x| Vec2::new(x as f32, y as f32)))
+        .collect();
+    let mut triangles = vec![];
+    for y in 0..3 {
+        for x in 0..3 {
+            if (x, y) == (1, 1) {
+                continue;
+            }
+            let v = y * 4 + x;
+            if x < 2 {
+                triangles.push([v, v + 1, v + 5]);
+                triangles.push([v, v + 5, v + 4]);
+            } else {
+                triangles.push([v, v + 1, v + 4]);
+                triangles.push([v + 1, v + 5, v + 4]);
+            }
+        }
+    }
+    let mut mesh: Mesh = Trimesh {
+        vertices,
+        triangles,
+    }
+    .try_into()
+    .unwrap();
+    mesh.layers[0].height = vec![0.0; 16];
+    mesh.layers[0].height[5] = hill;
+    mesh.layers[0].height[6] = hill;
+    mesh.bake();
+    mesh
+}
+
+#[test]
+fn flat_mesh() {
+    let mesh = mesh_with_hill(0.0);
+    let from = Vec2::new(0.3, 1.4);
+    let to = Vec2::new(2.7, 1.4);
+
+    let path = mesh.path(from, to).unwrap();
+    let path_3d = mesh.path_with_heights(from, to, 0.0).unwrap();
+    assert_eq!(path_3d.path, path.path);
+    assert_delta!(path_3d.length, path.length);
+}
+
+#[test]
+fn go_around_hill() {
+    let mesh = mesh_with_hill(2.0);
+    let from = Vec2::new(0.3, 1.4);
+    let to = Vec2::new(2.7, 1.4);
+
+    // In 2D, going below the hole is shorter
+    let path = mesh.path(from, to).unwrap();
+    assert_eq!(
+        path.path,
+        vec![Vec2::new(1.0, 1.0), Vec2::new(2.0, 1.0), to]
+    );
+
+    // but it's over a hill
+    let path_3d = mesh.path_with_heights(from, to, 0.0).unwrap();
+    assert_eq!(
+        path_3d.path,
+        vec![Vec2::new(1.0, 2.0), Vec2::new(2.0, 2.0), to]
+    );
+    assert_delta!(path_3d.length, Vec2::new(0.7, 0.6).length() * 2.0 + 1.0);
+}
+
+#[test]
+fn slope_penalty() {
+    let mesh = mesh_with_hill(0.1);
+    let from = Vec2::new(0.3, 1.4);
+    let to = Vec2::new(2.7, 1.4);
+
+    // A small hill is not enough to go around
+    let path = mesh.path_with_heights(from, to, 0.0).unwrap();
+    assert_eq!(
+        path.path,
+        vec![Vec2::new(1.0, 1.0), Vec2::new(2.0, 1.0), to]
+    );
+
+    // unless climbing is expensive
+    let path = mesh.path_with_heights(from, to, 10.0).unwrap();
+    assert_eq!(
+        path.path,
+        vec![Vec2::new(1.0, 2.0), Vec2::new(2.0, 2.0), to]
+    );
+}
+
+#[test]
+fn without_heights() {
+    let mesh: Mesh = polyanya::PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap();
+    for (from, to) in [
+        (Vec2::new(3.0, 10.0), Vec2::new(45.0, 40.0)),
+        (Vec2::new(1.0, 10.0), Vec2::new(19.0, 18.0)),
+        (Vec2::new(30.0, 1.0), Vec2::new(35.0, 1.0)),
+    ] {
+        let path = mesh.path(from, to).unwrap();
+        let path_3d = mesh.path_with_heights(from, to, 1.0).unwrap();
+        assert_eq!(path_3d.path, path.path);
+        assert_delta!(path_3d.length, path.length);
+    }
+}
+
+#[test]
+fn max_slope() {
+    let mut mesh = mesh_with_hill(0.1);
+    let from = Vec2::new(0.3, 1.4);
+    let to = Vec2::new(2.7, 1.4);
+
+    // The hill is not too steep
+    let path = mesh.path_with_max_slope(from, to, 10.0_f32.to_radians());
+    assert_eq!(path, mesh.path(from, to));
+
+    // unless the limit is lower than its slope
+    for _ in 0..2 {
+        let path = mesh
+            .path_with_max_slope(from, to, 5.0_f32.to_radians())
+            .unwrap();
+        assert_eq!(
+            path.path,
+            vec![Vec2::new(1.0, 2.0), Vec2::new(2.0, 2.0), to]
+        );
+        // same result without pre-computed slopes
+        mesh.unbake();
+    }
+
+    // No path when starting on a polygon that is too steep
+    assert_eq!(
+        mesh.path_with_max_slope(Vec2::new(1.5, 0.9), to, 5.0_f32.to_radians()),
+        None
+    );
+}
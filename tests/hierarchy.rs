@@ -0,0 +1,124 @@
+use std::io::{BufRead, BufReader};
+
+use glam::{vec2, Vec2};
+use polyanya::{Mesh, PolyanyaFile, Triangulation};
+
+fn arena_mesh() -> Mesh {
+    PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap()
+}
+
+fn arena_scenarios() -> Vec<(Vec2, Vec2)> {
+    let file = std::fs::File::open("scenarios/arena.scen").unwrap();
+    BufReader::new(file)
+        .lines()
+        .skip(1)
+        .map_while(Result::ok)
+        .map(|line| {
+            let values = line
+                .split('\t')
+                .skip(4)
+                .take(4)
+                .map(|v| v.parse::<f32>().unwrap())
+                .collect::<Vec<_>>();
+            (
+                Vec2::new(values[0], values[1]),
+                Vec2::new(values[2], values[3]),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn hierarchy_regions() {
+    let mut mesh = arena_mesh();
+    assert!(mesh.hierarchy().is_none());
+
+    mesh.bake_hierarchy(8);
+    let hierarchy = mesh.hierarchy().unwrap();
+    let polygons = mesh.layers[0].polygons.len();
+    assert!(hierarchy.regions_count() >= polygons / 8);
+    assert!(hierarchy.portals_count() > 0);
+    for polygon in 0..polygons as u32 {
+        assert!(hierarchy.region(0, polygon).is_some());
+    }
+
+    mesh.unbake();
+    assert!(mesh.hierarchy().is_none());
+}
+
+#[test]
+fn hierarchical_path_arena() {
+    let mut mesh = arena_mesh();
+    mesh.bake_hierarchy(8);
+
+    for (from, to) in arena_scenarios() {
+        let path = mesh.path(from, to).unwrap();
+        let hierarchical = mesh.hierarchical_path(from, to).unwrap();
+        assert_eq!(*hierarchical.path.last().unwrap(), to);
+        // Restricting the search to a corridor can't find a shorter path
+        assert!(hierarchical.length >= path.length - 0.001);
+        // but shouldn't go much out of its way either
+        assert!(hierarchical.length <= path.length * 1.2 + 0.001);
+    }
+}
+
+#[test]
+fn hierarchical_path_without_hierarchy() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(1.0, 11.0);
+    let to = Vec2::new(8.0, 11.0);
+    assert_eq!(mesh.hierarchical_path(from, to), mesh.path(from, to));
+}
+
+#[test]
+fn hierarchical_path_one_way_stitches() {
+    // A U shaped layer, with a bridge on another layer between the top of its arms that can only
+    // be taken from left to right
+    let u = Triangulation::from_outer_edges(&[
+        vec2(0.0, 0.0),
+        vec2(7.0, 0.0),
+        vec2(7.0, 8.0),
+        vec2(6.0, 8.0),
+        vec2(6.0, 7.0),
+        vec2(6.0, 1.0),
+        vec2(1.0, 1.0),
+        vec2(1.0, 7.0),
+        vec2(1.0, 8.0),
+        vec2(0.0, 8.0),
+    ]);
+    let bridge = Triangulation::from_outer_edges(&[
+        vec2(1.0, 7.0),
+        vec2(6.0, 7.0),
+        vec2(6.0, 8.0),
+        vec2(1.0, 8.0),
+    ]);
+    let mut mesh = Mesh::default();
+    mesh.layers = vec![
+        u.as_navmesh().layers.remove(0),
+        bridge.as_navmesh().layers.remove(0),
+    ];
+    mesh.bake();
+    mesh.stitch_at_points(
+        vec![
+            ((0, 1), vec![vec2(1.0, 7.0), vec2(1.0, 8.0)]),
+            ((1, 0), vec![vec2(6.0, 7.0), vec2(6.0, 8.0)]),
+        ],
+        true,
+    );
+    mesh.bake_hierarchy(2);
+
+    for (from, to) in [
+        (vec2(0.5, 7.5), vec2(6.5, 7.5)),
+        (vec2(6.5, 7.5), vec2(0.5, 7.5)),
+        (vec2(0.5, 2.5), vec2(6.5, 6.5)),
+        (vec2(6.5, 6.5), vec2(0.5, 2.5)),
+    ] {
+        let path = mesh.path(from, to).unwrap();
+        let hierarchical = mesh.hierarchical_path(from, to).unwrap();
+        assert_eq!(*hierarchical.path.last().unwrap(), to);
+        assert!(hierarchical.length >= path.length - 0.001);
+        assert!(hierarchical.length <= path.length * 1.2 + 0.001);
+    }
+}
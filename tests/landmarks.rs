@@ -0,0 +1,161 @@
+use std::io::{BufRead, BufReader};
+
+use glam::{vec2, Vec2};
+use polyanya::{Mesh, PolyanyaFile, Triangulation};
+
+fn arena_mesh() -> Mesh {
+    PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap()
+}
+
+fn arena_scenarios() -> Vec<(Vec2, Vec2)> {
+    let file = std::fs::File::open("scenarios/arena.scen").unwrap();
+    BufReader::new(file)
+        .lines()
+        .skip(1)
+        .map_while(Result::ok)
+        .map(|line| {
+            let values = line
+                .split('\t')
+                .skip(4)
+                .take(4)
+                .map(|v| v.parse::<f32>().unwrap())
+                .collect::<Vec<_>>();
+            (
+                Vec2::new(values[0], values[1]),
+                Vec2::new(values[2], values[3]),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn landmarks_distances() {
+    let mut mesh = arena_mesh();
+    assert!(mesh.landmarks().is_none());
+
+    mesh.bake_landmarks(4);
+    let landmarks = mesh.landmarks().unwrap();
+    assert_eq!(landmarks.count(), 4);
+
+    for landmark in 0..landmarks.count() {
+        let (layer, vertex) = landmarks.landmark(landmark).unwrap();
+        assert_eq!(landmarks.distance(landmark, layer, vertex), Some(0.0));
+        let from = mesh.layers[layer as usize].vertices[vertex as usize].coords;
+        for (index, to) in mesh.layers[0].vertices.iter().enumerate() {
+            let Some(distance) = landmarks.distance(landmark, 0, index as u32) else {
+                continue;
+            };
+            let path = mesh.path(from, to.coords).unwrap();
+            assert!(
+                (path.length - distance).abs() < 0.01,
+                "{from} -> {}: {} != {distance}",
+                to.coords,
+                path.length
+            );
+        }
+    }
+
+    mesh.unbake();
+    assert!(mesh.landmarks().is_none());
+}
+
+#[test]
+fn landmarks_keep_paths_optimal() {
+    let mesh = arena_mesh();
+    let mut with_landmarks = mesh.clone();
+    with_landmarks.bake_landmarks(4);
+
+    for (from, to) in arena_scenarios() {
+        let path = mesh.path(from, to).unwrap();
+        let with_landmarks = with_landmarks.path(from, to).unwrap();
+        assert!(
+            (path.length - with_landmarks.length).abs() < 0.001,
+            "{from} -> {to}: {} != {}",
+            path.length,
+            with_landmarks.length
+        );
+        assert_eq!(path.path.last(), with_landmarks.path.last());
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn landmarks_serde() {
+    let mut mesh = arena_mesh();
+    mesh.bake_landmarks(2);
+
+    let serialized = serde_json::to_string(&mesh).unwrap();
+    let deserialized: Mesh = serde_json::from_str(&serialized).unwrap();
+    let landmarks = deserialized.landmarks().unwrap();
+    assert_eq!(landmarks.count(), 2);
+    assert_eq!(landmarks.landmark(1), mesh.landmarks().unwrap().landmark(1));
+}
+
+/// A U shaped layer, with a bridge on another layer between the top of its arms that can only be
+/// taken from left to right.
+fn one_way_bridge_mesh() -> Mesh {
+    let u = Triangulation::from_outer_edges(&[
+        vec2(0.0, 0.0),
+        vec2(7.0, 0.0),
+        vec2(7.0, 8.0),
+        vec2(6.0, 8.0),
+        vec2(6.0, 7.0),
+        vec2(6.0, 1.0),
+        vec2(1.0, 1.0),
+        vec2(1.0, 7.0),
+        vec2(1.0, 8.0),
+        vec2(0.0, 8.0),
+    ]);
+    let bridge = Triangulation::from_outer_edges(&[
+        vec2(1.0, 7.0),
+        vec2(6.0, 7.0),
+        vec2(6.0, 8.0),
+        vec2(1.0, 8.0),
+    ]);
+    let mut mesh = Mesh::default();
+    mesh.layers = vec![
+        u.as_navmesh().layers.remove(0),
+        bridge.as_navmesh().layers.remove(0),
+    ];
+    mesh.bake();
+    mesh.stitch_at_points(
+        vec![
+            ((0, 1), vec![vec2(1.0, 7.0), vec2(1.0, 8.0)]),
+            ((1, 0), vec![vec2(6.0, 7.0), vec2(6.0, 8.0)]),
+        ],
+        true,
+    );
+    mesh
+}
+
+#[test]
+fn landmarks_one_way_stitches() {
+    let mesh = one_way_bridge_mesh();
+    assert_eq!(
+        mesh.path(vec2(0.5, 7.5), vec2(6.5, 7.5)).unwrap().length,
+        6.0
+    );
+    assert!(mesh.path(vec2(6.5, 7.5), vec2(0.5, 7.5)).unwrap().length > 15.0);
+
+    let mut with_landmarks = mesh.clone();
+    with_landmarks.bake_landmarks(2);
+
+    let points = (0..20)
+        .flat_map(|i| (0..20).map(move |j| vec2(i as f32 * 0.35 + 0.1, j as f32 * 0.4 + 0.1)))
+        .filter(|point| mesh.point_in_mesh(*point))
+        .collect::<Vec<_>>();
+    for from in &points {
+        for to in &points {
+            let path = mesh.path(*from, *to).unwrap();
+            let with_landmarks = with_landmarks.path(*from, *to).unwrap();
+            assert!(
+                (path.length - with_landmarks.length).abs() < 0.001,
+                "{from} -> {to}: {} != {}",
+                path.length,
+                with_landmarks.length
+            );
+        }
+    }
+}
@@ -12,7 +12,7 @@ struct Scenario {
 macro_rules! assert_delta {
     ($x:expr, $y:expr) => {
         let val = $x.unwrap().length;
-        if !((val - $y).abs() < 0.0001) {
+        if (val - $y).abs() >= 0.0001 || val.is_nan() {
             assert_eq!(val, $y);
         }
     };
@@ -31,7 +31,7 @@ fn load_v3_scenario(path: &str) -> Vec<Scenario> {
         panic!("bad file, version header does not match 'version 1'")
     }
     lines
-        .filter_map(|line| line.ok())
+        .map_while(Result::ok)
         .map(|line| {
             let mut values = line.split_whitespace().skip(4);
             let start = Vec2::new(
@@ -70,3 +70,63 @@ fn v3_scenario() {
         assert_delta!(mesh.path(scenario.start, scenario.goal), scenario.cost);
     }
 }
+
+/// Read a v3 file, write it back in the v3 format, and read it again.
+fn v3_round_trip(path: &str) -> (PolyanyaFile, PolyanyaFile) {
+    let file = PolyanyaFile::from_file(path);
+    let written = PolyanyaFile::from_bytes(&file.to_bytes_v3());
+    (file, written)
+}
+
+#[test]
+fn write_v3() {
+    let (file, written) = v3_round_trip("meshes/v3/cube.mesh");
+    assert_eq!(
+        String::from_utf8(file.to_bytes_v3()).unwrap(),
+        "mesh
+3
+5 4
+0 0
+0 10
+10 10
+10 0
+5 5
+1 3 1 2 5 -4 0 2
+1 3 2 3 5 1 0 3
+1 3 3 4 5 2 0 -4
+0 3 4 1 5 -3 0 -1
+"
+    );
+    assert_eq!(written.polygons, file.polygons);
+    assert_eq!(written.obstacles(), file.obstacles());
+}
+
+#[test]
+fn v3_round_trip_scenario() {
+    let path = "meshes/v3/scene_mp_2p_01.mesh";
+    let (file, written) = v3_round_trip(path);
+    assert_eq!(written.polygons, file.polygons);
+    assert_eq!(written.obstacles(), file.obstacles());
+    for (vertex, other) in file.vertices.iter().zip(&written.vertices) {
+        assert_eq!(vertex.coords, other.coords);
+        assert_eq!(vertex.is_corner, other.is_corner);
+        let polygons = |vertex: &polyanya::Vertex| {
+            let mut polygons = vertex.polygons.clone();
+            polygons.sort_unstable();
+            polygons.dedup();
+            polygons
+        };
+        assert_eq!(polygons(vertex), polygons(other));
+    }
+
+    let mesh: Mesh = written.try_into().unwrap();
+    for scenario in load_v3_scenario(&format!("{path}.scen")).iter().take(20) {
+        assert_delta!(mesh.path(scenario.start, scenario.goal), scenario.cost);
+    }
+}
+
+#[test]
+fn write_to_invalid_path() {
+    let file = PolyanyaFile::from_file("meshes/v3/cube.mesh");
+    assert!(file.to_file_v3("meshes/missing/cube.mesh").is_err());
+}
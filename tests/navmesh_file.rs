@@ -0,0 +1,127 @@
+use glam::{vec2, Vec2};
+use polyanya::{Layer, Mesh, MeshError, PolyanyaFile, PolyanyaFileError, Polygon, Vertex};
+
+fn square_layer(offset: Vec2) -> Layer {
+    let mut layer = Layer::new(
+        vec![
+            Vertex::new(vec2(0., 0.), vec![0, u32::MAX]),
+            Vertex::new(vec2(1., 0.), vec![0, u32::MAX]),
+            Vertex::new(vec2(0., 1.), vec![0, u32::MAX]),
+            Vertex::new(vec2(1., 1.), vec![0, u32::MAX]),
+        ],
+        vec![Polygon::new(vec![0, 1, 3, 2], false)],
+    )
+    .unwrap();
+    layer.offset = offset;
+    layer
+}
+
+fn stitched_mesh() -> Mesh {
+    let mut mesh = Mesh::default();
+    mesh.layers = vec![square_layer(Vec2::ZERO), square_layer(vec2(1.0, 0.0))];
+    mesh.layers[1].height = vec![0.0, 0.0, 1.0, 1.0];
+    mesh.set_search_delta(0.25).set_search_steps(3);
+    mesh.bake();
+    mesh.stitch_at_vertices(vec![((0, 1), vec![(1, 0), (3, 2)])], false);
+    mesh
+}
+
+fn assert_same_mesh(a: &Mesh, b: &Mesh) {
+    assert_eq!(a.layers.len(), b.layers.len());
+    for (a, b) in a.layers.iter().zip(&b.layers) {
+        assert_eq!(a.vertices, b.vertices);
+        assert_eq!(a.polygons, b.polygons);
+        assert_eq!(a.offset, b.offset);
+        assert_eq!(a.height, b.height);
+    }
+    assert_eq!(a.search_delta(), b.search_delta());
+    assert_eq!(a.search_steps(), b.search_steps());
+}
+
+#[test]
+fn round_trip_layers() {
+    let mesh = stitched_mesh();
+    let read = Mesh::from_navmesh_bytes(&mesh.to_navmesh_bytes()).unwrap();
+    assert_same_mesh(&mesh, &read);
+
+    assert_eq!(
+        read.path(vec2(0.5, 0.5), vec2(1.5, 0.5)),
+        mesh.path(vec2(0.5, 0.5), vec2(1.5, 0.5))
+    );
+    assert!(read.path(vec2(0.5, 0.5), vec2(1.5, 0.5)).is_some());
+}
+
+#[test]
+fn round_trip_arena() {
+    let mesh: Mesh = PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap();
+    let bytes = mesh.to_navmesh_bytes();
+    let read = Mesh::from_navmesh_reader(bytes.as_slice()).unwrap();
+    assert_same_mesh(&mesh, &read);
+    assert_eq!(read.to_navmesh_bytes(), bytes);
+    assert_eq!(
+        read.path(vec2(3.0, 10.0), vec2(45.0, 40.0)),
+        mesh.path(vec2(3.0, 10.0), vec2(45.0, 40.0))
+    );
+}
+
+#[test]
+fn skip_unknown_sections() {
+    let content = "navmesh
+1
+settings 2
+search_delta 0.5
+future_setting 12
+layer 2
+offset 0 0
+future_property a b c
+future_section 2
+some data
+more data
+vertices 3
+0 0 2 0 -1
+1 0 2 0 -1
+0 1 2 0 -1
+polygons 1
+3 0 1 2 0
+";
+    let mesh = Mesh::from_navmesh_bytes(content.as_bytes()).unwrap();
+    assert_eq!(mesh.search_delta(), 0.5);
+    assert_eq!(mesh.layers.len(), 1);
+    assert_eq!(mesh.layers[0].polygons.len(), 1);
+    assert!(mesh.point_in_mesh(vec2(0.2, 0.2)));
+}
+
+#[test]
+fn invalid_files() {
+    assert!(matches!(
+        Mesh::from_navmesh_bytes(b"navmesh\n2\n"),
+        Err(PolyanyaFileError::UnexpectedToken { line: 2, .. })
+    ));
+    assert!(matches!(
+        Mesh::from_navmesh_bytes(b"navmesh\n1\nvertices 1\n0 0 1 0\n"),
+        Err(PolyanyaFileError::UnexpectedToken {
+            line: 3,
+            expected: "a `layer` section first",
+            ..
+        })
+    ));
+    assert!(matches!(
+        Mesh::from_navmesh_bytes(
+            b"navmesh\n1\nlayer 0\nvertices 1\n0 0 1 2:0\npolygons 1\n1 0 0\n"
+        ),
+        Err(PolyanyaFileError::Mesh(MeshError::InvalidMesh))
+    ));
+    // A polygon needs at least three vertices
+    assert!(matches!(
+        Mesh::from_navmesh_bytes(
+            b"navmesh\n1\nlayer 0\nvertices 2\n0 0 1 0\n1 0 1 0\npolygons 1\n2 0 1 0\n"
+        ),
+        Err(PolyanyaFileError::Mesh(MeshError::InvalidMesh))
+    ));
+    assert!(matches!(
+        Mesh::from_navmesh_bytes(b"navmesh\n1\n"),
+        Err(PolyanyaFileError::Mesh(MeshError::EmptyMesh))
+    ));
+}
@@ -0,0 +1,139 @@
+use glam::{vec2, Vec2};
+use polyanya::{Layer, Mesh, ObjFileError, PolyanyaFile, PolyanyaFileError, Polygon, Vertex};
+
+fn square_layer(offset: Vec2) -> Layer {
+    let mut layer = Layer::new(
+        vec![
+            Vertex::new(vec2(0., 0.), vec![0, u32::MAX]),
+            Vertex::new(vec2(1., 0.), vec![0, u32::MAX]),
+            Vertex::new(vec2(0., 1.), vec![0, u32::MAX]),
+            Vertex::new(vec2(1., 1.), vec![0, u32::MAX]),
+        ],
+        vec![Polygon::new(vec![0, 1, 3, 2], true)],
+    )
+    .unwrap();
+    layer.offset = offset;
+    layer
+}
+
+#[test]
+fn write_layers() {
+    let mut mesh = Mesh::default();
+    mesh.layers = vec![square_layer(Vec2::ZERO), square_layer(vec2(1.0, 0.0))];
+    mesh.layers[1].height = vec![0.0, 0.0, 1.0, 1.0];
+    assert_eq!(
+        String::from_utf8(mesh.to_obj_bytes()).unwrap(),
+        "o layer_0
+v 0 0 0
+v 1 0 0
+v 0 0 -1
+v 1 0 -1
+f 1 2 4 3
+o layer_1
+v 1 0 0
+v 2 0 0
+v 1 1 -1
+v 2 1 -1
+f 5 6 8 7
+"
+    );
+
+    let read = Mesh::from_obj_bytes(&mesh.to_obj_bytes()).unwrap();
+    assert_eq!(read.layers.len(), 2);
+    assert_eq!(read.layers[0].vertices, mesh.layers[0].vertices);
+    assert_eq!(read.layers[0].polygons, mesh.layers[0].polygons);
+    assert!(read.layers[0].height.is_empty());
+    assert_eq!(read.layers[1].height, mesh.layers[1].height);
+    assert_eq!(read.layers[1].vertices[0].coords, vec2(1.0, 0.0));
+}
+
+#[test]
+fn round_trip_arena() {
+    let mesh: Mesh = PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap();
+    let read = Mesh::from_obj_bytes(&mesh.to_obj_bytes()).unwrap();
+    assert_eq!(read.layers[0].polygons.len(), mesh.layers[0].polygons.len());
+    for (from, to) in [
+        (vec2(3.0, 10.0), vec2(45.0, 40.0)),
+        (vec2(10.0, 10.0), vec2(40.0, 5.0)),
+        (vec2(2.0, 45.0), vec2(40.0, 30.0)),
+    ] {
+        assert_eq!(
+            read.path(from, to).map(|path| path.path),
+            mesh.path(from, to).map(|path| path.path)
+        );
+    }
+}
+
+#[test]
+fn read_blender_model() {
+    // Two triangles and a quad, facing down, with normals and texture coordinates
+    let content = "# Blender 4.2
+mtllib floor.mtl
+o Floor
+v 0 0.5 0
+v 2 0.5 0
+v 2 0.5 -1
+v 0 0.5 -1
+v 3 0.5 0
+v 3 0.5 -1
+vn 0 -1 0
+vt 0 0
+s 0
+usemtl floor
+f 1/1/1 4/1/1 3/1/1
+f 1//1 3//1 2//1
+f -4 -1 -2 -5
+";
+    let mesh = Mesh::from_obj_bytes(content.as_bytes()).unwrap();
+    let layer = &mesh.layers[0];
+    assert_eq!(layer.polygons.len(), 3);
+    assert_eq!(layer.height, vec![0.5; 6]);
+    assert_eq!(layer.vertices[2].coords, vec2(2.0, 1.0));
+    // Vertex 2 is on the border between two faces
+    assert_eq!(layer.vertices[1].polygons.len(), 3);
+    // Vertex 3 is shared by the three faces, with an obstacle above it
+    assert!(layer.vertices[2].polygons.contains(&u32::MAX));
+    assert_eq!(layer.vertices[2].polygons.len(), 4);
+    assert!(layer.polygons[0].is_one_way);
+    assert!(!layer.polygons[1].is_one_way);
+
+    let path = mesh.path(vec2(0.2, 0.8), vec2(2.8, 0.2)).unwrap();
+    assert_eq!(path.path, vec![vec2(2.8, 0.2)]);
+}
+
+#[test]
+fn invalid_faces() {
+    let vertices = "v 0 0 0\nv 2 0 0\nv 2 0 -2\nv 1 0 -0.5\nv 0 0 -2\nv 1 0 2\n";
+    assert!(matches!(
+        Mesh::from_obj_bytes(format!("{vertices}f 1 2 3 4 5\n").as_bytes()),
+        Err(ObjFileError::NonConvexFace { line: 7 })
+    ));
+    assert!(matches!(
+        Mesh::from_obj_bytes(format!("{vertices}f 1 2 2 1\n").as_bytes()),
+        Err(ObjFileError::DegenerateFace { line: 7 })
+    ));
+    assert!(matches!(
+        Mesh::from_obj_bytes(format!("{vertices}f 1 2 3\nf 1 2 5\n").as_bytes()),
+        Err(ObjFileError::NonManifoldEdge {
+            line: 8,
+            edge: (1, 2)
+        })
+    ));
+    assert!(matches!(
+        Mesh::from_obj_bytes(format!("{vertices}f 1 2 3\nf 1 2 6\nf 2 1 6\n").as_bytes()),
+        Err(ObjFileError::NonManifoldEdge { line: 9, .. })
+    ));
+    assert!(matches!(
+        Mesh::from_obj_bytes(format!("{vertices}f 1 2 7\n").as_bytes()),
+        Err(ObjFileError::Parse(PolyanyaFileError::UnexpectedToken {
+            line: 7,
+            ..
+        }))
+    ));
+    assert!(matches!(
+        Mesh::from_obj_bytes(vertices.as_bytes()),
+        Err(ObjFileError::Mesh(_))
+    ));
+}
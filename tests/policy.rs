@@ -0,0 +1,115 @@
+use std::collections::HashSet;
+
+use glam::Vec2;
+use polyanya::{Mesh, Path, PolyanyaFile, SearchPolicy, ShortestPath};
+
+fn arena_mesh() -> Mesh {
+    PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap()
+}
+
+/// Polygons that cost more to go through
+struct Weighted {
+    expensive: HashSet<(u8, u32)>,
+    weight: f32,
+}
+
+impl SearchPolicy for Weighted {
+    fn segment_cost(&self, from: Vec2, to: Vec2, layer: u8, polygon: u32) -> f32 {
+        if self.expensive.contains(&(layer, polygon)) {
+            from.distance(to) * self.weight
+        } else {
+            from.distance(to)
+        }
+    }
+}
+
+/// Reject paths turning at a corner
+struct AvoidCorner(Vec2);
+
+impl SearchPolicy for AvoidCorner {
+    fn is_goal(&self, path: &Path) -> bool {
+        !path.path.contains(&self.0)
+    }
+}
+
+#[test]
+fn shortest_path_policy() {
+    let mesh = arena_mesh();
+    for (from, to) in [
+        (Vec2::new(3.0, 10.0), Vec2::new(45.0, 40.0)),
+        (Vec2::new(1.0, 10.0), Vec2::new(19.0, 18.0)),
+        (Vec2::new(30.0, 1.0), Vec2::new(35.0, 1.0)),
+    ] {
+        let path = mesh.path(from, to).unwrap();
+        let with_policy = mesh.path_with_policy(from, to, &ShortestPath).unwrap();
+        assert_eq!(with_policy.path, path.path);
+        assert!((with_policy.length - path.length).abs() < 0.001);
+    }
+}
+
+#[test]
+fn weighted_polygons() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let to = Vec2::new(45.0, 40.0);
+
+    let path = mesh.path(from, to).unwrap();
+    assert_eq!(
+        path.path,
+        vec![Vec2::new(15.0, 19.0), Vec2::new(35.0, 31.0), to]
+    );
+    let polygons = path.polygons();
+    let expensive: HashSet<_> = polygons[1..polygons.len() - 1].iter().copied().collect();
+
+    let same = mesh
+        .path_with_policy(
+            from,
+            to,
+            &Weighted {
+                expensive: expensive.clone(),
+                weight: 1.0,
+            },
+        )
+        .unwrap();
+    assert_eq!(same.path, path.path);
+
+    let around = mesh
+        .path_with_policy(
+            from,
+            to,
+            &Weighted {
+                expensive: expensive.clone(),
+                weight: 10.0,
+            },
+        )
+        .unwrap();
+    assert_eq!(around.path, vec![Vec2::new(15.0, 35.0), to]);
+    assert!(around.length > path.length);
+    assert!(
+        around
+            .polygons()
+            .iter()
+            .filter(|polygon| expensive.contains(polygon))
+            .count()
+            < expensive.len() / 2
+    );
+}
+
+#[test]
+fn goal_test() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let to = Vec2::new(45.0, 40.0);
+
+    let path = mesh
+        .path_with_policy(from, to, &AvoidCorner(Vec2::new(35.0, 31.0)))
+        .unwrap();
+    assert_eq!(path.path, vec![Vec2::new(31.0, 35.0), to]);
+
+    // A path in a single polygon can also be rejected
+    let to = Vec2::new(3.1, 10.1);
+    assert_eq!(mesh.path(from, to).unwrap().polygons().len(), 1);
+    assert_eq!(mesh.path_with_policy(from, to, &AvoidCorner(to)), None);
+}
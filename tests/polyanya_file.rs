@@ -0,0 +1,153 @@
+use polyanya::{Mesh, PolyanyaFile, PolyanyaFileError};
+
+const EXAMPLE: &str = "mesh
+2
+4 2
+0.0 0.0 2 0 -1
+1.5 0.0 3 0 1 -1
+1.5 1.5 2 1 -1
+0.0 1.5 3 -1 1 0
+3 0 1 3 -1 -1 1
+3 1 2 3 -1 0 -1
+";
+
+#[test]
+fn read_valid_files() {
+    let file = PolyanyaFile::try_from_bytes(EXAMPLE.as_bytes()).unwrap();
+    assert_eq!(file.vertices.len(), 4);
+    assert_eq!(file.polygons.len(), 2);
+
+    for path in [
+        "meshes/v2/arena.mesh",
+        "meshes/v2/arena-merged.mesh",
+        "meshes/v2/aurora.mesh",
+        "meshes/v3/cube.mesh",
+        "meshes/v3/scene_mp_2p_01.mesh",
+    ] {
+        let file = PolyanyaFile::try_from_file(path).unwrap();
+        let reader = PolyanyaFile::try_from_reader(std::fs::File::open(path).unwrap()).unwrap();
+        assert_eq!(file.vertices, reader.vertices);
+        assert_eq!(file.polygons, reader.polygons);
+        let _: Mesh = file.try_into().unwrap();
+    }
+}
+
+#[test]
+fn round_trip() {
+    let file = PolyanyaFile::try_from_bytes(EXAMPLE.as_bytes()).unwrap();
+    let read = PolyanyaFile::try_from_bytes(&file.to_bytes()).unwrap();
+    assert_eq!(file.vertices, read.vertices);
+}
+
+fn error(content: &str) -> PolyanyaFileError {
+    PolyanyaFile::try_from_bytes(content.as_bytes()).unwrap_err()
+}
+
+#[test]
+fn invalid_header() {
+    assert!(matches!(
+        error("mash\n2\n"),
+        PolyanyaFileError::UnexpectedToken {
+            line: 1,
+            expected: "the header `mesh`",
+            ..
+        }
+    ));
+    assert!(matches!(
+        error("mesh\n4\n"),
+        PolyanyaFileError::UnexpectedToken { line: 2, token, .. } if token == "4"
+    ));
+    assert!(matches!(
+        error(""),
+        PolyanyaFileError::UnexpectedEnd { line: 0, .. }
+    ));
+}
+
+#[test]
+fn invalid_token() {
+    let content = EXAMPLE.replace("1.5 1.5 2", "1.5 oops 2");
+    let error = error(&content);
+    assert_eq!(
+        error.to_string(),
+        "Line 6: expected the y coordinate of a vertex, found `oops`"
+    );
+    assert!(matches!(
+        error,
+        PolyanyaFileError::UnexpectedToken {
+            line: 6,
+            token,
+            expected: "the y coordinate of a vertex",
+        } if token == "oops"
+    ));
+}
+
+#[test]
+fn missing_values() {
+    let content = EXAMPLE.replace("3 1 2 3 -1 0 -1", "3 1 2");
+    assert!(matches!(
+        error(&content),
+        PolyanyaFileError::MissingToken {
+            line: 9,
+            expected: "a vertex index",
+        }
+    ));
+
+    let content = EXAMPLE.replace("3 1 2 3 -1 0 -1\n", "");
+    assert!(matches!(
+        error(&content),
+        PolyanyaFileError::UnexpectedEnd {
+            line: 8,
+            expected: "a polygon",
+        }
+    ));
+}
+
+#[test]
+fn extra_line() {
+    let content = format!("{EXAMPLE}3 0 1 2\n");
+    assert!(matches!(
+        error(&content),
+        PolyanyaFileError::UnexpectedToken {
+            line: 10,
+            expected: "the end of the file",
+            ..
+        }
+    ));
+}
+
+#[test]
+fn missing_file() {
+    assert!(matches!(
+        PolyanyaFile::try_from_file("meshes/missing.mesh"),
+        Err(PolyanyaFileError::Io(_))
+    ));
+}
+
+#[test]
+fn invalid_counts() {
+    // Counts are not used to allocate memory before reading the vertices
+    assert!(matches!(
+        error("mesh\n2\n999999999999 1\n"),
+        PolyanyaFileError::UnexpectedEnd {
+            line: 3,
+            expected: "a vertex",
+        }
+    ));
+    assert!(matches!(
+        error("mesh\n3\n999999999999 1\n"),
+        PolyanyaFileError::UnexpectedEnd {
+            line: 3,
+            expected: "a vertex",
+        }
+    ));
+
+    let content = EXAMPLE.replace("3 1 2 3 -1 0 -1", "3 1 2 4 -1 0 -1");
+    assert!(matches!(
+        error(&content),
+        PolyanyaFileError::UnexpectedToken {
+            line: 9,
+            token,
+            expected: "a vertex index",
+        } if token == "4"
+    ));
+}
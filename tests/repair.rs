@@ -0,0 +1,123 @@
+use std::io::{BufRead, BufReader};
+
+use glam::{vec2, Vec2};
+use polyanya::{Mesh, PathError, PolyanyaFile, Triangulation};
+
+fn triangulation() -> Triangulation {
+    Triangulation::from_outer_edges(&[
+        vec2(0., 6.),
+        vec2(2., 5.),
+        vec2(2., 4.),
+        vec2(1., 4.),
+        vec2(1., 3.),
+        vec2(2., 1.),
+        vec2(4., 1.),
+        vec2(4., 2.),
+        vec2(7., 4.),
+        vec2(7., 0.),
+        vec2(12., 0.),
+        vec2(12., 3.),
+        vec2(11., 3.),
+        vec2(11., 5.),
+        vec2(13., 5.),
+        vec2(13., 7.),
+        vec2(10., 7.),
+        vec2(11., 8.),
+        vec2(7., 8.),
+        vec2(7., 7.),
+        vec2(5., 7.),
+        vec2(5., 8.),
+        vec2(0., 8.),
+    ])
+}
+
+fn with_obstacle(obstacle: [Vec2; 4]) -> Mesh {
+    let mut triangulation = triangulation();
+    triangulation.add_obstacle(obstacle);
+    triangulation.as_navmesh()
+}
+
+#[test]
+fn valid_path() {
+    let mesh = triangulation().as_navmesh();
+    let from = vec2(12.0, 0.0);
+    let to = vec2(3.0, 1.0);
+    let path = mesh.path(from, to).unwrap();
+    assert_eq!(mesh.validate_path(from, &path), Ok(()));
+
+    let repaired = mesh
+        .repair_path(from, mesh.path(from, to).unwrap())
+        .unwrap();
+    assert_eq!(repaired.path, path.path);
+    assert!((repaired.length - path.length).abs() < 0.001);
+}
+
+#[test]
+fn blocked_path() {
+    let mesh = triangulation().as_navmesh();
+    let from = vec2(12.0, 0.0);
+    let to = vec2(3.0, 1.0);
+    let path = mesh.path(from, to).unwrap();
+    assert_eq!(
+        path.path,
+        vec![vec2(7.0, 4.0), vec2(4.0, 2.0), vec2(3.0, 1.0)]
+    );
+
+    // Block the segment between the two corners
+    let edited = with_obstacle([
+        vec2(5.3, 2.6),
+        vec2(5.8, 2.6),
+        vec2(5.8, 3.4),
+        vec2(5.3, 3.4),
+    ]);
+    assert_eq!(
+        edited.validate_path(from, &path),
+        Err(PathError::BlockedSegment { segment: 1 })
+    );
+    let repaired = edited.repair_path(from, path).unwrap();
+    assert_eq!(edited.validate_path(from, &repaired), Ok(()));
+    assert_eq!(Some(repaired), edited.path(from, to));
+}
+
+#[test]
+fn keep_prefix() {
+    let mesh = triangulation().as_navmesh();
+    let from = vec2(12.0, 0.0);
+    let to = vec2(3.0, 1.0);
+    let path = mesh.path(from, to).unwrap();
+
+    // Block the last segment
+    let edited = with_obstacle([
+        vec2(3.4, 1.4),
+        vec2(3.6, 1.4),
+        vec2(3.6, 1.6),
+        vec2(3.4, 1.6),
+    ]);
+    assert_eq!(
+        edited.validate_path(from, &path),
+        Err(PathError::BlockedSegment { segment: 2 })
+    );
+    let repaired = edited.repair_path(from, path).unwrap();
+    assert_eq!(edited.validate_path(from, &repaired), Ok(()));
+    assert_eq!(repaired.path[..2], [vec2(7.0, 4.0), vec2(4.0, 2.0)]);
+    assert_eq!(repaired.path.last(), Some(&to));
+}
+
+#[test]
+fn valid_arena_paths() {
+    let mesh: Mesh = PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap();
+    let file = std::fs::File::open("scenarios/arena.scen").unwrap();
+    for line in BufReader::new(file).lines().skip(1).map_while(Result::ok) {
+        let values = line
+            .split('\t')
+            .skip(4)
+            .take(4)
+            .map(|v| v.parse::<f32>().unwrap())
+            .collect::<Vec<_>>();
+        let from = vec2(values[0], values[1]);
+        let path = mesh.path(from, vec2(values[2], values[3])).unwrap();
+        assert_eq!(mesh.validate_path(from, &path), Ok(()), "{line}");
+    }
+}
@@ -0,0 +1,89 @@
+use glam::Vec2;
+use polyanya::{Mesh, PolyanyaFile};
+
+fn arena_mesh() -> Mesh {
+    PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn point_at_distance() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let path = mesh.path(from, Vec2::new(45.0, 40.0)).unwrap();
+    assert_eq!(path.path[0], Vec2::new(15.0, 19.0));
+
+    assert_eq!(path.point_at_distance(&mesh, from, 0.0).position(), from);
+    assert_eq!(
+        path.point_at_distance(&mesh, from, 7.5).position(),
+        Vec2::new(9.0, 14.5)
+    );
+    assert_eq!(
+        path.point_at_distance(&mesh, from, 15.0).position(),
+        Vec2::new(15.0, 19.0)
+    );
+    assert_eq!(
+        path.point_at_distance(&mesh, from, 1000.0).position(),
+        Vec2::new(45.0, 40.0)
+    );
+
+    for distance in [0.0, 7.5, 20.0, 40.0, path.length] {
+        let point = path.point_at_distance(&mesh, from, distance);
+        assert!(point.layer() == Some(0));
+        assert_eq!(
+            mesh.get_closest_point(point.position()).unwrap().polygon(),
+            point.polygon()
+        );
+    }
+}
+
+#[test]
+fn direction_and_projection() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let path = mesh.path(from, Vec2::new(45.0, 40.0)).unwrap();
+
+    let directions = [
+        (0.0, Vec2::new(0.8, 0.6)),
+        (
+            15.0,
+            (Vec2::new(35.0, 31.0) - Vec2::new(15.0, 19.0)).normalize(),
+        ),
+        (
+            1000.0,
+            (Vec2::new(45.0, 40.0) - Vec2::new(35.0, 31.0)).normalize(),
+        ),
+    ];
+    for (distance, direction) in directions {
+        assert!(path
+            .direction_at(from, distance)
+            .abs_diff_eq(direction, 0.001));
+    }
+
+    assert_eq!(path.project(from, from), 0.0);
+    assert_eq!(path.project(from, Vec2::new(9.0, 14.5)), 7.5);
+    assert_eq!(path.project(from, Vec2::new(12.0, 23.0)), 15.0);
+    assert!((path.project(from, Vec2::new(50.0, 50.0)) - path.length).abs() < 0.001);
+}
+
+#[test]
+fn resample() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let path = mesh.path(from, Vec2::new(45.0, 40.0)).unwrap();
+
+    let points = path.resample(&mesh, from, 1.0);
+    assert_eq!(points.len(), path.length.ceil() as usize + 1);
+    assert_eq!(points[0].position(), from);
+    assert_eq!(points.last().unwrap().position(), Vec2::new(45.0, 40.0));
+    for pair in points.windows(2) {
+        assert!(pair[0].position().distance(pair[1].position()) <= 1.0 + 0.001);
+    }
+    for (index, point) in points.iter().enumerate().take(points.len() - 1) {
+        assert!((path.project(from, point.position()) - index as f32).abs() < 0.001);
+        assert!(path
+            .polygons()
+            .contains(&(point.layer().unwrap(), point.polygon())));
+    }
+}
@@ -0,0 +1,65 @@
+use std::io::{BufRead, BufReader};
+
+use glam::Vec2;
+use polyanya::{Mesh, PolyanyaFile};
+
+fn arena_mesh() -> Mesh {
+    PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn straight_segment() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(1.0, 10.0);
+    let path = mesh.path(from, Vec2::new(7.0, 10.0)).unwrap();
+
+    let segments = path.segments(&mesh, from).collect::<Vec<_>>();
+    assert_eq!(segments.len(), 1);
+    assert_eq!(segments[0].start.position(), from);
+    assert_eq!(segments[0].end.position(), Vec2::new(7.0, 10.0));
+    assert_eq!(segments[0].length(), 6.0);
+    assert_eq!(segments[0].polygons, path.polygons());
+    assert_eq!(segments[0].crossings.len(), path.polygons().len() - 1);
+    assert_eq!(segments[0].layer_changes().count(), 0);
+}
+
+#[test]
+fn arena_segments() {
+    let mesh = arena_mesh();
+    let file = std::fs::File::open("scenarios/arena.scen").unwrap();
+    for line in BufReader::new(file).lines().skip(1).map_while(Result::ok) {
+        let values = line
+            .split('\t')
+            .skip(4)
+            .take(4)
+            .map(|v| v.parse::<f32>().unwrap())
+            .collect::<Vec<_>>();
+        let from = Vec2::new(values[0], values[1]);
+        let to = Vec2::new(values[2], values[3]);
+        let path = mesh.path(from, to).unwrap();
+
+        let segments = path.segments(&mesh, from).collect::<Vec<_>>();
+        assert_eq!(segments.len(), path.path.len());
+        let length: f32 = segments.iter().map(|segment| segment.length()).sum();
+        assert!((length - path.length).abs() < 0.001, "{from} -> {to}");
+
+        let mut start = from;
+        let mut polygons = vec![];
+        for (segment, step) in segments.iter().zip(&path.path) {
+            assert_eq!(segment.start.position(), start);
+            assert_eq!(segment.end.position(), *step);
+            assert_eq!(segment.start.polygon(), segment.polygons.first().unwrap().1);
+            assert_eq!(segment.end.polygon(), segment.polygons.last().unwrap().1);
+            assert_eq!(segment.crossings.len(), segment.polygons.len() - 1);
+            for (crossing, pair) in segment.crossings.iter().zip(segment.polygons.windows(2)) {
+                assert_eq!((crossing.from, crossing.to), (pair[0], pair[1]));
+            }
+            polygons.extend(segment.polygons.iter().copied());
+            start = *step;
+        }
+        polygons.dedup();
+        assert_eq!(polygons, path.polygons(), "{from} -> {to}");
+    }
+}
@@ -0,0 +1,105 @@
+use std::io::{BufRead, BufReader};
+
+use glam::Vec2;
+use polyanya::{Mesh, PolyanyaFile};
+
+fn arena_mesh() -> Mesh {
+    PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap()
+}
+
+#[test]
+fn straight_path() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(1.0, 10.0);
+    let path = mesh.path(from, Vec2::new(7.0, 10.0)).unwrap();
+
+    let smoothed = path.smooth(&mesh, from, 1.0, 5.0);
+    assert_eq!(smoothed.len(), 7);
+    for (index, point) in smoothed.iter().enumerate() {
+        assert!(point.abs_diff_eq(Vec2::new(1.0 + index as f32, 10.0), 0.001));
+    }
+}
+
+#[test]
+fn curved_corners() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let path = mesh.path(from, Vec2::new(45.0, 40.0)).unwrap();
+
+    let smoothed = path.smooth(&mesh, from, 0.5, f32::INFINITY);
+    assert_eq!(smoothed.first(), Some(&from));
+    assert_eq!(smoothed.last(), Some(&Vec2::new(45.0, 40.0)));
+    for step in &path.path {
+        assert!(smoothed.contains(step));
+    }
+    // The curve leaves the straight segments
+    let mut steps = std::iter::once(from).chain(path.path.iter().copied());
+    let first = (steps.next().unwrap(), steps.next().unwrap());
+    assert!(smoothed
+        .iter()
+        .take_while(|point| **point != first.1)
+        .any(|point| (first.1 - first.0).perp_dot(*point - first.0).abs() > 0.1));
+}
+
+#[test]
+fn stays_on_mesh() {
+    let mesh = arena_mesh();
+    let file = std::fs::File::open("scenarios/arena.scen").unwrap();
+    for line in BufReader::new(file).lines().skip(1).map_while(Result::ok) {
+        let values = line
+            .split('\t')
+            .skip(4)
+            .take(4)
+            .map(|v| v.parse::<f32>().unwrap())
+            .collect::<Vec<_>>();
+        let from = Vec2::new(values[0], values[1]);
+        let to = Vec2::new(values[2], values[3]);
+        let path = mesh.path(from, to).unwrap();
+
+        let smoothed = path.smooth(&mesh, from, 0.25, 10.0);
+        assert_eq!(smoothed.last(), Some(&to));
+        // Each chord of the curve is a straight line in the mesh
+        for chord in smoothed.windows(2) {
+            let straight = mesh.path(chord[0], chord[1]).unwrap();
+            assert!(
+                straight.length <= chord[0].distance(chord[1]) + 0.001,
+                "{from} -> {to}: {} -> {}",
+                chord[0],
+                chord[1]
+            );
+        }
+    }
+}
+
+#[test]
+fn max_radius() {
+    let mesh = arena_mesh();
+    let from = Vec2::new(3.0, 10.0);
+    let path = mesh.path(from, Vec2::new(45.0, 40.0)).unwrap();
+    let steps: Vec<Vec2> = std::iter::once(from)
+        .chain(path.path.iter().copied())
+        .collect();
+    let distance_to_path = |point: Vec2| {
+        steps
+            .windows(2)
+            .map(|pair| {
+                let segment = pair[1] - pair[0];
+                let t = ((point - pair[0]).dot(segment) / segment.length_squared()).clamp(0.0, 1.0);
+                point.distance(pair[0] + segment * t)
+            })
+            .fold(f32::INFINITY, f32::min)
+    };
+
+    let deviation = |max_radius: f32| {
+        path.smooth(&mesh, from, 0.25, max_radius)
+            .iter()
+            .map(|point| distance_to_path(*point))
+            .fold(0.0, f32::max)
+    };
+    let (wide, narrow) = (deviation(f32::INFINITY), deviation(1.0));
+    assert!(wide > 0.1);
+    assert!(narrow > 0.0);
+    assert!(narrow < wide.min(1.0 / 3.0));
+}
@@ -0,0 +1,81 @@
+use glam::Vec2;
+use polyanya::{Coords, Mesh, PolyanyaFile, WaypointError};
+
+fn arena_mesh() -> Mesh {
+    PolyanyaFile::from_file("meshes/v2/arena.mesh")
+        .try_into()
+        .unwrap()
+}
+
+fn waypoints(points: &[Vec2]) -> Vec<Coords> {
+    points.iter().map(|point| Coords::on_mesh(*point)).collect()
+}
+
+#[test]
+fn straight_through_waypoint() {
+    let mesh = arena_mesh();
+    let through = mesh
+        .path_through(&waypoints(&[
+            Vec2::new(1.0, 10.0),
+            Vec2::new(4.0, 10.0),
+            Vec2::new(7.0, 10.0),
+        ]))
+        .unwrap();
+    assert_eq!(through.legs, vec![3.0, 3.0]);
+    assert_eq!(through.path.length, 6.0);
+    assert_eq!(through.path.path, vec![Vec2::new(7.0, 10.0)]);
+    assert_eq!(
+        through.path.polygons(),
+        mesh.path(Vec2::new(1.0, 10.0), Vec2::new(7.0, 10.0))
+            .unwrap()
+            .polygons()
+    );
+}
+
+#[test]
+fn turn_at_waypoints() {
+    let mesh = arena_mesh();
+    let points = [
+        Vec2::new(3.0, 10.0),
+        Vec2::new(19.0, 18.5),
+        Vec2::new(45.0, 40.0),
+        Vec2::new(3.0, 11.0),
+    ];
+    let through = mesh.path_through(&waypoints(&points)).unwrap();
+
+    let mut expected = vec![];
+    let mut length = 0.0;
+    for (leg, pair) in points.windows(2).enumerate() {
+        let path = mesh.path(pair[0], pair[1]).unwrap();
+        assert_eq!(through.legs[leg], path.length);
+        length += path.length;
+        expected.extend(path.path);
+    }
+    assert_eq!(through.legs.len(), 3);
+    assert!((through.path.length - length).abs() < 0.001);
+    assert_eq!(through.path.path, expected);
+    assert_eq!(through.path.path.last(), points.last());
+}
+
+#[test]
+fn unreachable_leg() {
+    let mesh = arena_mesh();
+    let error = mesh
+        .path_through(&waypoints(&[
+            Vec2::new(3.0, 10.0),
+            Vec2::new(45.0, 40.0),
+            Vec2::new(0.0, 0.0),
+            Vec2::new(3.0, 11.0),
+        ]))
+        .unwrap_err();
+    assert_eq!(error, WaypointError::UnreachableLeg { leg: 1 });
+    assert_eq!(
+        error.to_string(),
+        "No path for leg 1, from waypoint 1 to waypoint 2"
+    );
+
+    assert_eq!(
+        mesh.path_through(&waypoints(&[Vec2::new(3.0, 10.0)])),
+        Err(WaypointError::NotEnoughWaypoints)
+    );
+}